@@ -1,6 +1,6 @@
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
 use encrustant::{
-    board::Board,
+    board::{Board, square::Square},
     move_generator::{MoveGenerator, move_data::Move},
 };
 
@@ -29,5 +29,27 @@ pub fn make_unmake_move_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, make_unmake_move_benchmark);
+pub fn piece_at_benchmark(c: &mut Criterion) {
+    let board =
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+    c.bench_function("piece_at", |bencher| {
+        bencher.iter(|| {
+            for index in 0..64 {
+                black_box(board.piece_at(black_box(Square::from_index(index))));
+            }
+        });
+    });
+    c.bench_function("friendly_and_enemy_piece_at", |bencher| {
+        bencher.iter(|| {
+            for index in 0..64 {
+                let square = black_box(Square::from_index(index));
+                black_box(board.friendly_piece_at(square));
+                black_box(board.enemy_piece_at(square));
+            }
+        });
+    });
+}
+
+criterion_group!(benches, make_unmake_move_benchmark, piece_at_benchmark);
 criterion_main!(benches);
@@ -0,0 +1,16 @@
+#![no_main]
+
+use encrustant::move_generator::differential;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(board) = differential::position_from_bytes(data) {
+        if let Err(mismatch) = differential::compare(&board) {
+            let minimised = differential::minimise(&board);
+            panic!(
+                "{mismatch}\nminimised: {}",
+                differential::compare(&minimised).unwrap_err()
+            );
+        }
+    }
+});
@@ -8,14 +8,14 @@ pub struct BitBoard(u64);
 
 impl fmt::Display for BitBoard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for rank in (0..8).rev() {
-            for file in 0..8 {
-                if self.get(&Square::from_coords(rank, file)) {
+        for row in Self::display_rows(false) {
+            for (column, square) in row.iter().enumerate() {
+                if self.get(square) {
                     write!(f, "1")?;
                 } else {
                     write!(f, "0")?;
                 }
-                if file != 7 {
+                if column != 7 {
                     write!(f, " ")?;
                 }
             }
@@ -82,6 +82,22 @@ impl BitBoard {
     /// A bit board with only dark squares set.
     pub const DARK_SQUARES: Self = Self(0xAA55_AA55_AA55_AA55);
 
+    /// Squares from the top left to the bottom right of a printed board, row by row.
+    /// Rank 8 is at the top unless flipped.
+    pub(crate) fn display_rows(flipped: bool) -> [[Square; 8]; 8] {
+        let mut rows = [[Square::from_index(0); 8]; 8];
+        for (row, squares) in (0..8).zip(&mut rows) {
+            for (column, square) in (0..8).zip(squares) {
+                *square = if flipped {
+                    Square::from_coords(row, 7 - column)
+                } else {
+                    Square::from_coords(7 - row, column)
+                };
+            }
+        }
+        rows
+    }
+
     /// Bit board from a 64 bit number.
     #[must_use]
     pub const fn new(bits: u64) -> Self {
@@ -260,6 +276,21 @@ impl BitBoard {
         self.0.count_ones()
     }
 
+    /// Returns the bit board with the ranks flipped, so rank 1 becomes rank 8.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::bit_board::BitBoard;
+    ///
+    /// assert_eq!(BitBoard::RANK_2.flip_vertical(), BitBoard::RANK_7);
+    /// assert_eq!(BitBoard::LIGHT_SQUARES.flip_vertical(), BitBoard::DARK_SQUARES);
+    /// ```
+    #[must_use]
+    pub const fn flip_vertical(&self) -> Self {
+        Self(self.0.swap_bytes())
+    }
+
     /// Used to traverse subsets of a set.
     /// This takes the current subset and finds the next highest subset after it.
     ///
@@ -289,6 +320,18 @@ impl BitBoard {
         let hash = self.0.wrapping_mul(magic);
         (hash >> shift) as usize
     }
+
+    /// Packs the bits that are set in `mask` into the low bits, using the BMI2 `pext` instruction.
+    #[must_use]
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    #[allow(
+        clippy::cast_possible_truncation,
+        reason = "usize is 64 bits on x86_64"
+    )]
+    pub fn pext_index(&self, mask: Self) -> usize {
+        // SAFETY: BMI2 is enabled at compile time
+        (unsafe { core::arch::x86_64::_pext_u64(self.0, mask.0) }) as usize
+    }
 }
 
 macro_rules! implement_op {
@@ -0,0 +1,463 @@
+use core::fmt;
+use std::num::NonZeroU32;
+
+use crate::move_generator::MoveGenerator;
+
+use super::{
+    Board,
+    bit_board::BitBoard,
+    game_state::{CastlingRights, GameState},
+    piece::Piece,
+    square::Square,
+};
+
+/// Reasons a position can not be reached in a legal game.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PositionError {
+    /// A side has no king.
+    MissingKing {
+        /// Whether the king is white.
+        white: bool,
+    },
+
+    /// A side has more than one king.
+    TooManyKings {
+        /// Whether the kings are white.
+        white: bool,
+    },
+
+    /// The kings are adjacent.
+    TouchingKings,
+
+    /// There is a pawn on the first or last rank.
+    PawnOnBackRank(Square),
+
+    /// A side has more than 8 pawns.
+    TooManyPawns {
+        /// Whether the pawns are white.
+        white: bool,
+    },
+
+    /// A side has more than 16 pieces.
+    TooManyPieces {
+        /// Whether the pieces are white.
+        white: bool,
+    },
+
+    /// A side has more promoted pieces than it has missing pawns.
+    TooManyPromotedPieces {
+        /// Whether the pieces are white.
+        white: bool,
+    },
+
+    /// A castling right, given as its FEN character, is set but the king or rook is not on its starting square.
+    InvalidCastlingRights(char),
+
+    /// The en passant square could not have been skipped by a pawn moving two squares up,
+    /// or the half move clock was not reset by that pawn move.
+    InvalidEnPassant,
+
+    /// Side to move is in triple check or more.
+    TooManyChecks,
+
+    /// Enemy is in check.
+    EnemyInCheck,
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let colour = |white| if white { "white" } else { "black" };
+        match self {
+            Self::MissingKing { white } => write!(f, "{} has no king", colour(*white)),
+            Self::TooManyKings { white } => write!(f, "{} has more than one king", colour(*white)),
+            Self::TouchingKings => write!(f, "the kings are adjacent"),
+            Self::PawnOnBackRank(square) => write!(f, "pawn on back rank at {square}"),
+            Self::TooManyPawns { white } => write!(f, "{} has more than 8 pawns", colour(*white)),
+            Self::TooManyPieces { white } => {
+                write!(f, "{} has more than 16 pieces", colour(*white))
+            }
+            Self::TooManyPromotedPieces { white } => write!(
+                f,
+                "{} has more promoted pieces than missing pawns",
+                colour(*white)
+            ),
+            Self::InvalidCastlingRights(right) => write!(
+                f,
+                "castling right {right} is set but the king or rook has moved"
+            ),
+            Self::InvalidEnPassant => write!(f, "no pawn could have moved two squares up"),
+            Self::TooManyChecks => write!(f, "side to move is in check by more than 2 pieces"),
+            Self::EnemyInCheck => write!(f, "side not to move is in check"),
+        }
+    }
+}
+
+impl std::error::Error for PositionError {}
+
+/// Sets up a position piece by piece.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::board::{builder::BoardBuilder, piece::Piece, square::Square};
+///
+/// let board = BoardBuilder::new()
+///     .piece(Square::from_notation("e1").unwrap(), Piece::WhiteKing)
+///     .piece(Square::from_notation("e2").unwrap(), Piece::WhitePawn)
+///     .piece(Square::from_notation("e8").unwrap(), Piece::BlackKing)
+///     .white_to_move(false)
+///     .build()
+///     .unwrap();
+/// assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 b - - 0 1");
+/// ```
+#[derive(Clone, Copy)]
+pub struct BoardBuilder {
+    bit_boards: [BitBoard; 12],
+    white_to_move: bool,
+    castling_rights: CastlingRights,
+    en_passant_square: Option<Square>,
+    half_move_clock: u32,
+    full_move_counter: NonZeroU32,
+}
+
+impl BoardBuilder {
+    /// Starts from an empty board with white to move and no castling rights.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            bit_boards: [BitBoard::EMPTY; 12],
+            white_to_move: true,
+            castling_rights: CastlingRights::new(false, false, false, false),
+            en_passant_square: None,
+            half_move_clock: 0,
+            full_move_counter: NonZeroU32::MIN,
+        }
+    }
+
+    /// Starts from an existing position.
+    #[must_use]
+    pub const fn from_board(board: &Board) -> Self {
+        Self {
+            bit_boards: board.bit_boards,
+            white_to_move: board.white_to_move,
+            castling_rights: board.game_state.castling_rights,
+            en_passant_square: board.game_state.en_passant_square,
+            half_move_clock: board.game_state.half_move_clock,
+            full_move_counter: board.full_move_counter,
+        }
+    }
+
+    /// Puts a piece on a square, replacing any piece already there.
+    #[must_use]
+    pub fn piece(self, square: Square, piece: Piece) -> Self {
+        let mut builder = self.remove(square);
+        builder.bit_boards[piece as usize].set(&square);
+        builder
+    }
+
+    /// Removes any piece on a square.
+    #[must_use]
+    pub fn remove(mut self, square: Square) -> Self {
+        for bit_board in &mut self.bit_boards {
+            bit_board.unset(&square);
+        }
+        self
+    }
+
+    /// Sets whose turn it is.
+    #[must_use]
+    pub const fn white_to_move(mut self, white_to_move: bool) -> Self {
+        self.white_to_move = white_to_move;
+        self
+    }
+
+    /// Sets the castling rights.
+    #[must_use]
+    pub const fn castling_rights(mut self, castling_rights: CastlingRights) -> Self {
+        self.castling_rights = castling_rights;
+        self
+    }
+
+    /// Sets the square which can be captured by en passant.
+    #[must_use]
+    pub const fn en_passant_square(mut self, en_passant_square: Option<Square>) -> Self {
+        self.en_passant_square = en_passant_square;
+        self
+    }
+
+    /// Sets the half move clock.
+    #[must_use]
+    pub const fn half_move_clock(mut self, half_move_clock: u32) -> Self {
+        self.half_move_clock = half_move_clock;
+        self
+    }
+
+    /// Sets the number of full moves.
+    #[must_use]
+    pub const fn full_move_counter(mut self, full_move_counter: NonZeroU32) -> Self {
+        self.full_move_counter = full_move_counter;
+        self
+    }
+
+    /// Creates the board without checking that it is valid.
+    /// Move generation and search can misbehave if it is not.
+    #[must_use]
+    pub fn build_unchecked(self) -> Board {
+        Board::from_bit_boards(
+            self.white_to_move,
+            self.bit_boards,
+            self.full_move_counter,
+            GameState {
+                en_passant_square: self.en_passant_square,
+                castling_rights: self.castling_rights,
+                half_move_clock: self.half_move_clock,
+                captured: None,
+            },
+        )
+    }
+
+    /// Creates the board.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the position can not be reached in a legal game, see [`Board::validate`].
+    pub fn build(self) -> Result<Board, PositionError> {
+        let board = self.build_unchecked();
+        board.validate()?;
+        Ok(board)
+    }
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    /// Checks that the position could be reached in a legal game.
+    ///
+    /// This catches everything move generation relies on, but does not prove a position is reachable.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the first problem found.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        for (white, pieces) in [(true, Piece::WHITE_PIECES), (false, Piece::BLACK_PIECES)] {
+            let [pawns, knights, bishops, rooks, queens, kings] =
+                pieces.map(|piece| self.get_bit_board(piece).count());
+
+            if kings == 0 {
+                return Err(PositionError::MissingKing { white });
+            }
+            if kings > 1 {
+                return Err(PositionError::TooManyKings { white });
+            }
+
+            let back_ranks = BitBoard::RANK_1 | BitBoard::RANK_8;
+            let pawns_on_back_rank = *self.get_bit_board(pieces[0]) & back_ranks;
+            if pawns_on_back_rank.is_not_empty() {
+                return Err(PositionError::PawnOnBackRank(
+                    pawns_on_back_rank.first_square(),
+                ));
+            }
+
+            if pawns > 8 {
+                return Err(PositionError::TooManyPawns { white });
+            }
+            if pawns + knights + bishops + rooks + queens + kings > 16 {
+                return Err(PositionError::TooManyPieces { white });
+            }
+            let promoted = knights.saturating_sub(2)
+                + bishops.saturating_sub(2)
+                + rooks.saturating_sub(2)
+                + queens.saturating_sub(1);
+            if promoted > 8 - pawns {
+                return Err(PositionError::TooManyPromotedPieces { white });
+            }
+        }
+
+        let white_king_square = self.get_bit_board(Piece::WhiteKing).first_square();
+        let black_king_square = self.get_bit_board(Piece::BlackKing).first_square();
+        if MoveGenerator::king_attack_bit_board(white_king_square)
+            .overlaps(&black_king_square.bit_board())
+        {
+            return Err(PositionError::TouchingKings);
+        }
+
+        self.validate_castling_rights()?;
+        self.validate_en_passant()?;
+        self.validate_checks()
+    }
+
+    pub(super) fn validate_castling_rights(&self) -> Result<(), PositionError> {
+        let castling_rights = self.game_state.castling_rights;
+        for (right, is_set, king, rook, king_square, rook_square) in [
+            (
+                'K',
+                castling_rights.get_white_king_side(),
+                Piece::WhiteKing,
+                Piece::WhiteRook,
+                "e1",
+                "h1",
+            ),
+            (
+                'Q',
+                castling_rights.get_white_queen_side(),
+                Piece::WhiteKing,
+                Piece::WhiteRook,
+                "e1",
+                "a1",
+            ),
+            (
+                'k',
+                castling_rights.get_black_king_side(),
+                Piece::BlackKing,
+                Piece::BlackRook,
+                "e8",
+                "h8",
+            ),
+            (
+                'q',
+                castling_rights.get_black_queen_side(),
+                Piece::BlackKing,
+                Piece::BlackRook,
+                "e8",
+                "a8",
+            ),
+        ] {
+            if is_set
+                && !(self
+                    .get_bit_board(king)
+                    .get(&Square::from_notation(king_square).unwrap())
+                    && self
+                        .get_bit_board(rook)
+                        .get(&Square::from_notation(rook_square).unwrap()))
+            {
+                return Err(PositionError::InvalidCastlingRights(right));
+            }
+        }
+        Ok(())
+    }
+
+    pub(super) fn validate_en_passant(&self) -> Result<(), PositionError> {
+        let Some(en_passant_square) = self.game_state.en_passant_square else {
+            return Ok(());
+        };
+
+        let (rank, enemy_pawn) = if self.white_to_move {
+            (5, Piece::BlackPawn)
+        } else {
+            (2, Piece::WhitePawn)
+        };
+        if en_passant_square.rank() != rank || self.game_state.half_move_clock != 0 {
+            return Err(PositionError::InvalidEnPassant);
+        }
+
+        // The pawn moved from behind the en passant square, to in front of it
+        let direction = if self.white_to_move { 1 } else { -1 };
+        let from = en_passant_square.up(direction);
+        let to = en_passant_square.down(direction);
+        if self.piece_at(from).is_some()
+            || self.piece_at(en_passant_square).is_some()
+            || !self.get_bit_board(enemy_pawn).get(&to)
+        {
+            return Err(PositionError::InvalidEnPassant);
+        }
+        Ok(())
+    }
+
+    fn validate_checks(&self) -> Result<(), PositionError> {
+        let occupied_squares = self
+            .bit_boards
+            .iter()
+            .fold(BitBoard::EMPTY, |occupied, bit_board| occupied | *bit_board);
+
+        for (white, pieces, enemy_pieces) in [
+            (true, Piece::WHITE_PIECES, Piece::BLACK_PIECES),
+            (false, Piece::BLACK_PIECES, Piece::WHITE_PIECES),
+        ] {
+            let bit_board = |piece: Piece| *self.get_bit_board(piece);
+            let checkers = MoveGenerator::calculate_checkers(
+                white,
+                bit_board(pieces[5]).first_square(),
+                bit_board(enemy_pieces[0]),
+                bit_board(enemy_pieces[1]),
+                bit_board(enemy_pieces[2]) | bit_board(enemy_pieces[4]),
+                bit_board(enemy_pieces[3]) | bit_board(enemy_pieces[4]),
+                occupied_squares,
+            );
+
+            if white == self.white_to_move {
+                if checkers.count() >= 3 {
+                    return Err(PositionError::TooManyChecks);
+                }
+            } else if checkers.is_not_empty() {
+                return Err(PositionError::EnemyInCheck);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{
+        Board,
+        builder::{BoardBuilder, PositionError},
+        piece::Piece,
+        square::Square,
+    };
+
+    #[test]
+    fn builder_matches_fen() {
+        let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+        let mut builder = BoardBuilder::new().castling_rights(board.game_state.castling_rights);
+        for square in (0..64).map(Square::from_index) {
+            if let Some(piece) = board.piece_at(square) {
+                builder = builder.piece(square, piece);
+            }
+        }
+        assert_eq!(builder.build().unwrap().to_fen(), Board::START_POSITION_FEN);
+    }
+
+    #[test]
+    fn invalid_positions() {
+        let e1 = Square::from_notation("e1").unwrap();
+        let e8 = Square::from_notation("e8").unwrap();
+        let kings = BoardBuilder::new()
+            .piece(e1, Piece::WhiteKing)
+            .piece(e8, Piece::BlackKing);
+        assert!(kings.build().is_ok());
+
+        assert_eq!(
+            kings.remove(e8).build().err(),
+            Some(PositionError::MissingKing { white: false })
+        );
+        assert_eq!(
+            kings.piece(e8.down(1), Piece::WhiteKing).build().err(),
+            Some(PositionError::TooManyKings { white: true })
+        );
+
+        let mut pawns = kings;
+        for file in 0..8 {
+            pawns = pawns.piece(Square::from_coords(1, file), Piece::WhitePawn);
+        }
+        assert!(pawns.build().is_ok());
+        assert_eq!(
+            pawns
+                .piece(Square::from_notation("a3").unwrap(), Piece::WhitePawn)
+                .build()
+                .err(),
+            Some(PositionError::TooManyPawns { white: true })
+        );
+        assert_eq!(
+            pawns
+                .piece(Square::from_notation("a3").unwrap(), Piece::WhiteQueen)
+                .piece(Square::from_notation("b3").unwrap(), Piece::WhiteQueen)
+                .build()
+                .err(),
+            Some(PositionError::TooManyPromotedPieces { white: true })
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{move_generator::move_data::Move, search::zobrist::Zobrist, uci};
+
+use super::{Board, bit_board::BitBoard, piece::Piece, square::Square};
+
+impl Board {
+    /// Recomputes everything that making or unmaking a move updates incrementally,
+    /// panicking with the position and move if any of it does not match.
+    ///
+    /// `action` describes what was just done with the move, such as "making".
+    pub(crate) fn validate_incremental_state(&self, move_data: Move, action: &str) {
+        let fail = |what: &str| -> ! {
+            panic!(
+                "{what} is wrong after {action} {} in {self}",
+                uci::encode_move(move_data)
+            )
+        };
+
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            let mut pieces = Piece::ALL_PIECES
+                .into_iter()
+                .filter(|piece| self.get_bit_board(*piece).get(&square));
+            let piece = pieces.next();
+            if pieces.next().is_some() {
+                fail(&format!("bit boards overlapping on {square}"));
+            }
+            if self.piece_at(square) != piece {
+                fail(&format!("mailbox on {square}"));
+            }
+        }
+
+        let occupancy = |pieces: [Piece; 6]| {
+            pieces
+                .into_iter()
+                .fold(BitBoard::EMPTY, |occupancy, piece| {
+                    occupancy | *self.get_bit_board(piece)
+                })
+        };
+        if self.white_occupancy() != occupancy(Piece::WHITE_PIECES) {
+            fail("white occupancy");
+        }
+        if self.black_occupancy() != occupancy(Piece::BLACK_PIECES) {
+            fail("black occupancy");
+        }
+
+        if self.zobrist() != Zobrist::compute(self) {
+            fail("zobrist key");
+        }
+        if self.pawn_zobrist() != Zobrist::pawn_key(self) {
+            fail("pawn zobrist key");
+        }
+        if self.minor_piece_zobrist() != Zobrist::minor_piece_key(self) {
+            fail("minor piece zobrist key");
+        }
+        if self.material_zobrist() != Zobrist::material_key(self) {
+            fail("material zobrist key");
+        }
+
+        // Only the parts of the game state that moves update, as illegal moves can leave the position invalid
+        if let Err(error) = self
+            .validate_castling_rights()
+            .and_then(|()| self.validate_en_passant())
+        {
+            fail(&format!("game state ({error})"));
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use core::fmt::Write;
+
+use crate::{move_generator::move_data::Move, search::encoded_move::EncodedMove};
+
+use super::{Board, bit_board::BitBoard, piece::Piece, square::Square};
+
+/// Characters used to draw a text diagram.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Charset {
+    /// FEN piece letters, `.` for empty squares.
+    Ascii,
+
+    /// Unicode chess symbols, `·` for empty squares.
+    Unicode,
+}
+
+/// How a diagram should be drawn.
+#[derive(Clone, Default)]
+pub struct DiagramOptions {
+    /// Whether the board is seen from black's side.
+    pub flipped: bool,
+
+    /// Whether ranks and files are labelled.
+    pub coordinates: bool,
+
+    /// Move to highlight, usually the last move played.
+    pub last_move: Option<Move>,
+
+    /// Arrows from one square to another, such as the moves of a principal variation.
+    /// Text diagrams do not draw arrows.
+    pub arrows: Vec<(Square, Square)>,
+}
+
+impl DiagramOptions {
+    /// Adds an arrow for every move in a line, stopping at the first null move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{board::diagram::DiagramOptions, search::pv::Pv};
+    ///
+    /// let options = DiagramOptions::default().with_line(Pv::new().best_line().copied());
+    /// assert!(options.arrows.is_empty());
+    /// ```
+    #[must_use]
+    pub fn with_line(mut self, line: impl IntoIterator<Item = EncodedMove>) -> Self {
+        self.arrows.extend(
+            line.into_iter()
+                .take_while(|encoded_move| !encoded_move.is_none())
+                .map(|encoded_move| (encoded_move.from(), encoded_move.to())),
+        );
+        self
+    }
+
+    fn highlighted(&self) -> BitBoard {
+        self.last_move.map_or(BitBoard::EMPTY, |move_data| {
+            move_data.from.bit_board() | move_data.to.bit_board()
+        })
+    }
+
+    /// Squares from the top left to the bottom right of the diagram, row by row.
+    fn rows(&self) -> [[Square; 8]; 8] {
+        BitBoard::display_rows(self.flipped)
+    }
+}
+
+const fn file_label(square: Square) -> char {
+    (b'a' + square.file().cast_unsigned()) as char
+}
+
+const fn rank_label(square: Square) -> char {
+    (b'1' + square.rank().cast_unsigned()) as char
+}
+
+const SQUARE_SIZE: i32 = 45;
+const MARGIN: i32 = 20;
+
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const LIGHT_SQUARE_HIGHLIGHTED: &str = "#cdd16a";
+const DARK_SQUARE_HIGHLIGHTED: &str = "#aaa23b";
+const ARROW: &str = "#15781b";
+
+impl Board {
+    /// Draws the board as text, with rank 8 at the top unless flipped.
+    /// Highlighted squares are surrounded by brackets, and trailing spaces are removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, diagram::{Charset, DiagramOptions}};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+    /// let diagram = board.to_text_diagram(Charset::Ascii, &DiagramOptions::default());
+    /// assert_eq!(diagram.lines().nth(6), Some(" .  .  .  .  P  .  .  ."));
+    /// ```
+    #[must_use]
+    pub fn to_text_diagram(&self, charset: Charset, options: &DiagramOptions) -> String {
+        let highlighted = options.highlighted();
+
+        let rows = options.rows();
+
+        let mut diagram = String::new();
+        for row in rows {
+            if options.coordinates {
+                diagram.push(rank_label(row[0]));
+                diagram.push(' ');
+            }
+            for square in row {
+                let is_highlighted = highlighted.get(&square);
+                diagram.push(if is_highlighted { '[' } else { ' ' });
+                diagram.push(match (self.piece_at(square), charset) {
+                    (Some(piece), Charset::Ascii) => piece.to_fen_char(),
+                    (Some(piece), Charset::Unicode) => piece.to_unicode_char(),
+                    (None, Charset::Ascii) => '.',
+                    (None, Charset::Unicode) => '·',
+                });
+                diagram.push(if is_highlighted { ']' } else { ' ' });
+            }
+            diagram.truncate(diagram.trim_end_matches(' ').len());
+            diagram.push('\n');
+        }
+
+        if options.coordinates {
+            diagram.push_str("  ");
+            for square in rows[0] {
+                diagram.push(' ');
+                diagram.push(file_label(square));
+                diagram.push(' ');
+            }
+            diagram.truncate(diagram.trim_end_matches(' ').len());
+            diagram.push('\n');
+        }
+
+        diagram
+    }
+
+    /// Draws the board as a standalone SVG image.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, diagram::DiagramOptions};
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// let svg = board.to_svg(&DiagramOptions::default());
+    /// assert!(svg.starts_with("<svg"));
+    /// assert!(svg.ends_with("</svg>\n"));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Should not panic.
+    #[must_use]
+    pub fn to_svg(&self, options: &DiagramOptions) -> String {
+        let margin = if options.coordinates { MARGIN } else { 0 };
+        let size = SQUARE_SIZE * 8 + margin * 2;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {size} {size}">"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<defs><marker id="arrowhead" markerWidth="2.5" markerHeight="2.5" refX="0" refY="1.25" orient="auto"><path d="M0,0 L2.5,1.25 L0,2.5 z" fill="{ARROW}" fill-opacity="0.8"/></marker></defs>"#
+        )
+        .unwrap();
+
+        if options.coordinates {
+            writeln!(
+                svg,
+                r##"<rect width="{size}" height="{size}" fill="#212121"/>"##
+            )
+            .unwrap();
+
+            let label = |svg: &mut String, x: i32, y: i32, text: char| {
+                writeln!(
+                    svg,
+                    r##"<text x="{x}" y="{y}" font-size="14" font-family="sans-serif" text-anchor="middle" dominant-baseline="central" fill="#e5e5e5">{text}</text>"##
+                )
+                .unwrap();
+            };
+            let rows = options.rows();
+            for ((offset, row), bottom) in (0..).zip(rows).zip(rows[7]) {
+                let centre = margin + offset * SQUARE_SIZE + SQUARE_SIZE / 2;
+                label(&mut svg, centre, size - margin / 2, file_label(bottom));
+                label(&mut svg, margin / 2, centre, rank_label(row[0]));
+            }
+        }
+
+        let highlighted = options.highlighted();
+        for (y, row) in (0..).zip(options.rows()) {
+            for (x, square) in (0..).zip(row) {
+                let (x, y) = (margin + x * SQUARE_SIZE, margin + y * SQUARE_SIZE);
+                Self::draw_square(&mut svg, x, y, square, self.piece_at(square), highlighted);
+            }
+        }
+
+        for &(from, to) in &options.arrows {
+            Self::draw_arrow(&mut svg, options, margin, from, to);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn draw_square(
+        svg: &mut String,
+        x: i32,
+        y: i32,
+        square: Square,
+        piece: Option<Piece>,
+        highlighted: BitBoard,
+    ) {
+        let is_light = (square.rank() + square.file()) % 2 == 1;
+        let fill = match (is_light, highlighted.get(&square)) {
+            (true, false) => LIGHT_SQUARE,
+            (false, false) => DARK_SQUARE,
+            (true, true) => LIGHT_SQUARE_HIGHLIGHTED,
+            (false, true) => DARK_SQUARE_HIGHLIGHTED,
+        };
+        writeln!(
+            svg,
+            r#"<rect x="{x}" y="{y}" width="{SQUARE_SIZE}" height="{SQUARE_SIZE}" fill="{fill}"/>"#
+        )
+        .unwrap();
+
+        if let Some(piece) = piece {
+            // The solid symbols are used for both colours so that white pieces can be filled in
+            let symbol = Piece::BLACK_PIECES[piece as usize % 6].to_unicode_char();
+            let fill = if piece.is_white() {
+                "#ffffff"
+            } else {
+                "#000000"
+            };
+            writeln!(
+                svg,
+                r##"<text x="{}" y="{}" font-size="38" font-family="serif" text-anchor="middle" dominant-baseline="central" fill="{fill}" stroke="#000000" stroke-width="1">{symbol}</text>"##,
+                x + SQUARE_SIZE / 2,
+                y + SQUARE_SIZE / 2,
+            )
+            .unwrap();
+        }
+    }
+
+    fn draw_arrow(
+        svg: &mut String,
+        options: &DiagramOptions,
+        margin: i32,
+        from: Square,
+        to: Square,
+    ) {
+        // Stop short so that the arrowhead ends in the centre of the square
+        const HEAD_LENGTH: f64 = 20.0;
+
+        let centre = |square: Square| {
+            let (column, row) = if options.flipped {
+                (7 - square.file(), square.rank())
+            } else {
+                (square.file(), 7 - square.rank())
+            };
+            (
+                f64::from(margin + i32::from(column) * SQUARE_SIZE + SQUARE_SIZE / 2),
+                f64::from(margin + i32::from(row) * SQUARE_SIZE + SQUARE_SIZE / 2),
+            )
+        };
+        let (x1, y1) = centre(from);
+        let (x2, y2) = centre(to);
+
+        let length = (x2 - x1).hypot(y2 - y1);
+        if length == 0.0 {
+            return;
+        }
+        let x2 = (HEAD_LENGTH / length).mul_add(x1 - x2, x2);
+        let y2 = (HEAD_LENGTH / length).mul_add(y1 - y2, y2);
+
+        writeln!(
+            svg,
+            r#"<line x1="{x1}" y1="{y1}" x2="{x2:.1}" y2="{y2:.1}" stroke="{ARROW}" stroke-width="8" stroke-opacity="0.8" stroke-linecap="round" marker-end="url(#arrowhead)"/>"#
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{
+            Board,
+            diagram::{Charset, DiagramOptions},
+            square::Square,
+        },
+        move_generator::move_data::{Flag, Move},
+    };
+
+    #[test]
+    fn text_diagram() {
+        let board =
+            Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        let options = DiagramOptions {
+            coordinates: true,
+            last_move: Some(Move {
+                from: Square::from_notation("e2").unwrap(),
+                to: Square::from_notation("e4").unwrap(),
+                flag: Flag::PawnTwoUp,
+            }),
+            ..DiagramOptions::default()
+        };
+        assert_eq!(
+            board.to_text_diagram(Charset::Ascii, &options),
+            "\
+8  r  n  b  q  k  b  n  r
+7  p  p  p  p  p  p  p  p
+6  .  .  .  .  .  .  .  .
+5  .  .  .  .  .  .  .  .
+4  .  .  .  . [P] .  .  .
+3  .  .  .  .  .  .  .  .
+2  P  P  P  P [.] P  P  P
+1  R  N  B  Q  K  B  N  R
+   a  b  c  d  e  f  g  h
+"
+        );
+
+        let flipped = DiagramOptions {
+            flipped: true,
+            ..options
+        };
+        let diagram = board.to_text_diagram(Charset::Unicode, &flipped);
+        assert_eq!(diagram.lines().next(), Some("1  ♖  ♘  ♗  ♔  ♕  ♗  ♘  ♖"));
+        assert_eq!(diagram.lines().last(), Some("   h  g  f  e  d  c  b  a"));
+    }
+
+    #[test]
+    fn svg_diagram() {
+        let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+        let options = DiagramOptions {
+            coordinates: true,
+            arrows: vec![(
+                Square::from_notation("g1").unwrap(),
+                Square::from_notation("f3").unwrap(),
+            )],
+            ..DiagramOptions::default()
+        };
+        let svg = board.to_svg(&options);
+        assert_eq!(svg.matches("<rect").count(), 65);
+        assert_eq!(svg.matches("font-family=\"serif\"").count(), 32);
+        assert_eq!(svg.matches("<line").count(), 1);
+    }
+}
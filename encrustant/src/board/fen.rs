@@ -1,15 +1,16 @@
-use crate::move_generator::MoveGenerator;
+use core::fmt;
 
 use super::{
     Board,
     bit_board::BitBoard,
-    game_state::{CastlingRights, GameState},
+    builder::{BoardBuilder, PositionError},
+    game_state::CastlingRights,
     piece::Piece,
     square::Square,
 };
 
 /// Errors that can occur when parsing a FEN string.
-#[derive(Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum FenParseErr {
     /// The position section of the FEN string is missing.
     MissingPosition,
@@ -17,21 +18,12 @@ pub enum FenParseErr {
     /// An invalid piece character was encountered in the position section of the FEN string.
     InvalidPiece,
 
-    /// A king or both kings are missing.
-    MissingKing,
-
-    /// There are more than 2 kings.
-    MultipleKings,
-
-    /// The kings are adjacent.
-    TouchingKings,
-
-    /// There is a pawn on the rank where it should have promoted.
-    PawnOnPromotionRank,
-
-    /// An invalid digit was encountered in the position section (e.g., a number greater than 8 or incorrect rank structure).
+    /// A digit in the position section is 0 or greater than 8.
     InvalidDigit,
 
+    /// A rank does not have exactly 8 squares, or there are not exactly 8 ranks.
+    InvalidRankLength,
+
     /// The side to move ("w" or "b") is missing from the FEN string.
     MissingSideToMove,
 
@@ -59,261 +51,248 @@ pub enum FenParseErr {
     /// The castling rights section is missing from the FEN string.
     MissingCastling,
 
-    /// Side to move is in triple check or more.
-    TooManyChecks,
+    /// The castling rights section is not "-" or a combination of `KQkq`.
+    InvalidCastling,
 
-    /// Enemy is in check.
-    EnemyInCheck,
+    /// The FEN string is well formed, but the position is impossible.
+    InvalidPosition(PositionError),
 }
 
-impl Board {
-    /// The starting position FEN in standard chess.
-    pub const START_POSITION_FEN: &'static str =
-        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
-
-    /// Creates a Board from Forsyth-Edwards Notation.
-    #[must_use]
-    pub fn from_fen(fen: &str) -> Result<Self, FenParseErr> {
-        let mut components = fen.split_whitespace();
+impl fmt::Display for FenParseErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPosition => write!(f, "missing position"),
+            Self::InvalidPiece => write!(f, "invalid piece"),
+            Self::InvalidDigit => write!(f, "invalid digit"),
+            Self::InvalidRankLength => write!(f, "rank does not have 8 squares"),
+            Self::MissingSideToMove => write!(f, "missing side to move"),
+            Self::InvalidSideToMove => write!(f, "invalid side to move"),
+            Self::MissingHalfMoveClock => write!(f, "missing half move clock"),
+            Self::InvalidHalfMoveClock => write!(f, "invalid half move clock"),
+            Self::MissingFullMoveCounter => write!(f, "missing full move counter"),
+            Self::InvalidFullMoveCounter => write!(f, "invalid full move counter"),
+            Self::MissingEnPassant => write!(f, "missing en passant square"),
+            Self::InvalidEnPassant => write!(f, "invalid en passant square"),
+            Self::MissingCastling => write!(f, "missing castling rights"),
+            Self::InvalidCastling => write!(f, "invalid castling rights"),
+            Self::InvalidPosition(error) => write!(f, "{error}"),
+        }
+    }
+}
 
-        let mut bit_boards = [BitBoard::EMPTY; 12];
+/// A FEN parsing error, and where it was found.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FenError {
+    /// What is wrong.
+    pub kind: FenParseErr,
 
-        let (mut rank, mut file) = (7, 0);
+    /// Byte index of the offending character in the FEN string.
+    /// This is the length of the string if a section is missing.
+    pub index: usize,
+}
 
-        let position = {
-            let component = components.next();
-            if let Some(component) = component {
-                component.chars()
-            } else {
-                return Err(FenParseErr::MissingPosition);
-            }
-        };
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at character {}", self.kind, self.index)
+    }
+}
 
-        let mut white_king_square = None;
-        let mut black_king_square = None;
-        for character in position {
-            if character == '/' {
-                continue;
-            }
+impl std::error::Error for FenError {}
 
-            if let Some(digit) = character.to_digit(10) {
-                if digit > 8 {
-                    return Err(FenParseErr::InvalidDigit);
-                }
-                file += digit as i8;
-                if file > 8 {
-                    return Err(FenParseErr::InvalidDigit);
-                }
-            } else if let Some(piece) = Piece::from_fen_char(&character) {
-                let square = Square::from_coords(rank, file);
-
-                match piece {
-                    Piece::WhiteKing => {
-                        if white_king_square.is_some() {
-                            return Err(FenParseErr::MultipleKings);
-                        }
-                        white_king_square = Some(square);
-                    }
-                    Piece::BlackKing => {
-                        if black_king_square.is_some() {
-                            return Err(FenParseErr::MultipleKings);
-                        }
-                        black_king_square = Some(square);
-                    }
-                    Piece::WhitePawn => {
-                        if BitBoard::RANK_8.get(&square) {
-                            return Err(FenParseErr::PawnOnPromotionRank);
-                        }
-                    }
-                    Piece::BlackPawn => {
-                        if BitBoard::RANK_1.get(&square) {
-                            return Err(FenParseErr::PawnOnPromotionRank);
-                        }
-                    }
-                    _ => {}
-                }
+/// Splits a FEN string into its sections, along with the index each one starts at.
+fn sections(fen: &str) -> Vec<(usize, &str)> {
+    fen.split_whitespace()
+        .map(|section| (section.as_ptr().addr() - fen.as_ptr().addr(), section))
+        .collect()
+}
 
-                bit_boards[piece as usize].set(&square);
+/// Index of the last character in the position section placing a piece on any of `bit_board`.
+fn last_index(square_indices: &[usize; 64], mut bit_board: BitBoard) -> usize {
+    let mut index = 0;
+    while bit_board.is_not_empty() {
+        index = index.max(square_indices[bit_board.pop_square().usize()]);
+    }
+    index
+}
 
+/// Places the pieces from the position section of a FEN string.
+/// Also returns the index of the character describing each square.
+fn parse_position(
+    position_index: usize,
+    position: &str,
+) -> Result<(BoardBuilder, [usize; 64]), FenError> {
+    let error = |kind, index| Err(FenError { kind, index });
+
+    let mut builder = BoardBuilder::new();
+
+    // Where each square was described, to point at in errors
+    let mut square_indices = [position_index; 64];
+
+    let (mut rank, mut file) = (7, 0);
+    for (offset, character) in position.char_indices() {
+        let index = position_index + offset;
+        if character == '/' {
+            if file != 8 || rank == 0 {
+                return error(FenParseErr::InvalidRankLength, index);
+            }
+            rank -= 1;
+            file = 0;
+        } else if let Some(digit) = character.to_digit(10) {
+            if digit == 0 || digit > 8 {
+                return error(FenParseErr::InvalidDigit, index);
+            }
+            for _ in 0..digit {
+                if file == 8 {
+                    return error(FenParseErr::InvalidRankLength, index);
+                }
+                square_indices[Square::from_coords(rank, file).usize()] = index;
                 file += 1;
-            } else {
-                return Err(FenParseErr::InvalidPiece);
             }
-
+        } else if let Some(piece) = Piece::from_fen_char(&character) {
             if file == 8 {
-                if rank == 0 {
-                    break;
-                }
-                rank -= 1;
-                file = 0;
+                return error(FenParseErr::InvalidRankLength, index);
             }
+            let square = Square::from_coords(rank, file);
+            square_indices[square.usize()] = index;
+            builder = builder.piece(square, piece);
+            file += 1;
+        } else {
+            return error(FenParseErr::InvalidPiece, index);
         }
+    }
+    if rank != 0 || file != 8 {
+        return error(
+            FenParseErr::InvalidRankLength,
+            position_index + position.len(),
+        );
+    }
 
-        if white_king_square.is_none() || black_king_square.is_none() {
-            return Err(FenParseErr::MissingKing);
-        }
+    Ok((builder, square_indices))
+}
 
-        if MoveGenerator::king_attack_bit_board(white_king_square.unwrap())
-            .overlaps(&black_king_square.unwrap().bit_board())
-        {
-            return Err(FenParseErr::TouchingKings);
-        }
+impl Board {
+    /// The starting position FEN in standard chess.
+    pub const START_POSITION_FEN: &'static str =
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
-        let white_to_move = match components.next() {
-            Some("w") => true,
-            Some("b") => false,
-            None => return Err(FenParseErr::MissingSideToMove),
-            _ => return Err(FenParseErr::InvalidSideToMove),
+    /// Creates a Board from Forsyth-Edwards Notation.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `fen` is malformed or the position is impossible, along with where the problem is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, fen::FenParseErr, builder::PositionError};
+    ///
+    /// let error = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w K - 0 1").err().unwrap();
+    /// assert_eq!(error.kind, FenParseErr::InvalidPosition(PositionError::InvalidCastlingRights('K')));
+    /// assert_eq!(error.index, 22);
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let sections = sections(fen);
+        let section = |index: usize, missing: FenParseErr| {
+            sections.get(index).copied().ok_or(FenError {
+                kind: missing,
+                index: fen.len(),
+            })
         };
+        let error = |kind, index| Err(FenError { kind, index });
 
-        let castling_rights = CastlingRights::from_fen_section({
-            if let Some(component) = components.next() {
-                component
-            } else {
-                return Err(FenParseErr::MissingCastling);
-            }
-        });
+        let (position_index, position) = section(0, FenParseErr::MissingPosition)?;
 
-        let en_passant = {
-            if let Some(en_passant) = components.next() {
-                en_passant
-            } else {
-                return Err(FenParseErr::MissingEnPassant);
-            }
+        let (builder, square_indices) = parse_position(position_index, position)?;
+
+        let (side_to_move_index, side_to_move) = section(1, FenParseErr::MissingSideToMove)?;
+        let white_to_move = match side_to_move {
+            "w" => true,
+            "b" => false,
+            _ => return error(FenParseErr::InvalidSideToMove, side_to_move_index),
         };
-        let en_passant_square = if en_passant == "-" {
-            None
-        } else {
-            let en_passant_square = Square::from_notation(en_passant);
-            if let Ok(en_passant_square) = en_passant_square {
-                if en_passant_square.rank() != if white_to_move { 5 } else { 2 } {
-                    return Err(FenParseErr::InvalidEnPassant);
+
+        let (castling_index, castling) = section(2, FenParseErr::MissingCastling)?;
+        if castling != "-" {
+            for (offset, character) in castling.char_indices() {
+                if !"KQkq".contains(character) || castling[..offset].contains(character) {
+                    return error(FenParseErr::InvalidCastling, castling_index + offset);
                 }
-                Some(en_passant_square)
-            } else {
-                return Err(FenParseErr::InvalidEnPassant);
             }
-        };
-        let half_move_clock = {
-            let component = components.next();
-            if component.is_none() {
-                return Err(FenParseErr::MissingHalfMoveClock);
-            }
-            let parsed = component.unwrap().parse();
-            if parsed.is_err() {
-                return Err(FenParseErr::InvalidHalfMoveClock);
-            }
-            parsed.unwrap()
-        };
-        if en_passant_square.is_some() && half_move_clock != 0 {
-            return Err(FenParseErr::InvalidHalfMoveClock);
         }
+        let castling_rights = CastlingRights::from_fen_section(castling);
 
-        let full_move_counter = {
-            let component = components.next();
-            if component.is_none() {
-                return Err(FenParseErr::MissingFullMoveCounter);
-            }
-            let parsed = component.unwrap().parse();
-            if parsed.is_err() {
-                return Err(FenParseErr::InvalidFullMoveCounter);
-            }
-            parsed.unwrap()
-        };
-
-        let game_state = GameState {
-            en_passant_square,
-
-            castling_rights,
-
-            half_move_clock,
-            captured: None,
-        };
-
-        let board = Self {
-            white_to_move,
-
-            bit_boards,
-
-            full_move_counter,
-
-            game_state,
+        let (en_passant_index, en_passant) = section(3, FenParseErr::MissingEnPassant)?;
+        let en_passant_square = if en_passant == "-" {
+            None
+        } else if let Ok(en_passant_square) = Square::from_notation(en_passant) {
+            Some(en_passant_square)
+        } else {
+            return error(FenParseErr::InvalidEnPassant, en_passant_index);
         };
 
-        let (friendly_king_square, enemy_king_square) = if white_to_move {
-            (white_king_square.unwrap(), black_king_square.unwrap())
-        } else {
-            (black_king_square.unwrap(), white_king_square.unwrap())
+        let (half_move_clock_index, half_move_clock) =
+            section(4, FenParseErr::MissingHalfMoveClock)?;
+        let Ok(half_move_clock) = half_move_clock.parse() else {
+            return error(FenParseErr::InvalidHalfMoveClock, half_move_clock_index);
         };
 
-        let occupied_squares = bit_boards[0]
-            | bit_boards[1]
-            | bit_boards[2]
-            | bit_boards[3]
-            | bit_boards[4]
-            | bit_boards[5]
-            | bit_boards[6]
-            | bit_boards[7]
-            | bit_boards[8]
-            | bit_boards[9]
-            | bit_boards[10]
-            | bit_boards[11];
-
-        let (
-            (friendly_pawns, friendly_knights, friendly_diagonal, friendly_orthogonal),
-            (enemy_pawns, enemy_knights, enemy_diagonal, enemy_orthogonal),
-        ) = {
-            let black = (
-                bit_boards[Piece::BlackPawn as usize],
-                bit_boards[Piece::BlackKnight as usize],
-                bit_boards[Piece::BlackBishop as usize] | bit_boards[Piece::BlackQueen as usize],
-                bit_boards[Piece::BlackRook as usize] | bit_boards[Piece::BlackQueen as usize],
-            );
-            let white = (
-                bit_boards[Piece::WhitePawn as usize],
-                bit_boards[Piece::WhiteKnight as usize],
-                bit_boards[Piece::WhiteBishop as usize] | bit_boards[Piece::WhiteQueen as usize],
-                bit_boards[Piece::WhiteRook as usize] | bit_boards[Piece::WhiteQueen as usize],
-            );
-            if white_to_move {
-                (white, black)
-            } else {
-                (black, white)
-            }
+        let (full_move_counter_index, full_move_counter) =
+            section(5, FenParseErr::MissingFullMoveCounter)?;
+        let Ok(full_move_counter) = full_move_counter.parse() else {
+            return error(FenParseErr::InvalidFullMoveCounter, full_move_counter_index);
         };
-        if MoveGenerator::calculate_checkers(
-            white_to_move,
-            friendly_king_square,
-            enemy_pawns,
-            enemy_knights,
-            enemy_diagonal,
-            enemy_orthogonal,
-            occupied_squares,
-        )
-        .count()
-            >= 3
-        {
-            return Err(FenParseErr::TooManyChecks);
-        }
-        if MoveGenerator::raw_calculate_is_in_check(
-            !white_to_move,
-            enemy_king_square,
-            friendly_pawns,
-            friendly_knights,
-            friendly_diagonal,
-            friendly_orthogonal,
-            occupied_squares,
-        ) {
-            return Err(FenParseErr::EnemyInCheck);
-        }
 
-        if let Some(en_passant_square) = en_passant_square {
-            let en_passant_pawn_position =
-                en_passant_square.down(if board.white_to_move { 1 } else { -1 });
-            if !enemy_pawns.get(&en_passant_pawn_position) {
-                return Err(FenParseErr::InvalidEnPassant);
+        let board = builder
+            .white_to_move(white_to_move)
+            .castling_rights(castling_rights)
+            .en_passant_square(en_passant_square)
+            .half_move_clock(half_move_clock)
+            .full_move_counter(full_move_counter)
+            .build_unchecked();
+
+        board.validate().map_err(|position_error| {
+            let pieces = |white| {
+                if white {
+                    Piece::WHITE_PIECES
+                } else {
+                    Piece::BLACK_PIECES
+                }
+            };
+            let index = match position_error {
+                PositionError::MissingKing { .. } => position_index,
+                PositionError::TooManyKings { white } => {
+                    last_index(&square_indices, *board.get_bit_board(pieces(white)[5]))
+                }
+                PositionError::TouchingKings => last_index(
+                    &square_indices,
+                    *board.get_bit_board(Piece::WhiteKing) | *board.get_bit_board(Piece::BlackKing),
+                ),
+                PositionError::PawnOnBackRank(square) => square_indices[square.usize()],
+                PositionError::TooManyPawns { white } => {
+                    last_index(&square_indices, *board.get_bit_board(pieces(white)[0]))
+                }
+                PositionError::TooManyPieces { white }
+                | PositionError::TooManyPromotedPieces { white } => last_index(
+                    &square_indices,
+                    pieces(white).iter().fold(BitBoard::EMPTY, |all, piece| {
+                        all | *board.get_bit_board(*piece)
+                    }),
+                ),
+                PositionError::InvalidCastlingRights(right) => {
+                    castling_index + castling.find(right).unwrap_or_default()
+                }
+                PositionError::InvalidEnPassant => en_passant_index,
+                PositionError::TooManyChecks => last_index(
+                    &square_indices,
+                    *board.get_bit_board(pieces(white_to_move)[5]),
+                ),
+                PositionError::EnemyInCheck => side_to_move_index,
+            };
+            FenError {
+                kind: FenParseErr::InvalidPosition(position_error),
+                index,
             }
-        }
+        })?;
 
         Ok(board)
     }
@@ -390,7 +369,15 @@ impl Board {
 
 #[cfg(test)]
 mod tests {
-    use crate::{board::Board, tests::TEST_FENS};
+    use crate::{
+        board::{
+            Board,
+            builder::PositionError,
+            fen::{FenError, FenParseErr},
+            square::Square,
+        },
+        tests::TEST_FENS,
+    };
 
     #[test]
     fn test_fen_encoding() {
@@ -399,4 +386,114 @@ mod tests {
             assert_eq!(fen, board.to_fen());
         }
     }
+
+    #[test]
+    fn fen_sections_split_on_any_whitespace() {
+        let board = Board::from_fen("  4k3/8/8/8/8/8/8/4K3\tw\t-  - 0\n1\n").unwrap();
+        assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn fen_errors() {
+        for (fen, kind, index) in [
+            ("", FenParseErr::MissingPosition, 0),
+            (
+                "8/8/8/8/8/8/8/8 w - - 0",
+                FenParseErr::MissingFullMoveCounter,
+                23,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2X w - - 0 1",
+                FenParseErr::InvalidPiece,
+                19,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - - 0 0",
+                FenParseErr::InvalidFullMoveCounter,
+                28,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 x - - 0 1",
+                FenParseErr::InvalidSideToMove,
+                20,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3\tx - - 0 1",
+                FenParseErr::InvalidSideToMove,
+                20,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w  KQx - 0 1",
+                FenParseErr::InvalidCastling,
+                25,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w KQx - 0 1",
+                FenParseErr::InvalidCastling,
+                24,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K31 w - - 0 1",
+                FenParseErr::InvalidRankLength,
+                19,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2 w - - 0 1",
+                FenParseErr::InvalidRankLength,
+                19,
+            ),
+            (
+                "4k3/8/8/8/8/8/4K3 w - - 0 1",
+                FenParseErr::InvalidRankLength,
+                17,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e9 0 1",
+                FenParseErr::InvalidEnPassant,
+                24,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K3 w - e6 0 1",
+                FenParseErr::InvalidPosition(PositionError::InvalidEnPassant),
+                24,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4KK2 w - - 0 1",
+                FenParseErr::InvalidPosition(PositionError::TooManyKings { white: true }),
+                18,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+                FenParseErr::InvalidPosition(PositionError::PawnOnBackRank(
+                    Square::from_notation("a1").unwrap(),
+                )),
+                16,
+            ),
+            (
+                "4k3/8/8/8/8/8/PPPPPPPP/P3K3 w - - 0 1",
+                FenParseErr::InvalidPosition(PositionError::PawnOnBackRank(
+                    Square::from_notation("a1").unwrap(),
+                )),
+                23,
+            ),
+            (
+                "4k3/8/8/8/8/P7/PPPPPPPP/4K3 w - - 0 1",
+                FenParseErr::InvalidPosition(PositionError::TooManyPawns { white: true }),
+                22,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R3K3 w KQ - 0 1",
+                FenParseErr::InvalidPosition(PositionError::InvalidCastlingRights('K')),
+                23,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2q b - - 0 1",
+                FenParseErr::InvalidPosition(PositionError::EnemyInCheck),
+                21,
+            ),
+        ] {
+            let error = Board::from_fen(fen).err();
+            assert_eq!(error, Some(FenError { kind, index }), "{fen}");
+        }
+    }
 }
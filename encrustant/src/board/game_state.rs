@@ -106,6 +106,12 @@ impl CastlingRights {
         self.0 == 0
     }
 
+    /// Returns the castling rights with white and black swapped.
+    #[must_use]
+    pub const fn mirrored(&self) -> Self {
+        Self((self.0 >> 2) | ((self.0 & 0b11) << 2))
+    }
+
     /// Returns the u8 internal representation.
     #[must_use]
     pub const fn internal_value(&self) -> u8 {
@@ -1,11 +1,22 @@
 //! Represents a chess position.
 
 use core::fmt::Display;
+use core::ops::{Deref, DerefMut};
 use std::num::NonZeroU32;
 
 /// Implements bit boards.
 pub mod bit_board;
 
+/// Sets up and validates positions.
+pub mod builder;
+
+/// Checks incrementally updated state against state computed from scratch.
+#[cfg(feature = "validate")]
+mod consistency;
+
+/// Draws the board as text or SVG.
+pub mod diagram;
+
 /// Implements FEN notation.
 pub mod fen;
 
@@ -23,14 +34,37 @@ use piece::Piece;
 use square::Square;
 
 use self::game_state::GameState;
+use crate::{consume_bit_board, search::zobrist::Zobrist};
 
 /// Represents a chess position.
+#[derive(Clone)]
 pub struct Board {
     /// Whether it is white's turn to move.
     pub white_to_move: bool,
 
     /// Bit boards, one for every piece type.
-    pub bit_boards: [BitBoard; 12],
+    bit_boards: [BitBoard; 12],
+
+    /// The piece on every square.
+    mailbox: [Option<Piece>; 64],
+
+    /// Squares with a white piece.
+    white_occupancy: BitBoard,
+
+    /// Squares with a black piece.
+    black_occupancy: BitBoard,
+
+    /// Position zobrist key.
+    zobrist_key: Zobrist,
+
+    /// Pawn zobrist key.
+    pawn_zobrist_key: Zobrist,
+
+    /// Minor piece (knight, bishop, king) zobrist key.
+    minor_piece_zobrist_key: Zobrist,
+
+    /// Material zobrist key, which changes with the number of each piece.
+    material_zobrist_key: Zobrist,
 
     /// The number of full moves.
     pub full_move_counter: NonZeroU32,
@@ -39,6 +73,46 @@ pub struct Board {
     pub game_state: GameState,
 }
 
+/// Mutable reference to a piece type's bit board, from [`Board::get_bit_board_mut`].
+pub struct BitBoardMut<'a> {
+    board: &'a mut Board,
+    piece: Piece,
+    bit_board: BitBoard,
+}
+
+impl Deref for BitBoardMut<'_> {
+    type Target = BitBoard;
+
+    fn deref(&self) -> &BitBoard {
+        &self.bit_board
+    }
+}
+
+impl DerefMut for BitBoardMut<'_> {
+    fn deref_mut(&mut self) -> &mut BitBoard {
+        &mut self.bit_board
+    }
+}
+
+impl Drop for BitBoardMut<'_> {
+    fn drop(&mut self) {
+        let old = *self.board.get_bit_board(self.piece);
+
+        let mut removed = old & !self.bit_board;
+        consume_bit_board!(removed, square {
+            self.board.remove_piece(self.piece, square);
+        });
+
+        let mut added = self.bit_board & !old;
+        consume_bit_board!(added, square {
+            if let Some(piece) = self.board.piece_at(square) {
+                self.board.remove_piece(piece, square);
+            }
+            self.board.add_piece(self.piece, square);
+        });
+    }
+}
+
 impl Display for Board {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.to_fen())
@@ -46,63 +120,145 @@ impl Display for Board {
 }
 
 impl Board {
-    /// Returns a piece at a square.
-    #[must_use]
-    pub fn piece_at(&self, square: Square) -> Option<Piece> {
-        let square_bit_board = square.bit_board();
+    /// Creates a board from its bit boards, filling in the mailbox and occupancy.
+    fn from_bit_boards(
+        white_to_move: bool,
+        bit_boards: [BitBoard; 12],
+        full_move_counter: NonZeroU32,
+        game_state: GameState,
+    ) -> Self {
+        let mut board = Self {
+            white_to_move,
+            bit_boards: [BitBoard::EMPTY; 12],
+            mailbox: [None; 64],
+            white_occupancy: BitBoard::EMPTY,
+            black_occupancy: BitBoard::EMPTY,
+            zobrist_key: Zobrist::EMPTY,
+            pawn_zobrist_key: Zobrist::EMPTY,
+            minor_piece_zobrist_key: Zobrist::EMPTY,
+            material_zobrist_key: Zobrist::EMPTY,
+            full_move_counter,
+            game_state,
+        };
         for piece in Piece::ALL_PIECES {
-            let piece_bit_board = self.get_bit_board(piece);
-            if piece_bit_board.overlaps(&square_bit_board) {
-                return Some(piece);
+            let mut bit_board = bit_boards[piece as usize];
+            consume_bit_board!(bit_board, square {
+                board.add_piece(piece, square);
+            });
+        }
+        board.xor_game_state_zobrist();
+        if !white_to_move {
+            board.zobrist_key.flip_side_to_move();
+        }
+        board
+    }
+
+    /// Returns the position zobrist key, which is updated as moves are made.
+    #[must_use]
+    pub const fn zobrist(&self) -> Zobrist {
+        self.zobrist_key
+    }
+
+    /// Returns the pawn zobrist key.
+    #[must_use]
+    pub const fn pawn_zobrist(&self) -> Zobrist {
+        self.pawn_zobrist_key
+    }
+
+    /// Returns the minor piece (knight, bishop, king) zobrist key.
+    #[must_use]
+    pub const fn minor_piece_zobrist(&self) -> Zobrist {
+        self.minor_piece_zobrist_key
+    }
+
+    /// Returns the material zobrist key, which identifies the number of each piece.
+    #[must_use]
+    pub const fn material_zobrist(&self) -> Zobrist {
+        self.material_zobrist_key
+    }
+
+    /// Toggles the castling rights and en passant square from the position zobrist key.
+    pub(crate) const fn xor_game_state_zobrist(&mut self) {
+        self.zobrist_key
+            .xor_castling_rights(&self.game_state.castling_rights);
+        if let Some(en_passant_square) = &self.game_state.en_passant_square {
+            self.zobrist_key.xor_en_passant(en_passant_square);
+        }
+    }
+
+    /// Passes the turn to the other side.
+    pub(crate) const fn flip_side_to_move(&mut self) {
+        self.white_to_move = !self.white_to_move;
+        self.zobrist_key.flip_side_to_move();
+    }
+
+    /// Toggles a piece from the zobrist keys.
+    const fn xor_piece_zobrist(&mut self, piece: Piece, square: Square) {
+        self.zobrist_key.xor_piece(piece as usize, square.usize());
+        match piece {
+            Piece::WhitePawn | Piece::BlackPawn => {
+                self.pawn_zobrist_key
+                    .xor_piece(piece as usize, square.usize());
             }
+            Piece::WhiteKnight
+            | Piece::BlackKnight
+            | Piece::WhiteBishop
+            | Piece::BlackBishop
+            | Piece::WhiteKing
+            | Piece::BlackKing => {
+                self.minor_piece_zobrist_key
+                    .xor_piece(piece as usize, square.usize());
+            }
+            _ => {}
         }
-        None
+    }
+
+    /// Checks that the incrementally updated zobrist keys match ones computed from scratch.
+    pub(crate) fn debug_assert_zobrist(&self) {
+        debug_assert_eq!(self.zobrist_key, Zobrist::compute(self), "{self}");
+        debug_assert_eq!(self.pawn_zobrist_key, Zobrist::pawn_key(self), "{self}");
+        debug_assert_eq!(
+            self.minor_piece_zobrist_key,
+            Zobrist::minor_piece_key(self),
+            "{self}"
+        );
+        debug_assert_eq!(
+            self.material_zobrist_key,
+            Zobrist::material_key(self),
+            "{self}"
+        );
+    }
+
+    /// Returns a piece at a square.
+    #[must_use]
+    pub const fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.mailbox[square.usize()]
     }
 
     /// Returns a white piece at a square.
     #[must_use]
     pub fn white_piece_at(&self, square: Square) -> Option<Piece> {
-        let square_bit_board = square.bit_board();
-        for piece in Piece::WHITE_PIECES {
-            let piece_bit_board = self.get_bit_board(piece);
-            if piece_bit_board.overlaps(&square_bit_board) {
-                return Some(piece);
-            }
-        }
-        None
+        self.piece_at(square).filter(|piece| piece.is_white())
     }
 
     /// Returns a black piece at a square.
     #[must_use]
     pub fn black_piece_at(&self, square: Square) -> Option<Piece> {
-        let square_bit_board = square.bit_board();
-        for piece in Piece::BLACK_PIECES {
-            let piece_bit_board = self.get_bit_board(piece);
-            if piece_bit_board.overlaps(&square_bit_board) {
-                return Some(piece);
-            }
-        }
-        None
+        self.piece_at(square).filter(|piece| !piece.is_white())
     }
 
     /// Returns a piece on the side-to-move at a square.
     #[must_use]
     pub fn friendly_piece_at(&self, square: Square) -> Option<Piece> {
-        if self.white_to_move {
-            self.white_piece_at(square)
-        } else {
-            self.black_piece_at(square)
-        }
+        self.piece_at(square)
+            .filter(|piece| piece.is_white() == self.white_to_move)
     }
 
     /// Returns an opponent's piece at a square.
     #[must_use]
     pub fn enemy_piece_at(&self, square: Square) -> Option<Piece> {
-        if self.white_to_move {
-            self.black_piece_at(square)
-        } else {
-            self.white_piece_at(square)
-        }
+        self.piece_at(square)
+            .filter(|piece| piece.is_white() != self.white_to_move)
     }
 
     /// Returns a reference to a piece type's bit board.
@@ -112,9 +268,155 @@ impl Board {
     }
 
     /// Returns a mutable reference to a piece type's bit board.
+    ///
+    /// Squares set or unset through it are added or removed with [`Self::add_piece`] and
+    /// [`Self::remove_piece`] when the reference is dropped, replacing any piece already there.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, piece::Piece, square::Square};
+    ///
+    /// let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    /// board.get_bit_board_mut(Piece::WhiteRook).set(&Square::from_notation("a1").unwrap());
+    /// assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    /// assert_eq!(board.zobrist(), Board::from_fen(&board.to_fen()).unwrap().zobrist());
+    /// ```
+    #[must_use]
+    pub const fn get_bit_board_mut(&mut self, piece: Piece) -> BitBoardMut<'_> {
+        let bit_board = self.bit_boards[piece as usize];
+        BitBoardMut {
+            board: self,
+            piece,
+            bit_board,
+        }
+    }
+
+    /// Returns the bit boards of every piece type, indexed by [`Piece`].
+    #[must_use]
+    pub const fn bit_boards(&self) -> &[BitBoard; 12] {
+        &self.bit_boards
+    }
+
+    /// Returns the squares with a white piece.
+    #[must_use]
+    pub const fn white_occupancy(&self) -> BitBoard {
+        self.white_occupancy
+    }
+
+    /// Returns the squares with a black piece.
+    #[must_use]
+    pub const fn black_occupancy(&self) -> BitBoard {
+        self.black_occupancy
+    }
+
+    /// Returns the squares with a piece on the side-to-move.
+    #[must_use]
+    pub const fn friendly_occupancy(&self) -> BitBoard {
+        if self.white_to_move {
+            self.white_occupancy
+        } else {
+            self.black_occupancy
+        }
+    }
+
+    /// Returns the squares with an opponent's piece.
     #[must_use]
-    pub const fn get_bit_board_mut(&mut self, piece: Piece) -> &mut BitBoard {
-        &mut self.bit_boards[piece as usize]
+    pub const fn enemy_occupancy(&self) -> BitBoard {
+        if self.white_to_move {
+            self.black_occupancy
+        } else {
+            self.white_occupancy
+        }
+    }
+
+    /// Returns the squares with any piece.
+    #[must_use]
+    pub fn occupied(&self) -> BitBoard {
+        self.white_occupancy | self.black_occupancy
+    }
+
+    const fn occupancy_mut(&mut self, piece: Piece) -> &mut BitBoard {
+        if piece.is_white() {
+            &mut self.white_occupancy
+        } else {
+            &mut self.black_occupancy
+        }
+    }
+
+    /// Puts a piece on an empty square.
+    pub fn add_piece(&mut self, piece: Piece, square: Square) {
+        debug_assert!(self.piece_at(square).is_none());
+        self.bit_boards[piece as usize].set(&square);
+        self.occupancy_mut(piece).set(&square);
+        self.mailbox[square.usize()] = Some(piece);
+        self.xor_piece_zobrist(piece, square);
+        self.material_zobrist_key.xor_piece(
+            piece as usize,
+            self.bit_boards[piece as usize].count() as usize - 1,
+        );
+    }
+
+    /// Takes a piece off a square.
+    pub fn remove_piece(&mut self, piece: Piece, square: Square) {
+        debug_assert!(self.piece_at(square) == Some(piece));
+        self.material_zobrist_key.xor_piece(
+            piece as usize,
+            self.bit_boards[piece as usize].count() as usize - 1,
+        );
+        self.bit_boards[piece as usize].unset(&square);
+        self.occupancy_mut(piece).unset(&square);
+        self.mailbox[square.usize()] = None;
+        self.xor_piece_zobrist(piece, square);
+    }
+
+    /// Moves a piece to an empty square.
+    pub fn move_piece(&mut self, piece: Piece, from: Square, to: Square) {
+        debug_assert!(self.piece_at(from) == Some(piece) && self.piece_at(to).is_none());
+        self.bit_boards[piece as usize].toggle_two(&from, &to);
+        self.occupancy_mut(piece).toggle_two(&from, &to);
+        self.mailbox[from.usize()] = None;
+        self.mailbox[to.usize()] = Some(piece);
+        self.xor_piece_zobrist(piece, from);
+        self.xor_piece_zobrist(piece, to);
+    }
+
+    /// Returns the position with the colours swapped and the ranks flipped.
+    ///
+    /// The mirrored position should be exactly as good for the side to move as the original.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    ///
+    /// let board = Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w Kq - 0 1").unwrap();
+    /// let mirrored = board.mirrored();
+    /// assert_eq!(mirrored.to_fen(), "r3k2r/pppbbppp/2n2q1P/1P2p3/3pn3/BN2PNP1/P1PPQPB1/R3K2R b Qk - 0 1");
+    /// assert_eq!(mirrored.mirrored().to_fen(), board.to_fen());
+    /// ```
+    #[must_use]
+    pub fn mirrored(&self) -> Self {
+        let mut bit_boards = [BitBoard::EMPTY; 12];
+        for piece in Piece::ALL_PIECES {
+            bit_boards[piece.opposite_colour() as usize] =
+                self.get_bit_board(piece).flip_vertical();
+        }
+
+        Self::from_bit_boards(
+            !self.white_to_move,
+            bit_boards,
+            self.full_move_counter,
+            GameState {
+                en_passant_square: self
+                    .game_state
+                    .en_passant_square
+                    .map(|square| square.flip()),
+                castling_rights: self.game_state.castling_rights.mirrored(),
+                half_move_clock: self.game_state.half_move_clock,
+                captured: self.game_state.captured.map(Piece::opposite_colour),
+            },
+        )
     }
 
     /// Returns true if any of the below are true:
@@ -97,6 +97,56 @@ impl Piece {
         }
     }
 
+    /// Converts a piece into its Unicode chess symbol.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::piece::Piece;
+    ///
+    /// assert_eq!(Piece::WhiteKnight.to_unicode_char(), '♘');
+    /// assert_eq!(Piece::BlackKnight.to_unicode_char(), '♞');
+    /// ```
+    #[must_use]
+    pub const fn to_unicode_char(self) -> char {
+        match self {
+            Self::WhitePawn => '♙',
+            Self::WhiteKnight => '♘',
+            Self::WhiteBishop => '♗',
+            Self::WhiteRook => '♖',
+            Self::WhiteQueen => '♕',
+            Self::WhiteKing => '♔',
+
+            Self::BlackPawn => '♟',
+            Self::BlackKnight => '♞',
+            Self::BlackBishop => '♝',
+            Self::BlackRook => '♜',
+            Self::BlackQueen => '♛',
+            Self::BlackKing => '♚',
+        }
+    }
+
+    /// Returns whether the piece is white.
+    #[must_use]
+    pub const fn is_white(self) -> bool {
+        (self as usize) < 6
+    }
+
+    /// Returns the same piece type of the other colour.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::piece::Piece;
+    ///
+    /// assert_eq!(Piece::WhiteRook.opposite_colour(), Piece::BlackRook);
+    /// assert_eq!(Piece::BlackKing.opposite_colour(), Piece::WhiteKing);
+    /// ```
+    #[must_use]
+    pub const fn opposite_colour(self) -> Self {
+        Self::ALL_PIECES[(self as usize + 6) % 12]
+    }
+
     /// Tries to convert a FEN character into the piece.
     ///
     /// # Errors
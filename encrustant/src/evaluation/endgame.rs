@@ -0,0 +1,380 @@
+//! Known endgames, which are recognised by their material key and evaluated by dedicated
+//! functions, and scale factors that shrink the evaluation of drawish endgames.
+
+use std::sync::OnceLock;
+
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+    search::zobrist::Zobrist,
+};
+
+use super::{eval_data::Score, kpk};
+
+/// Added to the score of an endgame that is known to be won.
+pub const KNOWN_WIN: Score = 10000;
+
+/// Scale factor that keeps the whole evaluation.
+pub const NORMAL_SCALE_FACTOR: Score = 64;
+
+/// Values of a pawn, knight, bishop, rook and queen, for the material of a side that is winning.
+const PIECE_VALUES: [Score; 5] = [100, 300, 300, 500, 900];
+
+/// An endgame with a dedicated evaluation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Endgame {
+    /// King and pawn versus king, which is looked up in the KPK bitbase.
+    Kpk,
+
+    /// King, bishop and knight versus king, where the king is driven to a corner the bishop covers.
+    Kbnk,
+
+    /// King and two knights versus king, which can not be forced.
+    Knnk,
+
+    /// Enough material to mate versus a lone king, where the king is driven to the edge.
+    Kxk,
+}
+
+/// Pieces of white, or black, ordered pawn to king.
+const fn pieces(white: bool) -> [Piece; 6] {
+    if white {
+        Piece::WHITE_PIECES
+    } else {
+        Piece::BLACK_PIECES
+    }
+}
+
+/// Bit boards of the pieces of white, or black, ordered pawn to king.
+fn bit_boards(board: &Board, white: bool) -> [BitBoard; 6] {
+    pieces(white).map(|piece| *board.get_bit_board(piece))
+}
+
+/// Number of moves a king needs to go from one square to the other.
+fn distance(square: Square, other_square: Square) -> Score {
+    let file_distance = (square.file() - other_square.file()).abs();
+    let rank_distance = (square.rank() - other_square.rank()).abs();
+    Score::from(file_distance.max(rank_distance))
+}
+
+/// Squares of the same colour as `square`.
+fn same_colour_squares(square: Square) -> BitBoard {
+    if BitBoard::LIGHT_SQUARES.get(&square) {
+        BitBoard::LIGHT_SQUARES
+    } else {
+        BitBoard::DARK_SQUARES
+    }
+}
+
+/// Bonus for the kings being close, so that the winning king can help to mate.
+fn push_close(strong_king: Square, weak_king: Square) -> Score {
+    140 - 20 * distance(strong_king, weak_king)
+}
+
+/// Bonus for the losing king being near the edge.
+fn push_to_edge(weak_king: Square) -> Score {
+    let file_distance = (weak_king.file() - 4).max(3 - weak_king.file());
+    let rank_distance = (weak_king.rank() - 4).max(3 - weak_king.rank());
+    20 * Score::from(file_distance + rank_distance)
+}
+
+/// Bonus for the losing king being near a corner of the same colour as the bishop.
+fn push_to_corner(weak_king: Square, bishop: Square) -> Score {
+    let bishop_squares = same_colour_squares(bishop);
+    let corner_distance = [0, 7, 56, 63]
+        .map(Square::from_index)
+        .into_iter()
+        .filter(|corner| bishop_squares.get(corner))
+        .map(|corner| distance(weak_king, corner))
+        .min()
+        .unwrap();
+    50 * (7 - corner_distance)
+}
+
+/// Value of the pawns and pieces of white, or black.
+fn material(board: &Board, white: bool) -> Score {
+    bit_boards(board, white)
+        .iter()
+        .zip(PIECE_VALUES)
+        .map(|(bit_board, value)| bit_board.count().cast_signed() * value)
+        .sum()
+}
+
+/// Whether white, or black, can force mate against a lone king without promoting.
+fn has_mating_material(board: &Board, white: bool) -> bool {
+    let [_, knights, bishops, rooks, queens, _] = bit_boards(board, white);
+    (rooks | queens).is_not_empty()
+        || (bishops.overlaps(&BitBoard::LIGHT_SQUARES) && bishops.overlaps(&BitBoard::DARK_SQUARES))
+        || (bishops.is_not_empty() && knights.is_not_empty())
+        || knights.count() >= 3
+}
+
+/// Material key of the endgame given by `code`, such as "KBNK", where the pieces of the first
+/// king are white's when `strong_white`.
+fn material_key(code: &str, strong_white: bool) -> Zobrist {
+    let (strong, weak) = code[1..].split_once('K').unwrap();
+
+    let mut piece_counts = [0; 12];
+    for (side_pieces, white) in [(strong, strong_white), (weak, !strong_white)] {
+        let pieces = pieces(white);
+        piece_counts[pieces[5] as usize] = 1;
+        for character in side_pieces.chars() {
+            piece_counts[pieces["PNBRQ".find(character).unwrap()] as usize] += 1;
+        }
+    }
+    Zobrist::material_key_from_counts(&piece_counts)
+}
+
+/// Material key of each endgame with fixed material, along with whether white is the side with more material.
+fn signatures() -> &'static [(Zobrist, Endgame, bool)] {
+    static SIGNATURES: OnceLock<Vec<(Zobrist, Endgame, bool)>> = OnceLock::new();
+    SIGNATURES.get_or_init(|| {
+        let mut signatures = Vec::new();
+        for (code, endgame) in [
+            ("KPK", Endgame::Kpk),
+            ("KBNK", Endgame::Kbnk),
+            ("KNNK", Endgame::Knnk),
+        ] {
+            for strong_white in [true, false] {
+                signatures.push((material_key(code, strong_white), endgame, strong_white));
+            }
+        }
+        signatures
+    })
+}
+
+impl Endgame {
+    /// Finds the endgame with a dedicated evaluation, if there is one, and whether white is the
+    /// side with more material.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::endgame::Endgame;
+    ///
+    /// let board = Board::from_fen("8/8/8/3k4/8/8/2n5/3bK3 w - - 0 1").unwrap();
+    /// assert_eq!(Endgame::find(&board), Some((Endgame::Kbnk, false)));
+    ///
+    /// let board = Board::from_fen("8/8/8/2k5/8/8/8/3QK3 w - - 0 1").unwrap();
+    /// assert_eq!(Endgame::find(&board), Some((Endgame::Kxk, true)));
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// assert_eq!(Endgame::find(&board), None);
+    /// ```
+    #[must_use]
+    pub fn find(board: &Board) -> Option<(Self, bool)> {
+        let key = board.material_zobrist();
+        if let Some(&(_, endgame, strong_white)) = signatures()
+            .iter()
+            .find(|(signature, ..)| *signature == key)
+        {
+            return Some((endgame, strong_white));
+        }
+
+        for strong_white in [true, false] {
+            let weak_occupancy = if strong_white {
+                board.black_occupancy()
+            } else {
+                board.white_occupancy()
+            };
+            if weak_occupancy.count() == 1 && has_mating_material(board, strong_white) {
+                return Some((Self::Kxk, strong_white));
+            }
+        }
+
+        None
+    }
+
+    /// Score from the perspective of the side with more material.
+    #[must_use]
+    pub fn evaluate(self, board: &Board, strong_white: bool) -> Score {
+        let [strong_pawns, _, strong_bishops, .., strong_king] = bit_boards(board, strong_white);
+        let strong_king = strong_king.first_square();
+        let weak_king = board.get_bit_board(pieces(!strong_white)[5]).first_square();
+
+        match self {
+            Self::Kpk => {
+                // The bitbase has the pawn moving up the board
+                let perspective =
+                    |square: Square| if strong_white { square } else { square.flip() };
+                let pawn = perspective(strong_pawns.first_square());
+                if kpk::probe(
+                    perspective(strong_king),
+                    pawn,
+                    perspective(weak_king),
+                    board.white_to_move == strong_white,
+                ) {
+                    KNOWN_WIN + PIECE_VALUES[0] + Score::from(pawn.rank())
+                } else {
+                    0
+                }
+            }
+            Self::Kbnk => {
+                KNOWN_WIN
+                    + material(board, strong_white)
+                    + push_close(strong_king, weak_king)
+                    + push_to_corner(weak_king, strong_bishops.first_square())
+            }
+            Self::Knnk => 0,
+            Self::Kxk => {
+                KNOWN_WIN
+                    + material(board, strong_white)
+                    + push_close(strong_king, weak_king)
+                    + push_to_edge(weak_king)
+            }
+        }
+    }
+}
+
+/// Returns the score for the side playing if the position is an endgame with a dedicated evaluation.
+#[must_use]
+pub fn evaluate(board: &Board) -> Option<Score> {
+    let (endgame, strong_white) = Endgame::find(board)?;
+    let score = endgame.evaluate(board, strong_white);
+    Some(if strong_white == board.white_to_move {
+        score
+    } else {
+        -score
+    })
+}
+
+/// How much of the evaluation to keep, out of `NORMAL_SCALE_FACTOR`, when white, or black, is ahead.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::board::Board;
+/// use encrustant::evaluation::endgame::{NORMAL_SCALE_FACTOR, scale_factor};
+///
+/// // Opposite-coloured bishops
+/// let board = Board::from_fen("4k3/8/4b3/8/8/2B1P3/8/4K3 w - - 0 1").unwrap();
+/// assert!(scale_factor(&board, true) < NORMAL_SCALE_FACTOR);
+///
+/// // The bishop can not drive the king out of the corner
+/// let board = Board::from_fen("k7/8/8/8/8/P7/8/2B1K3 w - - 0 1").unwrap();
+/// assert_eq!(scale_factor(&board, true), 0);
+/// ```
+#[must_use]
+pub fn scale_factor(board: &Board, strong_white: bool) -> Score {
+    let [
+        strong_pawns,
+        strong_knights,
+        strong_bishops,
+        strong_rooks,
+        strong_queens,
+        _,
+    ] = bit_boards(board, strong_white);
+    let [
+        weak_pawns,
+        weak_knights,
+        weak_bishops,
+        weak_rooks,
+        weak_queens,
+        weak_king,
+    ] = bit_boards(board, !strong_white);
+    let strong_others = strong_knights | strong_rooks | strong_queens;
+    let weak_others = weak_knights | weak_rooks | weak_queens;
+
+    // Rook pawns with a bishop that does not cover the promotion square, against a king in the corner
+    if strong_others.is_empty()
+        && strong_bishops.is_not_empty()
+        && strong_pawns.is_not_empty()
+        && (weak_pawns | weak_bishops | weak_others).is_empty()
+    {
+        for (file, other_files) in [(0, BitBoard::NOT_A_FILE), (7, BitBoard::NOT_H_FILE)] {
+            let promotion_square = Square::from_coords(if strong_white { 7 } else { 0 }, file);
+            if !strong_pawns.overlaps(&other_files)
+                && !strong_bishops.overlaps(&same_colour_squares(promotion_square))
+                && distance(weak_king.first_square(), promotion_square) <= 1
+            {
+                return 0;
+            }
+        }
+    }
+
+    // Opposite-coloured bishops
+    if strong_bishops.count() == 1
+        && weak_bishops.count() == 1
+        && !weak_bishops.overlaps(&same_colour_squares(strong_bishops.first_square()))
+    {
+        if (strong_others | weak_others).is_empty() {
+            let extra_pawns = strong_pawns.count().saturating_sub(weak_pawns.count());
+            return (16 + 8 * extra_pawns.cast_signed()).min(NORMAL_SCALE_FACTOR);
+        }
+        return 48;
+    }
+
+    NORMAL_SCALE_FACTOR
+}
+
+/// Scales a score from white's perspective by the scale factor of the side that is ahead.
+#[must_use]
+pub fn scale(board: &Board, white_score: Score) -> Score {
+    white_score * scale_factor(board, white_score > 0) / NORMAL_SCALE_FACTOR
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        evaluation::{
+            Eval,
+            endgame::KNOWN_WIN,
+            tests::{assert_mirror_symmetric, swap_sides},
+        },
+    };
+
+    use super::{Endgame, scale_factor};
+
+    #[test]
+    fn drives_king_to_bishop_corner() {
+        // The bishop covers a1 but not h1
+        let right_corner = Board::from_fen("8/8/8/3K1N2/3B4/8/8/k7 b - - 0 1").unwrap();
+        let wrong_corner = Board::from_fen("8/8/8/3K1N2/3B4/8/8/7k b - - 0 1").unwrap();
+        assert!(Eval::evaluate(&right_corner) < Eval::evaluate(&wrong_corner));
+        assert!(Eval::evaluate(&wrong_corner) < -KNOWN_WIN);
+    }
+
+    #[test]
+    fn drives_king_to_edge() {
+        let edge = Board::from_fen("3k4/8/3K4/8/8/8/8/7R w - - 0 1").unwrap();
+        let centre = Board::from_fen("8/8/3K4/8/3k4/8/8/7R w - - 0 1").unwrap();
+        assert_eq!(Endgame::find(&edge), Some((Endgame::Kxk, true)));
+        assert!(Eval::evaluate(&edge) > Eval::evaluate(&centre));
+        assert!(Eval::evaluate(&centre) > KNOWN_WIN);
+    }
+
+    #[test]
+    fn king_and_pawn_versus_king() {
+        let won = Board::from_fen("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1").unwrap();
+        assert!(Eval::evaluate(&won) < -KNOWN_WIN);
+
+        let drawn = Board::from_fen("8/8/8/8/8/4k3/4P3/4K3 w - - 0 1").unwrap();
+        assert_eq!(Eval::evaluate(&drawn), 0);
+        assert_eq!(Eval::evaluate(&drawn.mirrored()), 0);
+    }
+
+    #[test]
+    fn two_knights_are_drawn() {
+        let board = Board::from_fen("8/8/8/3k4/8/8/8/1N2K1N1 w - - 0 1").unwrap();
+        assert_eq!(Eval::evaluate(&board), 0);
+    }
+
+    #[test]
+    fn mirrored_endgames_are_swapped() {
+        assert_mirror_symmetric(
+            |board| {
+                (
+                    Endgame::find(board),
+                    [scale_factor(board, true), scale_factor(board, false)],
+                )
+            },
+            |(endgame, scale_factors)| {
+                (
+                    endgame.map(|(endgame, strong_white)| (endgame, !strong_white)),
+                    swap_sides(scale_factors),
+                )
+            },
+        );
+    }
+}
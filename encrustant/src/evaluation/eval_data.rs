@@ -6,6 +6,21 @@ pub type Score = i32;
 /// Table containing the value of each piece for every square on the board.
 pub type PieceSquareTable = [(i16, i16); 384];
 
+/// Middlegame and endgame value of each pawn structure term, indexed by the constants in `pawn_structure`.
+pub type PawnStructureTable = [(i16, i16); super::pawn_structure::PAWN_STRUCTURE_TERMS];
+
+/// Middlegame and endgame value of each mobility term, indexed by the constants in `mobility`.
+pub type MobilityTable = [(i16, i16); super::mobility::MOBILITY_TERMS];
+
+/// Middlegame and endgame value of each king safety term, indexed by the constants in `king_safety`.
+pub type KingSafetyTable = [(i16, i16); super::king_safety::KING_SAFETY_TERMS];
+
+/// Middlegame and endgame value of each threat term, indexed by the constants in `threats`.
+pub type ThreatTable = [(i16, i16); super::threats::THREAT_TERMS];
+
+/// Middlegame and endgame value of each material term, indexed by the constants in `material`.
+pub type MaterialTable = [(i16, i16); super::material::MATERIAL_TERMS];
+
 /// Piece square tables
 #[rustfmt::skip]
 pub const PIECE_SQUARE_TABLE: PieceSquareTable = [
@@ -70,3 +85,57 @@ pub const PIECE_SQUARE_TABLE: PieceSquareTable = [
 ];
 
 pub const PHASE_WEIGHTS: [i32; 5] = [-9, 90, 107, 189, 408];
+
+/// Pawn structure weights
+#[rustfmt::skip]
+pub const PAWN_STRUCTURE: PawnStructureTable = [
+(  -5, 10  ), (  -5, 15  ), (   5, 30  ), (  20, 55  ), (  45, 100 ), (  80, 160 ),
+(  10, 20  ), ( -10, -12 ), (  -8, -20 ), (  -6, -8  ),
+];
+
+/// Mobility weights, for knights, bishops, rooks then queens
+#[rustfmt::skip]
+pub const MOBILITY: MobilityTable = [
+(  22, -112), (  30, 8   ), (  44, 10  ), (  48, 16  ), (  55, 16  ), (  62, 24  ), (  68, 23  ), (  71, 14  ),
+(  35, 1   ),
+(   8, 10  ), (  27, 4   ), (  36, -12 ), (  42, 7   ), (  46, 17  ), (  47, 39  ), (  53, 31  ), (  53, 32  ),
+(  60, 43  ), (  51, 26  ), (  73, 26  ), (  87, -3  ), (   8, 62  ), ( -94, -47 ),
+(  77, -18 ), (  93, 2   ), (  85, 32  ), (  96, 13  ), (  98, 29  ), (  96, 42  ), ( 107, 36  ), ( 108, 43  ),
+( 103, 45  ), ( 112, 50  ), ( 100, 64  ), ( 121, 48  ), ( 125, 62  ), ( 145, 40  ), ( 151, 16  ),
+(  79, 111 ), (  53, 122 ), (  57, 60  ), (  63, 19  ), (  57, 96  ), (  66, 32  ), (  65, 84  ), (  66, 86  ),
+(  78, 32  ), (  84, 41  ), (  83, 33  ), (  87, 20  ), (  75, 43  ), (  87, 6   ), ( 102, 31  ), (  95, 12  ),
+( 117, 12  ), ( 119, 24  ), ( 107, 21  ), ( 129, 1   ), (  73, 4   ), ( 169, -58 ), ( 160, -38 ), ( 119, -74 ),
+( 114, -66 ), (  49, -65 ), (-115, -117), (  95, -117),
+];
+
+/// King safety weights
+#[rustfmt::skip]
+pub const KING_SAFETY: KingSafetyTable = [
+(  -1, 0   ), (  -7, 3   ), ( -21, 7   ), ( -14, -1  ),
+(   5, -7  ), ( -17, 8   ), ( -41, -3  ), (-145, 131 ),
+(  11, 7   ), (   7, 4   ), (   6, -2  ), ( -22, 7   ),
+( 116, 26  ), ( -26, 14  ), (   3, 1   ),
+];
+
+/// Threat weights
+#[rustfmt::skip]
+pub const THREATS: ThreatTable = [
+(  45, 40  ), (  45, 50  ), (  60, 30  ), (  55, 20  ),
+(  35, 30  ), (  40, 20  ), (  45, 15  ), (  25, 15  ),
+(  15, 10  ), (  20, 15  ),
+];
+
+/// Bishop pair and material imbalance weights, with a row for each of our piece types
+#[rustfmt::skip]
+pub const MATERIAL: MaterialTable = [
+(  30, 50  ),
+(   0, 0   ),
+(   1, 2   ), (  -2, -4  ),
+(   0, 0   ), (   0, 0   ), (   0, 0   ),
+(  -1, -2  ), (   0, 0   ), (   0, 0   ), (  -8, -10 ),
+(   0, 0   ), (   0, 0   ), (   0, 0   ), (  -6, -8  ), ( -10, -10 ),
+(   0, 0   ),
+(   0, 0   ), (   0, 0   ),
+(   0, 0   ), (   3, 4   ), (   3, 4   ),
+(   0, 0   ), (   0, 0   ), (   0, 0   ), (   4, 6   ),
+];
@@ -0,0 +1,189 @@
+//! Attacks on the squares around the king, and the pawns in front of it.
+
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+    move_generator::KING_MOVES_AT_SQUARE,
+};
+
+use super::{
+    eval_data::{KingSafetyTable, Score},
+    mobility::PieceAttacks,
+    pawn_structure::FRONT_SPANS,
+};
+
+/// Index of the king zone squares attacked by enemy knights, then bishops, rooks and queens.
+pub const KING_ZONE_ATTACKS: usize = 0;
+
+/// Index of one enemy piece attacking the king zone, then two, three, and four or more.
+pub const KING_ATTACKERS: usize = 4;
+
+/// Index of a friendly pawn one rank in front of the king on its file or an adjacent file,
+/// then two ranks, three or more ranks, and no pawn.
+pub const PAWN_SHELTER: usize = 8;
+
+/// Index of an enemy pawn one rank in front of the king on its file or an adjacent file,
+/// then two and three ranks.
+pub const PAWN_STORM: usize = 12;
+
+/// Number of king safety terms.
+pub const KING_SAFETY_TERMS: usize = 15;
+
+/// Name and first index of each group of terms.
+pub const KING_SAFETY_TERM_GROUPS: [(&str, usize); 4] = [
+    ("King zone attacks", KING_ZONE_ATTACKS),
+    ("King attackers", KING_ATTACKERS),
+    ("Pawn shelter", PAWN_SHELTER),
+    ("Pawn storm", PAWN_STORM),
+];
+
+/// How many of each king safety term the white and black kings have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KingSafety {
+    /// Counts for the white king, then the black king.
+    pub counts: [[u8; KING_SAFETY_TERMS]; 2],
+}
+
+impl KingSafety {
+    /// Finds the king safety terms of both kings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::king_safety::{KING_ATTACKERS, KingSafety, PAWN_SHELTER};
+    ///
+    /// let board = Board::from_fen("6k1/5ppp/8/8/8/8/5PPP/1q4K1 w - - 0 1").unwrap();
+    /// let king_safety = KingSafety::new(&board);
+    ///
+    /// // The queen attacks f1 and g1
+    /// assert_eq!(king_safety.counts[0][KING_ATTACKERS], 1);
+    /// // f2, g2 and h2
+    /// assert_eq!(king_safety.counts[0][PAWN_SHELTER], 3);
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        Self::from_attacks(
+            board,
+            &[
+                PieceAttacks::new(board, true),
+                PieceAttacks::new(board, false),
+            ],
+        )
+    }
+
+    /// Finds the king safety terms of both kings, given the attacks of white's pieces and black's.
+    #[must_use]
+    pub fn from_attacks(board: &Board, attacks: &[PieceAttacks; 2]) -> Self {
+        let white_pawns = *board.get_bit_board(Piece::WhitePawn);
+        let black_pawns = *board.get_bit_board(Piece::BlackPawn);
+        let white_king = board.get_bit_board(Piece::WhiteKing).first_square();
+        let black_king = board.get_bit_board(Piece::BlackKing).first_square();
+
+        let mut white_counts = [0; KING_SAFETY_TERMS];
+        Self::count_attacks(&mut white_counts, white_king, &attacks[1]);
+        Self::count_pawns(&mut white_counts, white_king, white_pawns, black_pawns);
+
+        let mut black_counts = [0; KING_SAFETY_TERMS];
+        Self::count_attacks(&mut black_counts, black_king, &attacks[0]);
+        Self::count_pawns(
+            &mut black_counts,
+            black_king.flip(),
+            black_pawns.flip_vertical(),
+            white_pawns.flip_vertical(),
+        );
+
+        Self {
+            counts: [white_counts, black_counts],
+        }
+    }
+
+    /// Counts the king zone squares attacked by each enemy piece type, and the number of attacking pieces.
+    #[allow(clippy::cast_possible_truncation)] // There are only 9 squares in the king zone
+    fn count_attacks(
+        counts: &mut [u8; KING_SAFETY_TERMS],
+        king_square: Square,
+        enemy_attacks: &PieceAttacks,
+    ) {
+        let king_zone = KING_MOVES_AT_SQUARE[king_square.usize()] | king_square.bit_board();
+
+        let mut attackers = 0;
+        for (piece_type, attacks) in enemy_attacks.iter() {
+            let attacked = (attacks & king_zone).count();
+            if attacked != 0 {
+                counts[KING_ZONE_ATTACKS + piece_type] += attacked as u8;
+                attackers += 1;
+            }
+        }
+
+        if attackers != 0 {
+            counts[KING_ATTACKERS + attackers.min(4) - 1] += 1;
+        }
+    }
+
+    /// Counts the nearest pawns in front of the king on its file and the adjacent files,
+    /// from the perspective of the king moving up the board.
+    fn count_pawns(
+        counts: &mut [u8; KING_SAFETY_TERMS],
+        king_square: Square,
+        our_pawns: BitBoard,
+        their_pawns: BitBoard,
+    ) {
+        let king_file = king_square.file();
+        for file in (king_file - 1).max(0)..=(king_file + 1).min(7) {
+            let front_span = FRONT_SPANS[king_square.offset(file - king_file).usize()];
+
+            let shelter = front_span & our_pawns;
+            if shelter.is_empty() {
+                counts[PAWN_SHELTER + 3] += 1;
+            } else {
+                let distance = shelter.first_square().usize() / 8 - king_square.usize() / 8;
+                counts[PAWN_SHELTER + distance.min(3) - 1] += 1;
+            }
+
+            let storm = front_span & their_pawns;
+            if storm.is_not_empty() {
+                let distance = storm.first_square().usize() / 8 - king_square.usize() / 8;
+                if distance <= 3 {
+                    counts[PAWN_STORM + distance - 1] += 1;
+                }
+            }
+        }
+    }
+
+    /// Scores the safety of the white king against the black king.
+    #[must_use]
+    pub fn score(&self, king_safety_table: &KingSafetyTable) -> (Score, Score) {
+        super::score_terms(&self.counts, king_safety_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        evaluation::tests::{assert_mirror_symmetric, swap_sides},
+    };
+
+    use super::{KING_ATTACKERS, KING_ZONE_ATTACKS, KingSafety, PAWN_SHELTER, PAWN_STORM};
+
+    #[test]
+    fn attacks_and_pawns() {
+        // The knight attacks g7 and the queen attacks f7 in the black king's zone,
+        // which is sheltered by f7 and g6 but not on the h file, where h6 is storming
+        let board = Board::from_fen("6k1/5p2/6pP/3Q1N2/8/8/8/6K1 w - - 0 1").unwrap();
+        let black = KingSafety::new(&board).counts[1];
+
+        assert_eq!(black[KING_ZONE_ATTACKS], 1);
+        assert_eq!(black[KING_ZONE_ATTACKS + 3], 1);
+        assert_eq!(black[KING_ATTACKERS + 1], 1);
+        assert_eq!(black[PAWN_SHELTER], 1);
+        assert_eq!(black[PAWN_SHELTER + 1], 1);
+        assert_eq!(black[PAWN_SHELTER + 3], 1);
+        assert_eq!(black[PAWN_STORM + 1], 1);
+    }
+
+    #[test]
+    fn mirrored_counts_are_swapped() {
+        assert_mirror_symmetric(|board| KingSafety::new(board).counts, swap_sides);
+    }
+}
@@ -0,0 +1,260 @@
+//! Bitbase of every king and pawn versus king position, which is generated on first use by
+//! working backwards from the positions where the pawn promotes or is lost.
+
+use std::sync::OnceLock;
+
+use crate::{
+    board::square::Square,
+    move_generator::{KING_MOVES_AT_SQUARE, PAWN_ATTACKS},
+};
+
+/// Either side to move, the pawn on files a to d and ranks 2 to 7, and each king on any square.
+const POSITIONS: usize = 2 * 24 * 64 * 64;
+
+// Results are flags so that the results of every move can be combined
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+/// Position from the perspective of the side with the pawn, which moves up the board.
+#[derive(Clone, Copy)]
+struct KpkPosition {
+    strong_to_move: bool,
+    strong_king: Square,
+    weak_king: Square,
+    pawn: Square,
+}
+
+impl KpkPosition {
+    const fn index(self) -> usize {
+        self.strong_king.usize()
+            | self.weak_king.usize() << 6
+            | (self.strong_to_move as usize) << 12
+            | (self.pawn.usize() % 8) << 13
+            | (6 - self.pawn.usize() / 8) << 15
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // Every part is less than 64
+    const fn from_index(index: usize) -> Self {
+        Self {
+            strong_to_move: (index >> 12) & 1 == 1,
+            strong_king: Square::from_index((index & 63) as i8),
+            weak_king: Square::from_index(((index >> 6) & 63) as i8),
+            pawn: Square::from_coords(6 - (index >> 15) as i8, ((index >> 13) & 3) as i8),
+        }
+    }
+
+    /// Result that is known without looking at the positions after each move.
+    fn initial_result(self) -> u8 {
+        let strong_king_moves = KING_MOVES_AT_SQUARE[self.strong_king.usize()];
+        let weak_king_moves = KING_MOVES_AT_SQUARE[self.weak_king.usize()];
+        let pawn_attacks = PAWN_ATTACKS.white_pawn_attacks_at_square[self.pawn.usize()];
+
+        if self.strong_king == self.weak_king
+            || strong_king_moves.get(&self.weak_king)
+            || self.strong_king == self.pawn
+            || self.weak_king == self.pawn
+            || (self.strong_to_move && pawn_attacks.get(&self.weak_king))
+        {
+            return INVALID;
+        }
+
+        if self.strong_to_move && self.pawn.rank() == 6 {
+            // Promotes without the new queen being captured
+            let promotion_square = self.pawn.up(1);
+            if self.strong_king != promotion_square
+                && self.weak_king != promotion_square
+                && (!weak_king_moves.get(&promotion_square)
+                    || strong_king_moves.get(&promotion_square))
+            {
+                return WIN;
+            }
+        }
+
+        if !self.strong_to_move {
+            let stalemate = (weak_king_moves & !(strong_king_moves | pawn_attacks)).is_empty();
+            let captures_pawn =
+                weak_king_moves.get(&self.pawn) && !strong_king_moves.get(&self.pawn);
+            if stalemate || captures_pawn {
+                return DRAW;
+            }
+        }
+
+        UNKNOWN
+    }
+
+    /// Result from the results of the positions after each move, which is still unknown
+    /// if the side to move can not reach a good position but can reach an unknown one.
+    fn result(self, results: &[u8]) -> u8 {
+        let mut reachable = INVALID;
+        if self.strong_to_move {
+            let mut moves = KING_MOVES_AT_SQUARE[self.strong_king.usize()];
+            while moves.is_not_empty() {
+                let square = moves.pop_square();
+                reachable |= results[Self {
+                    strong_to_move: false,
+                    strong_king: square,
+                    ..self
+                }
+                .index()];
+            }
+
+            if self.pawn.rank() < 6 {
+                let push = self.pawn.up(1);
+                reachable |= results[Self {
+                    strong_to_move: false,
+                    pawn: push,
+                    ..self
+                }
+                .index()];
+
+                if self.pawn.rank() == 1 && push != self.strong_king && push != self.weak_king {
+                    reachable |= results[Self {
+                        strong_to_move: false,
+                        pawn: push.up(1),
+                        ..self
+                    }
+                    .index()];
+                }
+            }
+        } else {
+            let mut moves = KING_MOVES_AT_SQUARE[self.weak_king.usize()];
+            while moves.is_not_empty() {
+                let square = moves.pop_square();
+                reachable |= results[Self {
+                    strong_to_move: true,
+                    weak_king: square,
+                    ..self
+                }
+                .index()];
+            }
+        }
+
+        let (good, bad) = if self.strong_to_move {
+            (WIN, DRAW)
+        } else {
+            (DRAW, WIN)
+        };
+        if reachable & good != 0 {
+            good
+        } else if reachable & UNKNOWN != 0 {
+            UNKNOWN
+        } else {
+            bad
+        }
+    }
+}
+
+/// One bit for each position, which is set if the side with the pawn wins.
+fn generate() -> Box<[u64]> {
+    let mut results: Vec<u8> = (0..POSITIONS)
+        .map(|index| KpkPosition::from_index(index).initial_result())
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for index in 0..POSITIONS {
+            if results[index] == UNKNOWN {
+                let result = KpkPosition::from_index(index).result(&results);
+                if result != UNKNOWN {
+                    results[index] = result;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut bitbase = vec![0; POSITIONS / 64].into_boxed_slice();
+    for (index, result) in results.into_iter().enumerate() {
+        if result == WIN {
+            bitbase[index / 64] |= 1 << (index % 64);
+        }
+    }
+    bitbase
+}
+
+/// Returns whether the side with the pawn wins, where the pawn moves up the board.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::board::square::Square;
+/// use encrustant::evaluation::kpk;
+///
+/// let square = |notation| Square::from_notation(notation).unwrap();
+///
+/// // The king can not catch the pawn
+/// assert!(kpk::probe(square("e1"), square("e2"), square("a1"), true));
+/// // The king holds the corner against a rook pawn
+/// assert!(!kpk::probe(square("a1"), square("a2"), square("a8"), true));
+/// ```
+#[must_use]
+pub fn probe(strong_king: Square, pawn: Square, weak_king: Square, strong_to_move: bool) -> bool {
+    static BITBASE: OnceLock<Box<[u64]>> = OnceLock::new();
+
+    // The bitbase only has pawns on files a to d, so mirror the rest
+    let mirror = |square: Square| {
+        if pawn.file() > 3 {
+            Square::from_index(square.index() ^ 7)
+        } else {
+            square
+        }
+    };
+    let index = KpkPosition {
+        strong_to_move,
+        strong_king: mirror(strong_king),
+        weak_king: mirror(weak_king),
+        pawn: mirror(pawn),
+    }
+    .index();
+
+    BITBASE.get_or_init(generate)[index / 64] >> (index % 64) & 1 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::square::Square;
+
+    use super::probe;
+
+    fn square(notation: &str) -> Square {
+        Square::from_notation(notation).unwrap()
+    }
+
+    #[test]
+    fn king_in_front() {
+        // The defending king has the opposition in front of the pawn, and the pawn is too far
+        // back to win even when the defending king has to give it up
+        assert!(!probe(square("e1"), square("e2"), square("e3"), true));
+        assert!(!probe(square("e1"), square("e2"), square("e3"), false));
+
+        // The king on the sixth rank in front of its pawn wins whoever is to move
+        assert!(probe(square("e6"), square("e5"), square("e8"), true));
+        assert!(probe(square("e6"), square("e5"), square("e8"), false));
+    }
+
+    #[test]
+    fn mirrored_files_match() {
+        for (strong_king, pawn, weak_king) in [("c3", "b4", "b6"), ("f6", "g5", "a8")] {
+            let mirror = |notation: &str| Square::from_index(square(notation).index() ^ 7);
+            for strong_to_move in [false, true] {
+                assert_eq!(
+                    probe(
+                        square(strong_king),
+                        square(pawn),
+                        square(weak_king),
+                        strong_to_move
+                    ),
+                    probe(
+                        mirror(strong_king),
+                        mirror(pawn),
+                        mirror(weak_king),
+                        strong_to_move
+                    )
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Bishop pair and the quadratic material imbalance.
+
+use crate::board::{Board, piece::Piece};
+
+use super::eval_data::{MaterialTable, Score};
+
+/// Index of having two or more bishops.
+pub const BISHOP_PAIR: usize = 0;
+
+/// Index of the products of the numbers of each pair of friendly piece types, see `our_imbalance_index`.
+pub const OUR_IMBALANCE: usize = 1;
+
+/// Index of the products of the numbers of friendly and less valuable enemy piece types, see
+/// `their_imbalance_index`.
+pub const THEIR_IMBALANCE: usize = 16;
+
+/// Number of material terms.
+pub const MATERIAL_TERMS: usize = 26;
+
+/// Name and first index of each group of terms.
+pub const MATERIAL_TERM_GROUPS: [(&str, usize); 3] = [
+    ("Bishop pair", BISHOP_PAIR),
+    ("Imbalance (own pieces)", OUR_IMBALANCE),
+    ("Imbalance (enemy pieces)", THEIR_IMBALANCE),
+];
+
+/// Index of the product of the numbers of friendly pieces of `piece_type` and `other_piece_type`,
+/// which are ordered pawn, knight, bishop, rook, queen and where `other_piece_type <= piece_type`.
+#[must_use]
+pub const fn our_imbalance_index(piece_type: usize, other_piece_type: usize) -> usize {
+    OUR_IMBALANCE + piece_type * (piece_type + 1) / 2 + other_piece_type
+}
+
+/// Index of the product of the numbers of friendly pieces of `piece_type` and enemy pieces of
+/// `other_piece_type`, where `other_piece_type < piece_type`.
+#[must_use]
+pub const fn their_imbalance_index(piece_type: usize, other_piece_type: usize) -> usize {
+    THEIR_IMBALANCE + piece_type * (piece_type - 1) / 2 + other_piece_type
+}
+
+/// How many of each material term white and black have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Material {
+    /// White's counts, then black's.
+    pub counts: [[u8; MATERIAL_TERMS]; 2],
+}
+
+impl Material {
+    /// Number of white pawns, knights, bishops, rooks and queens, then black's.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // count() should never return more than 64
+    pub fn piece_counts(board: &Board) -> [[u8; 5]; 2] {
+        let side_counts = |pieces: [Piece; 6]| {
+            let mut counts = [0; 5];
+            for (count, piece) in counts.iter_mut().zip(pieces) {
+                *count = board.get_bit_board(piece).count() as u8;
+            }
+            counts
+        };
+        [
+            side_counts(Piece::WHITE_PIECES),
+            side_counts(Piece::BLACK_PIECES),
+        ]
+    }
+
+    /// Finds the material terms of both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::material::{BISHOP_PAIR, Material, our_imbalance_index};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/PPP5/2BBK3 w - - 0 1").unwrap();
+    /// let material = Material::new(&board);
+    ///
+    /// assert_eq!(material.counts[0][BISHOP_PAIR], 1);
+    /// // Two bishops with three pawns
+    /// assert_eq!(material.counts[0][our_imbalance_index(2, 0)], 6);
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        Self::from_piece_counts(Self::piece_counts(board))
+    }
+
+    /// Finds the material terms from the numbers of each piece, as given by `Material::piece_counts`.
+    #[must_use]
+    pub fn from_piece_counts(piece_counts: [[u8; 5]; 2]) -> Self {
+        let [white, black] = piece_counts;
+        Self {
+            counts: [
+                Self::side_counts(white, black),
+                Self::side_counts(black, white),
+            ],
+        }
+    }
+
+    fn side_counts(ours: [u8; 5], theirs: [u8; 5]) -> [u8; MATERIAL_TERMS] {
+        let mut counts = [0; MATERIAL_TERMS];
+        counts[BISHOP_PAIR] = u8::from(ours[2] >= 2);
+
+        for piece_type in 0..5 {
+            for other_piece_type in 0..=piece_type {
+                counts[our_imbalance_index(piece_type, other_piece_type)] =
+                    ours[piece_type] * ours[other_piece_type];
+            }
+            // Products with the same piece type cancel out between the two sides
+            for other_piece_type in 0..piece_type {
+                counts[their_imbalance_index(piece_type, other_piece_type)] =
+                    ours[piece_type] * theirs[other_piece_type];
+            }
+        }
+
+        counts
+    }
+
+    /// Scores white's material combination against black's.
+    #[must_use]
+    pub fn score(&self, material_table: &MaterialTable) -> (Score, Score) {
+        super::score_terms(&self.counts, material_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        evaluation::tests::{assert_mirror_symmetric, swap_sides},
+    };
+
+    use super::{
+        BISHOP_PAIR, MATERIAL_TERMS, Material, THEIR_IMBALANCE, our_imbalance_index,
+        their_imbalance_index,
+    };
+
+    #[test]
+    fn indices_cover_every_term() {
+        let mut indices = vec![BISHOP_PAIR];
+        for piece_type in 0..5 {
+            for other_piece_type in 0..=piece_type {
+                indices.push(our_imbalance_index(piece_type, other_piece_type));
+            }
+        }
+        assert_eq!(indices.last(), Some(&(THEIR_IMBALANCE - 1)));
+        for piece_type in 1..5 {
+            for other_piece_type in 0..piece_type {
+                indices.push(their_imbalance_index(piece_type, other_piece_type));
+            }
+        }
+        assert_eq!(indices, (0..MATERIAL_TERMS).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn rook_against_two_minors() {
+        let board = Board::from_fen("4k3/pppp4/8/8/8/8/PPPP4/R3K1nb w - - 0 1").unwrap();
+        let counts = Material::new(&board).counts;
+
+        // One rook with four pawns, against a knight and a bishop
+        assert_eq!(counts[0][our_imbalance_index(3, 0)], 4);
+        assert_eq!(counts[0][their_imbalance_index(3, 1)], 1);
+        assert_eq!(counts[0][their_imbalance_index(3, 2)], 1);
+        assert_eq!(counts[1][our_imbalance_index(2, 1)], 1);
+        assert_eq!(counts[1][their_imbalance_index(2, 1)], 0);
+        assert_eq!(counts[1][BISHOP_PAIR], 0);
+    }
+
+    #[test]
+    fn bishop_pair() {
+        let board = Board::from_fen("2b1kb2/8/8/8/8/8/8/2B1KB2 w - - 0 1").unwrap();
+        assert_eq!(Material::new(&board).counts[0][BISHOP_PAIR], 1);
+        assert_eq!(Material::new(&board).counts[1][BISHOP_PAIR], 1);
+
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/3BK3 w - - 0 1").unwrap();
+        assert_eq!(Material::new(&board).counts[0][BISHOP_PAIR], 0);
+    }
+
+    #[test]
+    fn mirrored_counts_are_swapped() {
+        assert_mirror_symmetric(|board| Material::new(board).counts, swap_sides);
+    }
+}
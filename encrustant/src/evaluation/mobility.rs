@@ -0,0 +1,237 @@
+//! How many safe squares each piece attacks.
+
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+    consume_bit_board,
+    move_generator::{
+        KNIGHT_MOVES_AT_SQUARE,
+        slider_lookup::{
+            get_bishop_moves, get_rook_moves, relevant_bishop_blockers, relevant_rook_blockers,
+        },
+    },
+};
+
+use super::eval_data::{MobilityTable, Score};
+
+/// Index of a knight attacking no safe squares, followed by one index for each safe square up to 8.
+pub const KNIGHT_MOBILITY: usize = 0;
+
+/// Index of a bishop attacking no safe squares, followed by one index for each safe square up to 13.
+pub const BISHOP_MOBILITY: usize = 9;
+
+/// Index of a rook attacking no safe squares, followed by one index for each safe square up to 14.
+pub const ROOK_MOBILITY: usize = 23;
+
+/// Index of a queen attacking no safe squares, followed by one index for each safe square up to 27.
+pub const QUEEN_MOBILITY: usize = 38;
+
+/// Number of mobility terms.
+pub const MOBILITY_TERMS: usize = 66;
+
+/// Name and first index of the terms of each piece type.
+pub const MOBILITY_TERM_GROUPS: [(&str, usize); 4] = [
+    ("Knight mobility", KNIGHT_MOBILITY),
+    ("Bishop mobility", BISHOP_MOBILITY),
+    ("Rook mobility", ROOK_MOBILITY),
+    ("Queen mobility", QUEEN_MOBILITY),
+];
+
+/// Squares attacked by the pawns moving up the board.
+#[must_use]
+pub fn white_pawn_attacks(pawns: BitBoard) -> BitBoard {
+    ((pawns << 7) & BitBoard::NOT_H_FILE) | ((pawns << 9) & BitBoard::NOT_A_FILE)
+}
+
+/// Squares attacked by the pawns moving down the board.
+#[must_use]
+pub fn black_pawn_attacks(pawns: BitBoard) -> BitBoard {
+    ((pawns >> 7) & BitBoard::NOT_A_FILE) | ((pawns >> 9) & BitBoard::NOT_H_FILE)
+}
+
+/// Squares attacked by a knight, bishop, rook or queen on `square`.
+#[must_use]
+pub fn piece_attacks(piece: Piece, square: Square, occupied: BitBoard) -> BitBoard {
+    let bishop_attacks = || get_bishop_moves(square, occupied & relevant_bishop_blockers(square));
+    let rook_attacks = || get_rook_moves(square, occupied & relevant_rook_blockers(square));
+
+    match piece {
+        Piece::WhiteKnight | Piece::BlackKnight => KNIGHT_MOVES_AT_SQUARE[square.usize()],
+        Piece::WhiteBishop | Piece::BlackBishop => bishop_attacks(),
+        Piece::WhiteRook | Piece::BlackRook => rook_attacks(),
+        Piece::WhiteQueen | Piece::BlackQueen => bishop_attacks() | rook_attacks(),
+        _ => unreachable!("{piece:?} is not a knight, bishop, rook or queen"),
+    }
+}
+
+/// Most knights, bishops, rooks and queens that one side can have in a legal position, with every pawn
+/// promoted.
+const MAX_PIECES: usize = 15;
+
+/// Squares attacked by each knight, bishop, rook and queen of one side, found once and shared by the
+/// mobility, king safety and threat terms.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PieceAttacks {
+    /// Type of each piece, ordered knight, bishop, rook and queen, with the squares it attacks.
+    attacks: [(usize, BitBoard); MAX_PIECES],
+    piece_count: usize,
+}
+
+impl PieceAttacks {
+    /// Finds the squares attacked by each of white's pieces, or black's.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    /// use encrustant::evaluation::mobility::PieceAttacks;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1").unwrap();
+    /// let (piece_type, attacks) = PieceAttacks::new(&board, true).iter().next().unwrap();
+    ///
+    /// assert_eq!(piece_type, 0);
+    /// assert!(attacks.get(&Square::from_notation("c3").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board, white: bool) -> Self {
+        let pieces = if white {
+            Piece::WHITE_PIECES
+        } else {
+            Piece::BLACK_PIECES
+        };
+        let occupied = board.occupied();
+
+        let mut attacks = [(0, BitBoard::EMPTY); MAX_PIECES];
+        let mut piece_count = 0;
+        // Knight, bishop, rook, queen
+        for (piece_type, piece) in pieces[1..5].iter().enumerate() {
+            let mut bit_board = *board.get_bit_board(*piece);
+            consume_bit_board!(bit_board, square {
+                attacks[piece_count] = (piece_type, piece_attacks(*piece, square, occupied));
+                piece_count += 1;
+            });
+        }
+
+        Self {
+            attacks,
+            piece_count,
+        }
+    }
+
+    /// The type of each piece, ordered knight, bishop, rook and queen, with the squares it attacks.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, BitBoard)> {
+        self.attacks[..self.piece_count].iter().copied()
+    }
+}
+
+/// How many of each mobility term white and black have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Mobility {
+    /// White's counts, then black's.
+    pub counts: [[u8; MOBILITY_TERMS]; 2],
+}
+
+impl Mobility {
+    /// Counts the squares attacked by each piece that are not occupied by a friendly piece or
+    /// attacked by an enemy pawn.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::mobility::{KNIGHT_MOBILITY, Mobility, ROOK_MOBILITY};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/2p5/8/5N2/8/R3K3 w - - 0 1").unwrap();
+    /// let mobility = Mobility::new(&board);
+    ///
+    /// // d2, e5, g1, g5, h2 and h4, but not d4 which is attacked by the pawn
+    /// assert_eq!(mobility.counts[0][KNIGHT_MOBILITY + 6], 1);
+    /// // a2 to a8, b1, c1 and d1
+    /// assert_eq!(mobility.counts[0][ROOK_MOBILITY + 10], 1);
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        Self::from_attacks(
+            board,
+            &[
+                PieceAttacks::new(board, true),
+                PieceAttacks::new(board, false),
+            ],
+        )
+    }
+
+    /// Counts the safe squares attacked by each piece, given the attacks of white's pieces and black's.
+    #[must_use]
+    pub fn from_attacks(board: &Board, attacks: &[PieceAttacks; 2]) -> Self {
+        let white_pawns = *board.get_bit_board(Piece::WhitePawn);
+        let black_pawns = *board.get_bit_board(Piece::BlackPawn);
+
+        Self {
+            counts: [
+                Self::side_counts(
+                    &attacks[0],
+                    !(board.white_occupancy() | black_pawn_attacks(black_pawns)),
+                ),
+                Self::side_counts(
+                    &attacks[1],
+                    !(board.black_occupancy() | white_pawn_attacks(white_pawns)),
+                ),
+            ],
+        }
+    }
+
+    fn side_counts(attacks: &PieceAttacks, safe_squares: BitBoard) -> [u8; MOBILITY_TERMS] {
+        let mut counts = [0; MOBILITY_TERMS];
+        for (piece_type, attacks) in attacks.iter() {
+            let first_index = MOBILITY_TERM_GROUPS[piece_type].1;
+            counts[first_index + (attacks & safe_squares).count() as usize] += 1;
+        }
+        counts
+    }
+
+    /// Scores white's mobility against black's.
+    #[must_use]
+    pub fn score(&self, mobility_table: &MobilityTable) -> (Score, Score) {
+        super::score_terms(&self.counts, mobility_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::Board,
+        evaluation::tests::{assert_mirror_symmetric, swap_sides},
+    };
+
+    use super::{BISHOP_MOBILITY, KNIGHT_MOBILITY, Mobility, QUEEN_MOBILITY, ROOK_MOBILITY};
+
+    #[test]
+    fn blocked_pieces() {
+        let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+        let counts = Mobility::new(&board).counts;
+        assert_eq!(counts[0][BISHOP_MOBILITY], 2);
+        assert_eq!(counts[0][QUEEN_MOBILITY], 1);
+        assert_eq!(counts[0], counts[1]);
+    }
+
+    #[test]
+    fn open_board() {
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/RN1QK2B w - - 0 1").unwrap();
+        let counts = Mobility::new(&board).counts;
+
+        // a3, c3 and d2
+        assert_eq!(counts[0][KNIGHT_MOBILITY + 3], 1);
+        // g2 to a8
+        assert_eq!(counts[0][BISHOP_MOBILITY + 7], 1);
+        // a2 to a8, but not b1
+        assert_eq!(counts[0][ROOK_MOBILITY + 7], 1);
+        // c1, d2 to d8, a4 to c2 and e2 to h5
+        assert_eq!(counts[0][QUEEN_MOBILITY + 15], 1);
+        assert_eq!(counts[0].iter().sum::<u8>(), 4);
+        assert_eq!(counts[1].iter().sum::<u8>(), 0);
+    }
+
+    #[test]
+    fn mirrored_counts_are_swapped() {
+        assert_mirror_symmetric(|board| Mobility::new(board).counts, swap_sides);
+    }
+}
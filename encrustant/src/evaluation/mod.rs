@@ -6,39 +6,70 @@ use crate::{
 };
 
 pub mod eval_data;
-use eval_data::{PieceSquareTable, Score};
+
+/// Efficiently updatable neural network evaluation.
+pub mod nnue;
+
+/// Pawn structure terms.
+pub mod pawn_structure;
+
+/// Mobility terms.
+pub mod mobility;
+
+/// King safety terms.
+pub mod king_safety;
+
+/// Threat terms.
+pub mod threats;
+
+/// Bishop pair and material imbalance terms.
+pub mod material;
+
+/// King and pawn versus king bitbase.
+pub mod kpk;
+
+/// Known endgames and scale factors.
+pub mod endgame;
+
+/// Term by term evaluation.
+pub mod trace;
+
+use eval_data::{
+    KingSafetyTable, MaterialTable, MobilityTable, PawnStructureTable, PieceSquareTable, Score,
+    ThreatTable,
+};
+use king_safety::KingSafety;
+use material::Material;
+use mobility::{Mobility, PieceAttacks};
+use pawn_structure::PawnStructure;
+use threats::Threats;
+
+/// Middlegame and endgame score from white's perspective of terms counted for white and black.
+fn score_terms<const TERMS: usize>(
+    counts: &[[u8; TERMS]; 2],
+    table: &[(i16, i16); TERMS],
+) -> (Score, Score) {
+    let mut middle_game_score = 0;
+    let mut end_game_score = 0;
+    for (term, (middle_game_value, end_game_value)) in table.iter().enumerate() {
+        let count = Score::from(counts[0][term]) - Score::from(counts[1][term]);
+        middle_game_score += count * Score::from(*middle_game_value);
+        end_game_score += count * Score::from(*end_game_value);
+    }
+    (middle_game_score, end_game_score)
+}
 
 /// Evaluation functions.
 pub struct Eval;
 impl Eval {
     /// Gets the phase.
     #[must_use]
-    #[allow(clippy::cast_possible_wrap)] // count() should never return more than 64
     pub fn get_phase(board: &Board, phases: &[Score; 5]) -> Score {
+        let [white, black] = Material::piece_counts(board);
         let mut phase = 0;
-
-        phase += phases[0]
-            * ((*board.get_bit_board(Piece::WhitePawn) | *board.get_bit_board(Piece::BlackPawn))
-                .count()) as Score;
-
-        phase += phases[1]
-            * ((*board.get_bit_board(Piece::WhiteKnight)
-                | *board.get_bit_board(Piece::BlackKnight))
-            .count()) as Score;
-
-        phase += phases[2]
-            * ((*board.get_bit_board(Piece::WhiteBishop)
-                | *board.get_bit_board(Piece::BlackBishop))
-            .count()) as Score;
-
-        phase += phases[3]
-            * ((*board.get_bit_board(Piece::WhiteRook) | *board.get_bit_board(Piece::BlackRook))
-                .count()) as Score;
-
-        phase += phases[4]
-            * ((*board.get_bit_board(Piece::WhiteQueen) | *board.get_bit_board(Piece::BlackQueen))
-                .count()) as Score;
-
+        for (piece_type, weight) in phases.iter().enumerate() {
+            phase += weight * Score::from(white[piece_type] + black[piece_type]);
+        }
         phase
     }
 
@@ -106,31 +137,80 @@ impl Eval {
         (total_middle_game_score, total_end_game_score)
     }
 
+    /// Sum of the phase weights of every piece in the starting position.
+    #[must_use]
+    pub const fn total_phase(phases: &[Score; 5]) -> Score {
+        phases[0] * 16 + phases[1] * 4 + phases[2] * 4 + phases[3] * 4 + phases[4] * 2
+    }
+
     /// Returns an estimated score of the position for the side playing, using the provided evaluation parameters.
     #[must_use]
+    #[allow(clippy::too_many_arguments)] // One table for each group of terms
     pub fn evaluate_with_parameters(
         piece_square_tables: &PieceSquareTable,
+        pawn_structure_table: &PawnStructureTable,
+        mobility_table: &MobilityTable,
+        king_safety_table: &KingSafetyTable,
+        threat_table: &ThreatTable,
+        material_table: &MaterialTable,
         phases: &[Score; 5],
         board: &Board,
     ) -> Score {
-        #[rustfmt::skip]
-        let total_phase = {
-            phases[0] * 16
-            + phases[1] * 4
-            + phases[2] * 4
-            + phases[3] * 4
-            + phases[4] * 2
-        };
-
-        let (total_middle_game_score, total_end_game_score) =
+        if let Some(score) = endgame::evaluate(board) {
+            return score;
+        }
+
+        let (piece_middle_game_score, piece_end_game_score) =
             Self::raw_evaluate_with_parameters(piece_square_tables, board);
+        let (pawn_middle_game_score, pawn_end_game_score) =
+            PawnStructure::new(board).score(pawn_structure_table);
+        let (activity_middle_game_score, activity_end_game_score) =
+            Self::piece_activity_with_parameters(
+                mobility_table,
+                king_safety_table,
+                threat_table,
+                board,
+            );
+        let (material_middle_game_score, material_end_game_score) =
+            Material::new(board).score(material_table);
         let phase = Self::get_phase(board, phases);
-        Self::calculate_score(
+        let score = Self::calculate_score(
             phase,
-            total_phase,
-            total_middle_game_score,
-            total_end_game_score,
-        ) * if board.white_to_move { 1 } else { -1 }
+            Self::total_phase(phases),
+            piece_middle_game_score
+                + pawn_middle_game_score
+                + activity_middle_game_score
+                + material_middle_game_score,
+            piece_end_game_score
+                + pawn_end_game_score
+                + activity_end_game_score
+                + material_end_game_score,
+        );
+        endgame::scale(board, score) * if board.white_to_move { 1 } else { -1 }
+    }
+
+    /// Middlegame and endgame score of mobility, king safety and threats from white's perspective.
+    #[must_use]
+    pub fn piece_activity_with_parameters(
+        mobility_table: &MobilityTable,
+        king_safety_table: &KingSafetyTable,
+        threat_table: &ThreatTable,
+        board: &Board,
+    ) -> (Score, Score) {
+        let attacks = [
+            PieceAttacks::new(board, true),
+            PieceAttacks::new(board, false),
+        ];
+        let (mobility_middle_game_score, mobility_end_game_score) =
+            Mobility::from_attacks(board, &attacks).score(mobility_table);
+        let (king_middle_game_score, king_end_game_score) =
+            KingSafety::from_attacks(board, &attacks).score(king_safety_table);
+        let (threat_middle_game_score, threat_end_game_score) =
+            Threats::from_attacks(board, &attacks).score(threat_table);
+        (
+            mobility_middle_game_score + king_middle_game_score + threat_middle_game_score,
+            mobility_end_game_score + king_end_game_score + threat_end_game_score,
+        )
     }
 
     /// Returns an estimated score of the position for the side playing.
@@ -138,6 +218,11 @@ impl Eval {
     pub fn evaluate(board: &Board) -> Score {
         Self::evaluate_with_parameters(
             &eval_data::PIECE_SQUARE_TABLE,
+            &eval_data::PAWN_STRUCTURE,
+            &eval_data::MOBILITY,
+            &eval_data::KING_SAFETY,
+            &eval_data::THREATS,
+            &eval_data::MATERIAL,
             &eval_data::PHASE_WEIGHTS,
             board,
         )
@@ -147,11 +232,60 @@ impl Eval {
     pub fn raw_evaluate(board: &Board) -> (Score, Score) {
         Self::raw_evaluate_with_parameters(&eval_data::PIECE_SQUARE_TABLE, board)
     }
+
+    /// Checks that the evaluation does not favour either colour, using `Board::mirrored`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the scores of the mirrored position are not exactly negated.
+    pub fn assert_symmetric(board: &Board) {
+        let mirrored = board.mirrored();
+
+        let (middle_game_score, end_game_score) = Self::raw_evaluate(board);
+        assert_eq!(
+            Self::raw_evaluate(&mirrored),
+            (-middle_game_score, -end_game_score),
+            "{board} is not evaluated as the opposite of {mirrored}"
+        );
+
+        // The side to move is swapped too, so the score for the side to move stays the same
+        assert_eq!(
+            Self::evaluate(&mirrored),
+            Self::evaluate(board),
+            "{board} is not evaluated as the opposite of {mirrored}"
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{board::Board, evaluation::Eval};
+    use core::fmt::Debug;
+
+    use crate::{
+        board::Board,
+        evaluation::{
+            Eval,
+            king_safety::KingSafety,
+            mobility::{Mobility, PieceAttacks},
+            threats::Threats,
+        },
+        tests::{TEST_FENS, test_positions},
+    };
+
+    /// Checks that `find` on each mirrored test position gives the same as `mirror` of `find` on the original.
+    pub fn assert_mirror_symmetric<T: PartialEq + Debug>(
+        find: impl Fn(&Board) -> T,
+        mirror: impl Fn(T) -> T,
+    ) {
+        for board in test_positions() {
+            assert_eq!(find(&board.mirrored()), mirror(find(&board)), "{board}");
+        }
+    }
+
+    /// Swaps white's and black's values.
+    pub const fn swap_sides<T: Copy>([white, black]: [T; 2]) -> [T; 2] {
+        [black, white]
+    }
 
     #[test]
     fn advanced_pawn_worth_more() {
@@ -169,4 +303,33 @@ mod tests {
         let knight_on_the_edge = Board::from_fen("7k/8/8/8/7n/8/8/K7 b - - 0 1").unwrap();
         assert!(Eval::evaluate(&centralised_knight) > Eval::evaluate(&knight_on_the_edge));
     }
+
+    #[test]
+    fn shared_attacks_give_the_same_counts() {
+        for board in test_positions() {
+            let attacks = [
+                PieceAttacks::new(&board, true),
+                PieceAttacks::new(&board, false),
+            ];
+            assert_eq!(
+                Mobility::from_attacks(&board, &attacks),
+                Mobility::new(&board)
+            );
+            assert_eq!(
+                KingSafety::from_attacks(&board, &attacks),
+                KingSafety::new(&board)
+            );
+            assert_eq!(
+                Threats::from_attacks(&board, &attacks),
+                Threats::new(&board)
+            );
+        }
+    }
+
+    #[test]
+    fn evaluation_is_symmetric() {
+        for (_, _, fen) in TEST_FENS {
+            Eval::assert_symmetric(&Board::from_fen(fen).unwrap());
+        }
+    }
 }
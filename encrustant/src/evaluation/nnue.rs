@@ -0,0 +1,486 @@
+use core::fmt;
+use std::{io, path::Path, sync::Arc};
+
+use crate::{
+    board::{Board, piece::Piece, square::Square},
+    consume_bit_board,
+};
+
+use super::eval_data::Score;
+
+/// Number of input features, one for each piece on each square.
+pub const INPUT_SIZE: usize = 768;
+
+/// Number of hidden neurons for each perspective.
+pub const HIDDEN_SIZE: usize = 128;
+
+/// Quantisation of the feature weights and biases.
+pub const QA: i32 = 255;
+
+/// Quantisation of the output weights.
+pub const QB: i32 = 64;
+
+/// Converts the output of the network to centipawns.
+pub const SCALE: i32 = 400;
+
+/// Largest magnitude of an output weight, so that an activation times a weight fits in an `i16`.
+pub const MAX_OUTPUT_WEIGHT: i16 = 127;
+
+/// Size of a network file, not counting the padding that may follow.
+pub const NETWORK_BYTES: usize = (INPUT_SIZE * HIDDEN_SIZE + HIDDEN_SIZE + 2 * HIDDEN_SIZE + 1) * 2;
+
+/// Keeps evaluations of broken networks well away from checkmate scores.
+const MAX_EVALUATION: Score = 30000;
+
+/// Most pieces that can be on the board, and so the most features active in an accumulator.
+const MAX_ACTIVE_FEATURES: i32 = 32;
+
+// The output layer is summed in an `i32`
+#[allow(clippy::cast_possible_wrap)]
+const _: () = assert!(
+    2 * HIDDEN_SIZE as i64 * (QA * QA) as i64 * MAX_OUTPUT_WEIGHT as i64 <= i32::MAX as i64
+);
+
+/// Values of the hidden neurons for one perspective.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(C, align(64))]
+pub struct Accumulator(pub [i16; HIDDEN_SIZE]);
+
+impl Accumulator {
+    // Networks are checked on load so that a position's accumulator fits in an `i16`, but a move can add a
+    // piece before removing another, so values are allowed to wrap in between
+    fn add(&mut self, weights: &Self) {
+        for (value, weight) in self.0.iter_mut().zip(weights.0) {
+            *value = value.wrapping_add(weight);
+        }
+    }
+
+    fn subtract(&mut self, weights: &Self) {
+        for (value, weight) in self.0.iter_mut().zip(weights.0) {
+            *value = value.wrapping_sub(weight);
+        }
+    }
+}
+
+/// Hidden neurons seen from white's and black's perspective.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Accumulators {
+    /// Pieces as they are.
+    pub white: Accumulator,
+
+    /// Pieces with colours swapped and the board flipped, so black's pieces look like white's.
+    pub black: Accumulator,
+}
+
+/// Why a network could not be loaded.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetworkError {
+    /// The file could not be read.
+    Io(io::ErrorKind),
+
+    /// The file is not [`NETWORK_BYTES`] long, or that rounded up to a multiple of 64.
+    WrongSize(usize),
+
+    /// An output weight is larger than [`MAX_OUTPUT_WEIGHT`].
+    OutputWeightOutOfRange(i16),
+
+    /// The hidden neuron at this index could overflow an `i16` with a full board, because its bias plus 32 times
+    /// its largest feature weight is too large.
+    AccumulatorOutOfRange(usize),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(kind) => write!(f, "could not read network: {kind}"),
+            Self::WrongSize(size) => write!(
+                f,
+                "network is {size} bytes but should be {NETWORK_BYTES} (or padded to a multiple of 64)"
+            ),
+            Self::OutputWeightOutOfRange(weight) => write!(
+                f,
+                "output weight {weight} is outside of -{MAX_OUTPUT_WEIGHT}..={MAX_OUTPUT_WEIGHT}"
+            ),
+            Self::AccumulatorOutOfRange(neuron) => write!(
+                f,
+                "hidden neuron {neuron} can overflow when {MAX_ACTIVE_FEATURES} pieces are on the board"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// Returns the input feature of a piece on a square, from white's and black's perspective.
+#[must_use]
+pub const fn feature_indices(piece: Piece, square: Square) -> (usize, usize) {
+    (
+        piece as usize * 64 + square.usize(),
+        piece.opposite_colour() as usize * 64 + square.flip().usize(),
+    )
+}
+
+/// Squares each activation after clipping it to `0..=QA`, then multiplies by the weights and sums.
+fn screlu_dot(accumulator: &Accumulator, weights: &Accumulator) -> i32 {
+    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+    {
+        screlu_dot_avx2(accumulator, weights)
+    }
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "avx2")))]
+    {
+        screlu_dot_scalar(accumulator, weights)
+    }
+}
+
+// Only compared against in tests when AVX2 is enabled
+#[cfg_attr(all(target_arch = "x86_64", target_feature = "avx2"), allow(dead_code))]
+fn screlu_dot_scalar(accumulator: &Accumulator, weights: &Accumulator) -> i32 {
+    accumulator
+        .0
+        .iter()
+        .zip(weights.0)
+        .map(|(value, weight)| {
+            let activation = i32::from(*value).clamp(0, QA);
+            activation * activation * i32::from(weight)
+        })
+        .sum()
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+// QA fits in an i16, and accumulators are aligned to 64 bytes
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_ptr_alignment
+)]
+fn screlu_dot_avx2(accumulator: &Accumulator, weights: &Accumulator) -> i32 {
+    use core::arch::x86_64::{
+        __m256i, _mm_add_epi32, _mm_cvtsi128_si32, _mm_shuffle_epi32, _mm_unpackhi_epi64,
+        _mm256_add_epi32, _mm256_castsi256_si128, _mm256_extracti128_si256, _mm256_load_si256,
+        _mm256_madd_epi16, _mm256_max_epi16, _mm256_min_epi16, _mm256_mullo_epi16,
+        _mm256_set1_epi16, _mm256_setzero_si256,
+    };
+
+    const LANES: usize = 16;
+
+    // SAFETY: AVX2 is enabled at compile time, and accumulators are aligned to 64 bytes with a length that is a
+    // multiple of the lane count
+    unsafe {
+        let zero = _mm256_setzero_si256();
+        let max = _mm256_set1_epi16(QA as i16);
+        let mut sum = _mm256_setzero_si256();
+        for index in (0..HIDDEN_SIZE).step_by(LANES) {
+            let values = _mm256_load_si256(accumulator.0.as_ptr().add(index).cast::<__m256i>());
+            let weights = _mm256_load_si256(weights.0.as_ptr().add(index).cast::<__m256i>());
+            let activations = _mm256_min_epi16(_mm256_max_epi16(values, zero), max);
+
+            // Multiplying by the weight first keeps every product in 16 bits, then the second multiplication
+            // widens to 32 bits and adds neighbouring pairs
+            let weighted = _mm256_mullo_epi16(activations, weights);
+            sum = _mm256_add_epi32(sum, _mm256_madd_epi16(weighted, activations));
+        }
+
+        let sum = _mm_add_epi32(
+            _mm256_castsi256_si128(sum),
+            _mm256_extracti128_si256::<1>(sum),
+        );
+        let sum = _mm_add_epi32(sum, _mm_unpackhi_epi64(sum, sum));
+        let sum = _mm_add_epi32(sum, _mm_shuffle_epi32::<0b01>(sum));
+        _mm_cvtsi128_si32(sum)
+    }
+}
+
+/// Efficiently updatable neural network with 768 inputs, a hidden layer of [`HIDDEN_SIZE`] neurons for each
+/// perspective with squared clipped rectified linear activation, and one output.
+///
+/// The file format is every value as a little endian `i16`: the feature weights of each input in turn, the
+/// feature biases, the output weights for the side to move then the other side, and the output bias.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Network {
+    /// Weights added to the accumulator when each input feature is active, [`INPUT_SIZE`] long.
+    pub feature_weights: Box<[Accumulator]>,
+
+    /// Starting values of the accumulators.
+    pub feature_biases: Accumulator,
+
+    /// Weights of the side to move's accumulator and then the other side's.
+    pub output_weights: [Accumulator; 2],
+
+    /// Added to the output.
+    pub output_bias: i16,
+}
+
+impl Network {
+    /// Reads a network in the format described on [`Network`].
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `bytes` is the wrong length, an output weight is out of range, or the feature weights
+    /// and biases could overflow an accumulator.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NetworkError> {
+        if bytes.len() != NETWORK_BYTES && bytes.len() != NETWORK_BYTES.next_multiple_of(64) {
+            return Err(NetworkError::WrongSize(bytes.len()));
+        }
+
+        let mut values = bytes
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]));
+        let mut read_accumulator = || {
+            let mut accumulator = Accumulator([0; HIDDEN_SIZE]);
+            for value in &mut accumulator.0 {
+                // The length was checked above
+                *value = values.next().unwrap_or_default();
+            }
+            accumulator
+        };
+
+        let feature_weights: Box<[Accumulator]> =
+            (0..INPUT_SIZE).map(|_| read_accumulator()).collect();
+        let feature_biases = read_accumulator();
+        let output_weights = [read_accumulator(), read_accumulator()];
+        let output_bias = values.next().unwrap_or_default();
+
+        if let Some(weight) = output_weights
+            .iter()
+            .flat_map(|weights| weights.0)
+            .find(|weight| weight.abs() > MAX_OUTPUT_WEIGHT)
+        {
+            return Err(NetworkError::OutputWeightOutOfRange(weight));
+        }
+
+        for neuron in 0..HIDDEN_SIZE {
+            let largest_weight = feature_weights
+                .iter()
+                .map(|weights| i32::from(weights.0[neuron].unsigned_abs()))
+                .max()
+                .unwrap_or_default();
+            let bias = i32::from(feature_biases.0[neuron].unsigned_abs());
+            if bias + MAX_ACTIVE_FEATURES * largest_weight > i32::from(i16::MAX) {
+                return Err(NetworkError::AccumulatorOutOfRange(neuron));
+            }
+        }
+
+        Ok(Self {
+            feature_weights,
+            feature_biases,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    /// Reads a network file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file could not be read or is not a valid network.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NetworkError> {
+        let bytes = std::fs::read(path).map_err(|error| NetworkError::Io(error.kind()))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Writes the network in the format described on [`Network`], padded to a multiple of 64 bytes.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(NETWORK_BYTES.next_multiple_of(64));
+        for accumulator in self
+            .feature_weights
+            .iter()
+            .chain([&self.feature_biases])
+            .chain(&self.output_weights)
+        {
+            for value in accumulator.0 {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+        bytes.extend(self.output_bias.to_le_bytes());
+        bytes.resize(NETWORK_BYTES.next_multiple_of(64), 0);
+        bytes
+    }
+
+    /// Returns the network embedded with the `embedded-network` feature.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the embedded network is invalid.
+    #[must_use]
+    #[allow(clippy::missing_const_for_fn)] // Not const with the feature enabled
+    pub fn embedded() -> Option<Arc<Self>> {
+        #[cfg(feature = "embedded-network")]
+        {
+            static EMBEDDED: std::sync::LazyLock<Arc<Network>> = std::sync::LazyLock::new(|| {
+                Arc::new(
+                    Network::from_bytes(include_bytes!(env!("ENCRUSTANT_NETWORK")))
+                        .expect("Embedded network is invalid"),
+                )
+            });
+            Some(Arc::clone(&EMBEDDED))
+        }
+        #[cfg(not(feature = "embedded-network"))]
+        {
+            None
+        }
+    }
+
+    /// Computes the accumulators of a position from scratch.
+    #[must_use]
+    pub fn refresh(&self, board: &Board) -> Accumulators {
+        let mut accumulators = Accumulators {
+            white: self.feature_biases,
+            black: self.feature_biases,
+        };
+        for piece in Piece::ALL_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                self.add_piece(&mut accumulators, piece, square);
+            });
+        }
+        accumulators
+    }
+
+    /// Updates the accumulators for a piece appearing on a square.
+    pub fn add_piece(&self, accumulators: &mut Accumulators, piece: Piece, square: Square) {
+        let (white, black) = feature_indices(piece, square);
+        accumulators.white.add(&self.feature_weights[white]);
+        accumulators.black.add(&self.feature_weights[black]);
+    }
+
+    /// Updates the accumulators for a piece leaving a square.
+    pub fn remove_piece(&self, accumulators: &mut Accumulators, piece: Piece, square: Square) {
+        let (white, black) = feature_indices(piece, square);
+        accumulators.white.subtract(&self.feature_weights[white]);
+        accumulators.black.subtract(&self.feature_weights[black]);
+    }
+
+    /// Returns an estimated score of the position for the side playing, from its accumulators.
+    #[must_use]
+    pub fn evaluate(&self, accumulators: &Accumulators, white_to_move: bool) -> Score {
+        let (us, them) = if white_to_move {
+            (&accumulators.white, &accumulators.black)
+        } else {
+            (&accumulators.black, &accumulators.white)
+        };
+        let output =
+            screlu_dot(us, &self.output_weights[0]) + screlu_dot(them, &self.output_weights[1]);
+
+        // Activations were squared, so divide out the extra QA before adding the bias. Scaling can overflow an
+        // i32 for large outputs
+        let evaluation = (i64::from(output / QA) + i64::from(self.output_bias)) * i64::from(SCALE)
+            / i64::from(QA * QB);
+        Score::try_from(evaluation.clamp(-i64::from(MAX_EVALUATION), i64::from(MAX_EVALUATION)))
+            .unwrap_or_default()
+    }
+
+    /// Returns an estimated score of the position for the side playing, computing the accumulators from scratch.
+    #[must_use]
+    pub fn evaluate_board(&self, board: &Board) -> Score {
+        self.evaluate(&self.refresh(board), board.white_to_move)
+    }
+
+    /// Creates a network with random weights small enough to never overflow, for testing.
+    #[cfg(test)]
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn random(seed: u64) -> Self {
+        let mut state = seed;
+        let mut random = |range: i16| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % (2 * range as u64 + 1)) as i16 - range
+        };
+        let mut random_accumulator = |range: i16| {
+            let mut accumulator = Accumulator([0; HIDDEN_SIZE]);
+            for value in &mut accumulator.0 {
+                *value = random(range);
+            }
+            accumulator
+        };
+
+        Self {
+            feature_weights: (0..INPUT_SIZE).map(|_| random_accumulator(40)).collect(),
+            feature_biases: random_accumulator(80),
+            output_weights: [
+                random_accumulator(MAX_OUTPUT_WEIGHT),
+                random_accumulator(MAX_OUTPUT_WEIGHT),
+            ],
+            output_bias: random_accumulator(1000).0[0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, tests::TEST_FENS};
+
+    use super::{
+        Accumulator, HIDDEN_SIZE, MAX_OUTPUT_WEIGHT, NETWORK_BYTES, Network, NetworkError, QA,
+    };
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let network = Network::random(1);
+        let bytes = network.to_bytes();
+        assert_eq!(bytes.len() % 64, 0);
+        assert_eq!(Network::from_bytes(&bytes), Ok(network.clone()));
+        assert_eq!(
+            Network::from_bytes(&bytes[..NETWORK_BYTES]),
+            Ok(network.clone())
+        );
+        assert_eq!(
+            Network::from_bytes(&bytes[..NETWORK_BYTES - 2]),
+            Err(NetworkError::WrongSize(NETWORK_BYTES - 2))
+        );
+
+        let mut out_of_range = network.clone();
+        out_of_range.output_weights[1].0[3] = 200;
+        assert_eq!(
+            Network::from_bytes(&out_of_range.to_bytes()),
+            Err(NetworkError::OutputWeightOutOfRange(200))
+        );
+
+        let mut out_of_range = network;
+        out_of_range.feature_weights[100].0[5] = 1000;
+        out_of_range.feature_biases.0[5] = 1000;
+        assert_eq!(
+            Network::from_bytes(&out_of_range.to_bytes()),
+            Err(NetworkError::AccumulatorOutOfRange(5))
+        );
+    }
+
+    #[test]
+    fn large_outputs_do_not_overflow() {
+        let mut network = Network::random(5);
+        network.feature_biases = Accumulator([i16::try_from(QA).unwrap(); HIDDEN_SIZE]);
+        network.output_weights = [Accumulator([MAX_OUTPUT_WEIGHT; HIDDEN_SIZE]); 2];
+        network.output_bias = i16::MAX;
+        let board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(network.evaluate(&network.refresh(&board), true) > 0);
+    }
+
+    #[test]
+    fn evaluation_is_symmetric() {
+        let network = Network::random(2);
+        for (_, _, fen) in TEST_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                network.evaluate_board(&board.mirrored()),
+                network.evaluate_board(&board),
+                "{board} is not evaluated as the opposite of its mirror"
+            );
+        }
+    }
+
+    #[test]
+    fn simd_matches_scalar() {
+        let network = Network::random(3);
+        let mut accumulator = Accumulator([0; HIDDEN_SIZE]);
+        for (index, value) in accumulator.0.iter_mut().enumerate() {
+            // Cover values below zero and above QA
+            *value = (i16::try_from(index).unwrap() - 30) * 3;
+        }
+        assert_eq!(
+            super::screlu_dot(&accumulator, &network.output_weights[0]),
+            super::screlu_dot_scalar(&accumulator, &network.output_weights[0])
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! Passed, isolated, doubled and backward pawns.
+
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece},
+    consume_bit_board,
+    move_generator::PAWN_ATTACKS,
+};
+
+use super::eval_data::{PawnStructureTable, Score};
+
+/// Index of a passed pawn on the second rank, followed by one index for each rank up to the seventh.
+pub const PASSED_PAWN: usize = 0;
+
+/// Index of a passed pawn that is defended by or next to another pawn.
+pub const CONNECTED_PASSED_PAWN: usize = 6;
+
+/// Index of a pawn without friendly pawns on the adjacent files.
+pub const ISOLATED_PAWN: usize = 7;
+
+/// Index of a pawn with a friendly pawn ahead on the same file.
+pub const DOUBLED_PAWN: usize = 8;
+
+/// Index of a pawn that is behind the pawns on the adjacent files and can not safely advance.
+pub const BACKWARD_PAWN: usize = 9;
+
+/// Number of pawn structure terms.
+pub const PAWN_STRUCTURE_TERMS: usize = 10;
+
+/// Name of each pawn structure term.
+pub const PAWN_STRUCTURE_TERM_NAMES: [&str; PAWN_STRUCTURE_TERMS] = [
+    "Passed pawn (rank 2)",
+    "Passed pawn (rank 3)",
+    "Passed pawn (rank 4)",
+    "Passed pawn (rank 5)",
+    "Passed pawn (rank 6)",
+    "Passed pawn (rank 7)",
+    "Connected passed pawn",
+    "Isolated pawn",
+    "Doubled pawn",
+    "Backward pawn",
+];
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+
+/// Squares on the files next to `file`.
+const fn adjacent_files(file: usize) -> u64 {
+    let mut bits = 0;
+    if file > 0 {
+        bits |= FILE_A << (file - 1);
+    }
+    if file < 7 {
+        bits |= FILE_A << (file + 1);
+    }
+    bits
+}
+
+/// Squares on the adjacent files of each file.
+const ADJACENT_FILES: [BitBoard; 8] = {
+    let mut adjacent_files_at_file = [BitBoard::EMPTY; 8];
+    let mut file = 0;
+    while file < 8 {
+        adjacent_files_at_file[file] = BitBoard::new(adjacent_files(file));
+        file += 1;
+    }
+    adjacent_files_at_file
+};
+
+/// Squares ahead of each square on the same file, from white's perspective.
+pub(super) const FRONT_SPANS: [BitBoard; 64] = {
+    let mut front_spans = [BitBoard::EMPTY; 64];
+    let mut index = 0;
+    while index < 64 {
+        front_spans[index] = BitBoard::new((FILE_A << (index % 8)) & ranks_above(index));
+        index += 1;
+    }
+    front_spans
+};
+
+/// Squares on the ranks above the rank of the square at `index`.
+const fn ranks_above(index: usize) -> u64 {
+    let rank = index / 8;
+    if rank == 7 { 0 } else { !0 << ((rank + 1) * 8) }
+}
+
+/// Squares that an enemy pawn stopping a pawn on each square could be on, from white's perspective.
+const PASSED_PAWN_SPANS: [BitBoard; 64] = {
+    let mut passed_pawn_spans = [BitBoard::EMPTY; 64];
+    let mut index = 0;
+    while index < 64 {
+        passed_pawn_spans[index] = BitBoard::new(
+            ((FILE_A << (index % 8)) | adjacent_files(index % 8)) & ranks_above(index),
+        );
+        index += 1;
+    }
+    passed_pawn_spans
+};
+
+/// Squares on the adjacent files that are on the same rank or behind each square, from white's perspective.
+const SUPPORT_SPANS: [BitBoard; 64] = {
+    let mut support_spans = [BitBoard::EMPTY; 64];
+    let mut index = 0;
+    while index < 64 {
+        support_spans[index] = BitBoard::new(adjacent_files(index % 8) & !ranks_above(index));
+        index += 1;
+    }
+    support_spans
+};
+
+/// How many of each pawn structure term white and black have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PawnStructure {
+    /// White's counts, then black's.
+    pub counts: [[u8; PAWN_STRUCTURE_TERMS]; 2],
+}
+
+impl PawnStructure {
+    /// Finds the pawn structure terms of both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::pawn_structure::{DOUBLED_PAWN, ISOLATED_PAWN, PASSED_PAWN, PawnStructure};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/2P5/8/2P5/8/4K3 w - - 0 1").unwrap();
+    /// let pawn_structure = PawnStructure::new(&board);
+    /// assert_eq!(pawn_structure.counts[0][PASSED_PAWN + 3], 1);
+    /// assert_eq!(pawn_structure.counts[0][ISOLATED_PAWN], 2);
+    /// assert_eq!(pawn_structure.counts[0][DOUBLED_PAWN], 1);
+    /// assert_eq!(pawn_structure.counts[1], [0; 10]);
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        let white_pawns = *board.get_bit_board(Piece::WhitePawn);
+        let black_pawns = *board.get_bit_board(Piece::BlackPawn);
+
+        Self {
+            counts: [
+                Self::side_counts(white_pawns, black_pawns),
+                Self::side_counts(black_pawns.flip_vertical(), white_pawns.flip_vertical()),
+            ],
+        }
+    }
+
+    /// Counts the terms of the pawns moving up the board.
+    fn side_counts(our_pawns: BitBoard, their_pawns: BitBoard) -> [u8; PAWN_STRUCTURE_TERMS] {
+        let mut counts = [0; PAWN_STRUCTURE_TERMS];
+
+        let mut pawns = our_pawns;
+        consume_bit_board!(pawns, square {
+            let file = square.usize() % 8;
+            let doubled = FRONT_SPANS[square.usize()].overlaps(&our_pawns);
+            let isolated = !ADJACENT_FILES[file].overlaps(&our_pawns);
+
+            if doubled {
+                counts[DOUBLED_PAWN] += 1;
+            } else if !PASSED_PAWN_SPANS[square.usize()].overlaps(&their_pawns) {
+                // Only the frontmost of doubled pawns can be passed. Pawns are never on the first rank in a valid
+                // position, but count them as on the second instead of underflowing
+                counts[PASSED_PAWN + (square.usize() / 8).saturating_sub(1)] += 1;
+
+                let defended =
+                    PAWN_ATTACKS.black_pawn_attacks_at_square[square.usize()].overlaps(&our_pawns);
+                let pawn = square.bit_board();
+                let phalanx = ((pawn << 1) & BitBoard::NOT_A_FILE | (pawn >> 1) & BitBoard::NOT_H_FILE)
+                    .overlaps(&our_pawns);
+                if defended || phalanx {
+                    counts[CONNECTED_PASSED_PAWN] += 1;
+                }
+            }
+
+            if isolated {
+                counts[ISOLATED_PAWN] += 1;
+            } else if !SUPPORT_SPANS[square.usize()].overlaps(&our_pawns) {
+                let stop_square = square.up(1);
+                if PAWN_ATTACKS.white_pawn_attacks_at_square[stop_square.usize()]
+                    .overlaps(&their_pawns)
+                {
+                    counts[BACKWARD_PAWN] += 1;
+                }
+            }
+        });
+
+        counts
+    }
+
+    /// Scores white's pawn structure against black's.
+    #[must_use]
+    pub fn score(&self, pawn_structure_table: &PawnStructureTable) -> (Score, Score) {
+        super::score_terms(&self.counts, pawn_structure_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, piece::Piece, square::Square},
+        evaluation::tests::{assert_mirror_symmetric, swap_sides},
+    };
+
+    use super::{
+        BACKWARD_PAWN, CONNECTED_PASSED_PAWN, DOUBLED_PAWN, ISOLATED_PAWN, PASSED_PAWN,
+        PawnStructure,
+    };
+
+    #[test]
+    fn finds_terms() {
+        // White: a2 is isolated, g6 and h6 are connected passed pawns, c3 and d4 are neither
+        // Black: every pawn is isolated, c6 is doubled behind c5 and none are passed
+        let board = Board::from_fen("4k3/p7/2p3PP/2p1p3/3P4/2P5/P7/4K3 w - - 0 1").unwrap();
+        let pawn_structure = PawnStructure::new(&board);
+
+        let white = pawn_structure.counts[0];
+        assert_eq!(white[PASSED_PAWN + 4], 2);
+        assert_eq!(
+            white[PASSED_PAWN..CONNECTED_PASSED_PAWN].iter().sum::<u8>(),
+            2
+        );
+        assert_eq!(white[CONNECTED_PASSED_PAWN], 2);
+        assert_eq!(white[ISOLATED_PAWN], 1);
+        assert_eq!(white[DOUBLED_PAWN], 0);
+        assert_eq!(white[BACKWARD_PAWN], 0);
+
+        let black = pawn_structure.counts[1];
+        assert_eq!(
+            black[PASSED_PAWN..CONNECTED_PASSED_PAWN].iter().sum::<u8>(),
+            0
+        );
+        assert_eq!(black[ISOLATED_PAWN], 4);
+        assert_eq!(black[DOUBLED_PAWN], 1);
+    }
+
+    #[test]
+    fn backward_pawn() {
+        // d3 is behind e4 and c5 guards d4
+        let board = Board::from_fen("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PawnStructure::new(&board).counts[0][BACKWARD_PAWN], 1);
+
+        // c3 can support d3
+        let board = Board::from_fen("4k3/8/8/2p5/4P3/2PP4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PawnStructure::new(&board).counts[0][BACKWARD_PAWN], 0);
+
+        // Nothing guards d4
+        let board = Board::from_fen("4k3/8/8/8/4P3/3P4/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(PawnStructure::new(&board).counts[0][BACKWARD_PAWN], 0);
+    }
+
+    #[test]
+    fn first_rank_pawn_does_not_underflow() {
+        let mut board = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        board.add_piece(Piece::WhitePawn, Square::from_notation("a1").unwrap());
+        assert_eq!(PawnStructure::new(&board).counts[0][PASSED_PAWN], 1);
+    }
+
+    #[test]
+    fn mirrored_counts_are_swapped() {
+        assert_mirror_symmetric(|board| PawnStructure::new(board).counts, swap_sides);
+    }
+}
@@ -0,0 +1,386 @@
+//! Pieces that are attacked by less valuable pieces or are left undefended.
+
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece},
+    consume_bit_board,
+    move_generator::KING_MOVES_AT_SQUARE,
+};
+
+use super::{
+    eval_data::{Score, ThreatTable},
+    mobility::{PieceAttacks, black_pawn_attacks, piece_attacks, white_pawn_attacks},
+};
+
+/// Index of an enemy knight attacked by a pawn, then a bishop, rook and queen.
+pub const THREAT_BY_PAWN: usize = 0;
+
+/// Index of an enemy rook attacked by a knight or bishop, then a queen.
+pub const THREAT_BY_MINOR: usize = 4;
+
+/// Index of an enemy queen attacked by a rook.
+pub const THREAT_BY_ROOK: usize = 6;
+
+/// Index of an enemy piece other than the king that is attacked and not defended.
+pub const HANGING_PIECE: usize = 7;
+
+/// Index of an enemy piece other than a pawn that a safe pawn push would attack.
+pub const PAWN_PUSH_THREAT: usize = 8;
+
+/// Index of the side to move threatening at least one enemy piece.
+pub const THREAT_ON_MOVE: usize = 9;
+
+/// Number of threat terms.
+pub const THREAT_TERMS: usize = 10;
+
+/// Name and first index of each group of terms.
+pub const THREAT_TERM_GROUPS: [(&str, usize); 6] = [
+    ("Threat by pawn", THREAT_BY_PAWN),
+    ("Threat by minor", THREAT_BY_MINOR),
+    ("Threat by rook", THREAT_BY_ROOK),
+    ("Hanging piece", HANGING_PIECE),
+    ("Pawn push threat", PAWN_PUSH_THREAT),
+    ("Threat on move", THREAT_ON_MOVE),
+];
+
+/// Squares attacked by the pieces of one side.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Attacks {
+    /// Squares attacked by pawns.
+    pub pawns: BitBoard,
+    /// Squares attacked by knights and bishops.
+    pub minors: BitBoard,
+    /// Squares attacked by rooks.
+    pub rooks: BitBoard,
+    /// Squares attacked by any piece, including the king.
+    pub all: BitBoard,
+}
+
+impl Attacks {
+    /// Finds the squares attacked by white's pieces, or black's.
+    #[must_use]
+    pub fn new(board: &Board, white: bool) -> Self {
+        let [pawn, knight, bishop, rook, queen, king] = if white {
+            Piece::WHITE_PIECES
+        } else {
+            Piece::BLACK_PIECES
+        };
+        let occupied = board.occupied();
+        let attacks_of = |piece: Piece| {
+            let mut attacks = BitBoard::EMPTY;
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                attacks |= piece_attacks(piece, square, occupied);
+            });
+            attacks
+        };
+
+        let pawns = if white {
+            white_pawn_attacks(*board.get_bit_board(pawn))
+        } else {
+            black_pawn_attacks(*board.get_bit_board(pawn))
+        };
+        let minors = attacks_of(knight) | attacks_of(bishop);
+        let rooks = attacks_of(rook);
+        let king_attacks = KING_MOVES_AT_SQUARE[board.get_bit_board(king).first_square().usize()];
+
+        Self {
+            pawns,
+            minors,
+            rooks,
+            all: pawns | minors | rooks | attacks_of(queen) | king_attacks,
+        }
+    }
+
+    /// Finds the squares attacked by white's pieces, or black's, given the attacks of their knights, bishops,
+    /// rooks and queens.
+    #[must_use]
+    pub fn from_piece_attacks(board: &Board, white: bool, piece_attacks: &PieceAttacks) -> Self {
+        let [pawn, _, _, _, _, king] = if white {
+            Piece::WHITE_PIECES
+        } else {
+            Piece::BLACK_PIECES
+        };
+
+        let pawns = if white {
+            white_pawn_attacks(*board.get_bit_board(pawn))
+        } else {
+            black_pawn_attacks(*board.get_bit_board(pawn))
+        };
+        // Knight, bishop, rook, queen
+        let mut by_type = [BitBoard::EMPTY; 4];
+        for (piece_type, attacks) in piece_attacks.iter() {
+            by_type[piece_type] |= attacks;
+        }
+        let [knights, bishops, rooks, queens] = by_type;
+        let minors = knights | bishops;
+        let king_attacks = KING_MOVES_AT_SQUARE[board.get_bit_board(king).first_square().usize()];
+
+        Self {
+            pawns,
+            minors,
+            rooks,
+            all: pawns | minors | rooks | queens | king_attacks,
+        }
+    }
+}
+
+/// How many of each threat term white and black have.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Threats {
+    /// Counts of white's threats, then black's.
+    pub counts: [[u8; THREAT_TERMS]; 2],
+
+    /// White's pieces that are attacked by a less valuable piece or are hanging, then black's.
+    pub threatened: [BitBoard; 2],
+}
+
+impl Threats {
+    /// Finds the threats of both sides.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    /// use encrustant::evaluation::threats::{HANGING_PIECE, THREAT_BY_PAWN, Threats};
+    ///
+    /// // The pawn attacks the knight, which is not defended
+    /// let board = Board::from_fen("4k3/8/2n5/3P4/8/8/8/4K3 w - - 0 1").unwrap();
+    /// let threats = Threats::new(&board);
+    ///
+    /// assert_eq!(threats.counts[0][THREAT_BY_PAWN], 1);
+    /// assert_eq!(threats.counts[0][HANGING_PIECE], 1);
+    /// assert!(threats.threatened(false).get(&Square::from_notation("c6").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        Self::from_side_attacks(
+            board,
+            &Attacks::new(board, true),
+            &Attacks::new(board, false),
+        )
+    }
+
+    /// Finds the threats of both sides, given the attacks of white's pieces and black's.
+    #[must_use]
+    pub fn from_attacks(board: &Board, attacks: &[PieceAttacks; 2]) -> Self {
+        Self::from_side_attacks(
+            board,
+            &Attacks::from_piece_attacks(board, true, &attacks[0]),
+            &Attacks::from_piece_attacks(board, false, &attacks[1]),
+        )
+    }
+
+    fn from_side_attacks(board: &Board, white_attacks: &Attacks, black_attacks: &Attacks) -> Self {
+        let empty = !board.occupied();
+
+        // Single and double pushes that land on squares not attacked by an enemy pawn, and not
+        // attacked by any enemy piece unless they are defended
+        let white_pawns = *board.get_bit_board(Piece::WhitePawn);
+        let mut white_pushes = (white_pawns << 8) & empty;
+        white_pushes |= ((white_pushes & BitBoard::RANK_3) << 8) & empty;
+        white_pushes &= !black_attacks.pawns & (!black_attacks.all | white_attacks.all);
+
+        let black_pawns = *board.get_bit_board(Piece::BlackPawn);
+        let mut black_pushes = (black_pawns >> 8) & empty;
+        black_pushes |= ((black_pushes & BitBoard::RANK_6) >> 8) & empty;
+        black_pushes &= !white_attacks.pawns & (!white_attacks.all | black_attacks.all);
+
+        let (mut white_counts, black_threatened) = Self::side_counts(
+            board,
+            Piece::BLACK_PIECES,
+            white_attacks,
+            black_attacks,
+            white_pawn_attacks(white_pushes),
+        );
+        let (mut black_counts, white_threatened) = Self::side_counts(
+            board,
+            Piece::WHITE_PIECES,
+            black_attacks,
+            white_attacks,
+            black_pawn_attacks(black_pushes),
+        );
+
+        if board.white_to_move {
+            white_counts[THREAT_ON_MOVE] = u8::from(black_threatened.is_not_empty());
+        } else {
+            black_counts[THREAT_ON_MOVE] = u8::from(white_threatened.is_not_empty());
+        }
+
+        Self {
+            counts: [white_counts, black_counts],
+            threatened: [white_threatened, black_threatened],
+        }
+    }
+
+    /// Counts the threats against `their_pieces`, and returns them along with the threatened pieces.
+    #[allow(clippy::cast_possible_truncation)] // There are at most 16 pieces to threaten
+    fn side_counts(
+        board: &Board,
+        their_pieces: [Piece; 6],
+        our_attacks: &Attacks,
+        their_attacks: &Attacks,
+        push_attacks: BitBoard,
+    ) -> ([u8; THREAT_TERMS], BitBoard) {
+        let [_, knights, bishops, rooks, queens, _] =
+            their_pieces.map(|piece| *board.get_bit_board(piece));
+        let pieces = knights | bishops | rooks | queens;
+
+        let mut counts = [0; THREAT_TERMS];
+        for (piece_type, bit_board) in [knights, bishops, rooks, queens].into_iter().enumerate() {
+            counts[THREAT_BY_PAWN + piece_type] = (our_attacks.pawns & bit_board).count() as u8;
+        }
+        counts[THREAT_BY_MINOR] = (our_attacks.minors & rooks).count() as u8;
+        counts[THREAT_BY_MINOR + 1] = (our_attacks.minors & queens).count() as u8;
+        counts[THREAT_BY_ROOK] = (our_attacks.rooks & queens).count() as u8;
+
+        let (hanging, threatened) =
+            Self::find_threatened(board, their_pieces, our_attacks, their_attacks);
+        counts[HANGING_PIECE] = hanging.count() as u8;
+        counts[PAWN_PUSH_THREAT] = (push_attacks & pieces).count() as u8;
+
+        (counts, threatened)
+    }
+
+    /// Finds which of `their_pieces` are hanging, and which are attacked by a less valuable piece or are
+    /// hanging.
+    fn find_threatened(
+        board: &Board,
+        their_pieces: [Piece; 6],
+        our_attacks: &Attacks,
+        their_attacks: &Attacks,
+    ) -> (BitBoard, BitBoard) {
+        let [pawns, knights, bishops, rooks, queens, _] =
+            their_pieces.map(|piece| *board.get_bit_board(piece));
+        let pieces = knights | bishops | rooks | queens;
+
+        let hanging = (pawns | pieces) & our_attacks.all & !their_attacks.all;
+        let threatened = (our_attacks.pawns & pieces)
+            | (our_attacks.minors & (rooks | queens))
+            | (our_attacks.rooks & queens)
+            | hanging;
+
+        (hanging, threatened)
+    }
+
+    /// Finds the pieces of white, or black, that are attacked by a less valuable piece or are hanging,
+    /// without counting any threat terms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::threats::Threats;
+    ///
+    /// let board = Board::from_fen("4k3/7p/1p6/2rn4/8/1N2P3/8/4K2R w - - 0 1").unwrap();
+    /// assert_eq!(
+    ///     Threats::threatened_pieces(&board, false),
+    ///     Threats::new(&board).threatened(false)
+    /// );
+    /// ```
+    #[must_use]
+    pub fn threatened_pieces(board: &Board, white: bool) -> BitBoard {
+        let their_pieces = if white {
+            Piece::WHITE_PIECES
+        } else {
+            Piece::BLACK_PIECES
+        };
+        Self::find_threatened(
+            board,
+            their_pieces,
+            &Attacks::new(board, !white),
+            &Attacks::new(board, white),
+        )
+        .1
+    }
+
+    /// Pieces of white, or black, that are attacked by a less valuable piece or are hanging.
+    #[must_use]
+    pub const fn threatened(&self, white: bool) -> BitBoard {
+        self.threatened[if white { 0 } else { 1 }]
+    }
+
+    /// Scores white's threats against black's.
+    #[must_use]
+    pub fn score(&self, threat_table: &ThreatTable) -> (Score, Score) {
+        super::score_terms(&self.counts, threat_table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, square::Square},
+        evaluation::tests::{assert_mirror_symmetric, swap_sides},
+        tests::test_positions,
+    };
+
+    use super::{
+        HANGING_PIECE, PAWN_PUSH_THREAT, THREAT_BY_MINOR, THREAT_BY_PAWN, THREAT_BY_ROOK,
+        THREAT_ON_MOVE, Threats,
+    };
+
+    #[test]
+    fn finds_threats() {
+        // The knight attacks the rook on c5, which is defended, the h7 pawn is hanging and e3 to
+        // e4 would attack the knight on d5, which attacks the hanging e3 pawn
+        let board = Board::from_fen("4k3/7p/1p6/2rn4/8/1N2P3/8/4K2R w - - 0 1").unwrap();
+        let threats = Threats::new(&board);
+
+        let white = threats.counts[0];
+        assert_eq!(white[THREAT_BY_MINOR], 1);
+        assert_eq!(white[HANGING_PIECE], 1);
+        assert_eq!(white[PAWN_PUSH_THREAT], 1);
+        assert_eq!(white[THREAT_ON_MOVE], 1);
+        assert_eq!(
+            threats.threatened(false),
+            Square::from_notation("c5").unwrap().bit_board()
+                | Square::from_notation("h7").unwrap().bit_board()
+        );
+
+        let black = threats.counts[1];
+        assert_eq!(black[HANGING_PIECE], 1);
+        assert_eq!(black[THREAT_ON_MOVE], 0);
+        assert_eq!(black.iter().sum::<u8>(), 1);
+    }
+
+    #[test]
+    fn threats_by_pawns_and_rooks() {
+        // The d4 pawn attacks the knight on c5 and the bishop on e5, and the rook attacks the queen
+        let board = Board::from_fen("q3k3/8/8/2n1b3/3P4/8/8/R3K3 b - - 0 1").unwrap();
+        let white = Threats::new(&board).counts[0];
+        assert_eq!(white[THREAT_BY_PAWN], 1);
+        assert_eq!(white[THREAT_BY_PAWN + 1], 1);
+        assert_eq!(white[THREAT_BY_ROOK], 1);
+    }
+
+    #[test]
+    fn threatened_pieces_match_threats() {
+        for board in test_positions() {
+            let threats = Threats::new(&board);
+            for white in [true, false] {
+                assert_eq!(
+                    Threats::threatened_pieces(&board, white),
+                    threats.threatened(white),
+                    "{}",
+                    board.to_fen()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mirrored_counts_are_swapped() {
+        assert_mirror_symmetric(
+            |board| {
+                let threats = Threats::new(board);
+                (threats.counts, threats.threatened)
+            },
+            |(counts, threatened)| {
+                (
+                    swap_sides(counts),
+                    swap_sides(threatened.map(|threatened| threatened.flip_vertical())),
+                )
+            },
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Breaks the evaluation down into its terms.
+
+use core::fmt;
+
+use crate::{
+    board::{Board, piece::Piece},
+    consume_bit_board,
+};
+
+use super::{
+    Eval,
+    endgame::{self, Endgame, NORMAL_SCALE_FACTOR},
+    eval_data::{self, Score},
+    king_safety::{KING_SAFETY_TERM_GROUPS, KingSafety},
+    material::{MATERIAL_TERM_GROUPS, Material},
+    mobility::{MOBILITY_TERM_GROUPS, Mobility},
+    pawn_structure::{PAWN_STRUCTURE_TERM_NAMES, PawnStructure},
+    threats::{THREAT_TERM_GROUPS, Threats},
+};
+
+/// Middlegame and endgame scores that one term gives each side.
+struct Term {
+    name: &'static str,
+    white: (Score, Score),
+    black: (Score, Score),
+}
+
+/// Adds a row for each group of terms, given by its name and first index.
+fn push_groups<const TERMS: usize>(
+    terms: &mut Vec<Term>,
+    groups: &[(&'static str, usize)],
+    counts: &[[u8; TERMS]; 2],
+    table: &[(i16, i16); TERMS],
+) {
+    for (group, &(name, first_term)) in groups.iter().enumerate() {
+        let last_term = groups
+            .get(group + 1)
+            .map_or(TERMS, |next_group| next_group.1);
+        let side_score = |side: usize| {
+            (first_term..last_term).fold((0, 0), |score: (Score, Score), term| {
+                let count = Score::from(counts[side][term]);
+                (
+                    score.0 + count * Score::from(table[term].0),
+                    score.1 + count * Score::from(table[term].1),
+                )
+            })
+        };
+        terms.push(Term {
+            name,
+            white: side_score(0),
+            black: side_score(1),
+        });
+    }
+}
+
+/// Every term of the hand-crafted evaluation of a position.
+pub struct Trace {
+    terms: Vec<Term>,
+    phase: Score,
+    total_phase: Score,
+    white_to_move: bool,
+    /// Endgame with a dedicated evaluation, and its score from white's perspective.
+    endgame: Option<(Endgame, Score)>,
+    /// Scale factors when white is ahead, then when black is.
+    scale_factors: [Score; 2],
+}
+
+impl Trace {
+    /// Evaluates `board` term by term, using the same weights as `Eval::evaluate`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::evaluation::{Eval, trace::Trace};
+    ///
+    /// let board = Board::from_fen("4k3/8/2p5/8/4P3/8/8/4K3 b - - 0 1").unwrap();
+    /// assert_eq!(Trace::new(&board).evaluation(), Eval::evaluate(&board));
+    /// ```
+    #[must_use]
+    pub fn new(board: &Board) -> Self {
+        let mut piece_square_tables = ((0, 0), (0, 0));
+        for piece in Piece::WHITE_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                let (middle_game_value, end_game_value) = Eval::get_piece_value(
+                    &eval_data::PIECE_SQUARE_TABLE,
+                    piece as usize,
+                    square.flip().usize(),
+                );
+                piece_square_tables.0.0 += Score::from(middle_game_value);
+                piece_square_tables.0.1 += Score::from(end_game_value);
+            });
+        }
+        for piece in Piece::BLACK_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                let (middle_game_value, end_game_value) = Eval::get_piece_value(
+                    &eval_data::PIECE_SQUARE_TABLE,
+                    piece as usize - 6,
+                    square.usize(),
+                );
+                piece_square_tables.1.0 += Score::from(middle_game_value);
+                piece_square_tables.1.1 += Score::from(end_game_value);
+            });
+        }
+
+        let mut terms = vec![Term {
+            name: "Piece-square tables",
+            white: piece_square_tables.0,
+            black: piece_square_tables.1,
+        }];
+
+        push_groups(
+            &mut terms,
+            &MATERIAL_TERM_GROUPS,
+            &Material::new(board).counts,
+            &eval_data::MATERIAL,
+        );
+
+        let pawn_structure_groups: Vec<(&str, usize)> = PAWN_STRUCTURE_TERM_NAMES
+            .into_iter()
+            .enumerate()
+            .map(|(term, name)| (name, term))
+            .collect();
+        push_groups(
+            &mut terms,
+            &pawn_structure_groups,
+            &PawnStructure::new(board).counts,
+            &eval_data::PAWN_STRUCTURE,
+        );
+        push_groups(
+            &mut terms,
+            &MOBILITY_TERM_GROUPS,
+            &Mobility::new(board).counts,
+            &eval_data::MOBILITY,
+        );
+        push_groups(
+            &mut terms,
+            &KING_SAFETY_TERM_GROUPS,
+            &KingSafety::new(board).counts,
+            &eval_data::KING_SAFETY,
+        );
+        push_groups(
+            &mut terms,
+            &THREAT_TERM_GROUPS,
+            &Threats::new(board).counts,
+            &eval_data::THREATS,
+        );
+
+        let endgame = Endgame::find(board).map(|(endgame, strong_white)| {
+            let score = endgame.evaluate(board, strong_white);
+            (endgame, if strong_white { score } else { -score })
+        });
+
+        let phases = eval_data::PHASE_WEIGHTS;
+        Self {
+            terms,
+            phase: Eval::get_phase(board, &phases),
+            total_phase: Eval::total_phase(&phases),
+            white_to_move: board.white_to_move,
+            endgame,
+            scale_factors: [
+                endgame::scale_factor(board, true),
+                endgame::scale_factor(board, false),
+            ],
+        }
+    }
+
+    /// Middlegame and endgame score from white's perspective.
+    fn total(&self) -> (Score, Score) {
+        self.terms.iter().fold((0, 0), |total, term| {
+            (
+                total.0 + term.white.0 - term.black.0,
+                total.1 + term.white.1 - term.black.1,
+            )
+        })
+    }
+
+    /// Tapered score from white's perspective, before it is scaled.
+    fn tapered_evaluation(&self) -> Score {
+        let (middle_game_score, end_game_score) = self.total();
+        Eval::calculate_score(
+            self.phase,
+            self.total_phase,
+            middle_game_score,
+            end_game_score,
+        )
+    }
+
+    /// Scale factor of the side that is ahead by `white_score`.
+    const fn scale_factor(&self, white_score: Score) -> Score {
+        self.scale_factors[if white_score > 0 { 0 } else { 1 }]
+    }
+
+    /// Score from white's perspective, which is the endgame score if there is one, otherwise the
+    /// scaled tapered score.
+    fn white_evaluation(&self) -> Score {
+        if let Some((_, score)) = self.endgame {
+            return score;
+        }
+
+        let score = self.tapered_evaluation();
+        score * self.scale_factor(score) / NORMAL_SCALE_FACTOR
+    }
+
+    /// Score for the side playing, which is the same as `Eval::evaluate`.
+    #[must_use]
+    pub fn evaluation(&self) -> Score {
+        self.white_evaluation() * if self.white_to_move { 1 } else { -1 }
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24}|{:^17}|{:^17}|{:^17}",
+            "Term", "White", "Black", "Total"
+        )?;
+        writeln!(
+            f,
+            "{:<24}|{:>8} {:>8}|{:>8} {:>8}|{:>8} {:>8}",
+            "", "MG", "EG", "MG", "EG", "MG", "EG"
+        )?;
+        writeln!(f, "{:-<24}+{:-<17}+{:-<17}+{:-<17}", "", "", "", "")?;
+        for term in &self.terms {
+            writeln!(
+                f,
+                "{:<24}|{:>8} {:>8}|{:>8} {:>8}|{:>8} {:>8}",
+                term.name,
+                term.white.0,
+                term.white.1,
+                term.black.0,
+                term.black.1,
+                term.white.0 - term.black.0,
+                term.white.1 - term.black.1,
+            )?;
+        }
+        writeln!(f, "{:-<24}+{:-<17}+{:-<17}+{:-<17}", "", "", "", "")?;
+
+        let (middle_game_score, end_game_score) = self.total();
+        writeln!(
+            f,
+            "{:<24}|{:>17}|{:>17}|{:>8} {:>8}",
+            "Total", "", "", middle_game_score, end_game_score
+        )?;
+        writeln!(f, "Phase: {} / {}", self.phase, self.total_phase)?;
+        if let Some((endgame, _)) = self.endgame {
+            writeln!(f, "Endgame: {endgame:?}")?;
+        } else {
+            writeln!(
+                f,
+                "Scale factor: {} / {NORMAL_SCALE_FACTOR}",
+                self.scale_factor(self.tapered_evaluation())
+            )?;
+        }
+        write!(
+            f,
+            "Evaluation: {} (white's perspective)",
+            self.white_evaluation()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, evaluation::Eval, tests::TEST_FENS};
+
+    use super::Trace;
+
+    #[test]
+    fn trace_matches_evaluation() {
+        for (_, _, fen) in TEST_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                Trace::new(&board).evaluation(),
+                Eval::evaluate(&board),
+                "{board}"
+            );
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Plays a game of chess, keeping track of the moves played.
+
+use crate::{
+    board::{Board, fen::FenError, game_state::GameState},
+    move_generator::{
+        MoveGenerator,
+        move_data::{Flag, Move},
+    },
+    search::zobrist::Zobrist,
+};
+
+/// How a game ended.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Outcome {
+    /// The side to move is checkmated.
+    Checkmate {
+        /// Whether white delivered checkmate.
+        white_wins: bool,
+    },
+
+    /// The side to move has no legal moves but is not in check.
+    Stalemate,
+
+    /// Neither side can checkmate.
+    InsufficientMaterial,
+
+    /// The same position occurred five times. The game is drawn automatically.
+    FivefoldRepetition,
+
+    /// 75 moves were played by each side without a capture or pawn move. The game is drawn automatically.
+    SeventyFiveMoveRule,
+
+    /// The same position occurred three times. Either player can claim a draw.
+    ThreefoldRepetition,
+
+    /// 50 moves were played by each side without a capture or pawn move. Either player can claim a draw.
+    FiftyMoveRule,
+}
+
+impl Outcome {
+    /// Returns `Some(true)` if white won, `Some(false)` if black won, or `None` if it is a draw.
+    #[must_use]
+    pub const fn winner(self) -> Option<bool> {
+        match self {
+            Self::Checkmate { white_wins } => Some(white_wins),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the game ends without either player claiming it.
+    #[must_use]
+    pub const fn is_automatic(self) -> bool {
+        !matches!(self, Self::ThreefoldRepetition | Self::FiftyMoveRule)
+    }
+}
+
+/// A chess position along with the moves that led to it.
+pub struct Game {
+    board: Board,
+
+    /// Moves played, and the state needed to undo them.
+    history: Vec<(Move, GameState)>,
+
+    /// Repetition key of every position reached, including the current one.
+    position_history: Vec<Zobrist>,
+}
+
+impl Game {
+    /// Starts a game from a position.
+    #[must_use]
+    pub fn from_board(board: Board) -> Self {
+        let position_history = vec![Self::repetition_key(&board)];
+        Self {
+            board,
+            history: Vec::new(),
+            position_history,
+        }
+    }
+
+    /// Starts a game from Forsyth-Edwards Notation.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `fen` is not a valid position.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Ok(Self::from_board(Board::from_fen(fen)?))
+    }
+
+    /// Starts a game from the standard starting position.
+    ///
+    /// # Panics
+    ///
+    /// Should not panic.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_fen(Board::START_POSITION_FEN).unwrap()
+    }
+
+    /// Returns the current position.
+    #[must_use]
+    pub const fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns the moves played, oldest first.
+    pub fn moves(&self) -> impl Iterator<Item = &Move> {
+        self.history.iter().map(|(move_data, _)| move_data)
+    }
+
+    /// Plays a move. The move is assumed to be legal.
+    pub fn make_move(&mut self, move_data: &Move) {
+        let old_state = self.board.make_move(move_data);
+        self.history.push((*move_data, old_state));
+        self.position_history
+            .push(Self::repetition_key(&self.board));
+    }
+
+    /// Takes back the last move played, returning it.
+    /// Returns `None` if no moves have been played.
+    pub fn undo(&mut self) -> Option<Move> {
+        let (move_data, old_state) = self.history.pop()?;
+        self.board.unmake_move(&move_data, &old_state);
+        self.position_history.pop();
+        Some(move_data)
+    }
+
+    /// Zobrist key used to find repeated positions.
+    ///
+    /// An en passant square only makes a position different if en passant can be played.
+    fn repetition_key(board: &Board) -> Zobrist {
+        let mut key = board.zobrist();
+        if let Some(en_passant_square) = &board.game_state.en_passant_square {
+            let mut can_en_passant = false;
+            MoveGenerator::new(board).generate_captures(|move_data| {
+                can_en_passant |= move_data.flag == Flag::EnPassant;
+            });
+            if !can_en_passant {
+                key.xor_en_passant(en_passant_square);
+            }
+        }
+        key
+    }
+
+    /// Returns how many times the current position has occurred, including now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{game::Game, uci::decode_move, board::square::Square, move_generator::move_data::Flag};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.repetition_count(), 1);
+    /// for _ in 0..2 {
+    ///     for (from, to) in [("g1", "f3"), ("g8", "f6"), ("f3", "g1"), ("f6", "g8")] {
+    ///         let (from, to) = (Square::from_notation(from).unwrap(), Square::from_notation(to).unwrap());
+    ///         game.make_move(&decode_move(game.board(), from, to, Flag::None));
+    ///     }
+    /// }
+    /// assert_eq!(game.repetition_count(), 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Should not panic.
+    #[must_use]
+    pub fn repetition_count(&self) -> usize {
+        let current = *self.position_history.last().unwrap();
+
+        // Positions before the last capture or pawn move can not repeat
+        let reversible = self.board.game_state.half_move_clock as usize + 1;
+        self.position_history
+            .iter()
+            .rev()
+            .take(reversible)
+            .step_by(2)
+            .filter(|&&key| key == current)
+            .count()
+    }
+
+    /// Returns whether the side to move has at least one legal move.
+    #[must_use]
+    pub fn has_legal_moves(&self) -> bool {
+        let mut has_legal_moves = false;
+        MoveGenerator::new(&self.board).generate(|_| has_legal_moves = true, false);
+        has_legal_moves
+    }
+
+    /// Returns how the game ended, or `None` if it has not.
+    ///
+    /// Automatic endings are reported before claimable draws.
+    #[must_use]
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.has_legal_moves() {
+            return Some(if MoveGenerator::calculate_is_in_check(&self.board) {
+                Outcome::Checkmate {
+                    white_wins: !self.board.white_to_move,
+                }
+            } else {
+                Outcome::Stalemate
+            });
+        }
+
+        if self.board.is_insufficient_material() {
+            return Some(Outcome::InsufficientMaterial);
+        }
+
+        let repetition_count = self.repetition_count();
+        let half_move_clock = self.board.game_state.half_move_clock;
+        if repetition_count >= 5 {
+            Some(Outcome::FivefoldRepetition)
+        } else if half_move_clock >= 150 {
+            Some(Outcome::SeventyFiveMoveRule)
+        } else if repetition_count >= 3 {
+            Some(Outcome::ThreefoldRepetition)
+        } else if half_move_clock >= 100 {
+            Some(Outcome::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::square::Square,
+        game::{Game, Outcome},
+        move_generator::move_data::Flag,
+        uci::decode_move,
+    };
+
+    fn play(game: &mut Game, moves: &[&str]) {
+        for uci_move in moves {
+            let from = Square::from_notation(&uci_move[0..2]).unwrap();
+            let to = Square::from_notation(&uci_move[2..4]).unwrap();
+            let move_data = decode_move(game.board(), from, to, Flag::None);
+            game.make_move(&move_data);
+        }
+    }
+
+    #[test]
+    fn checkmate() {
+        let mut game = Game::new();
+        play(&mut game, &["f2f3", "e7e5", "g2g4"]);
+        assert_eq!(game.outcome(), None);
+        play(&mut game, &["d8h4"]);
+        assert_eq!(
+            game.outcome(),
+            Some(Outcome::Checkmate { white_wins: false })
+        );
+    }
+
+    #[test]
+    fn stalemate() {
+        let game = Game::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::Stalemate));
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let game = Game::from_fen("8/8/8/6k1/1K6/8/8/8 w - - 0 1").unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::InsufficientMaterial));
+    }
+
+    #[test]
+    fn repetition() {
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+
+        let mut game = Game::new();
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), None);
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), Some(Outcome::ThreefoldRepetition));
+        play(&mut game, &shuffle);
+        play(&mut game, &shuffle);
+        assert_eq!(game.outcome(), Some(Outcome::FivefoldRepetition));
+
+        // The previous position only occurred four times
+        game.undo();
+        assert_eq!(game.outcome(), Some(Outcome::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn repetition_ignores_unplayable_en_passant() {
+        let shuffle = ["g8f6", "g1f3", "f6g8", "f3g1"];
+
+        // No black pawn can capture on e3, so the first position repeats
+        let mut game =
+            Game::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        play(&mut game, &shuffle);
+        play(&mut game, &shuffle);
+        assert_eq!(game.repetition_count(), 3);
+
+        // dxe3 is only possible in the first position
+        let mut game =
+            Game::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1").unwrap();
+        play(&mut game, &shuffle);
+        play(&mut game, &shuffle);
+        assert_eq!(game.repetition_count(), 2);
+    }
+
+    #[test]
+    fn move_rules() {
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 100 80").unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::FiftyMoveRule));
+        let game = Game::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 100").unwrap();
+        assert_eq!(game.outcome(), Some(Outcome::SeventyFiveMoveRule));
+    }
+
+    #[test]
+    fn undo_restores_position() {
+        let mut game =
+            Game::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+        play(&mut game, &["e1g1", "a6e2", "c3e2", "b4b3"]);
+        assert_eq!(game.moves().count(), 4);
+        while game.undo().is_some() {}
+        assert_eq!(
+            game.board().to_fen(),
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        );
+        assert_eq!(game.repetition_count(), 1);
+    }
+}
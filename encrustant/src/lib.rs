@@ -12,6 +12,7 @@
 
 pub mod board;
 pub mod evaluation;
+pub mod game;
 pub mod move_generator;
 pub mod perft;
 pub mod search;
@@ -784,4 +785,12 @@ mod tests {
         (6, 11030083, "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
         (5, 11139762, "rnbqkb1r/ppppp1pp/7n/4Pp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3"),
     ];
+
+    /// Each position in `TEST_FENS` once, since most are listed for several depths.
+    pub fn test_positions() -> impl Iterator<Item = crate::board::Board> {
+        let mut fens: Vec<&str> = TEST_FENS.iter().map(|(_, _, fen)| *fen).collect();
+        fens.dedup();
+        fens.into_iter()
+            .map(|fen| crate::board::Board::from_fen(fen).unwrap())
+    }
 }
@@ -12,6 +12,7 @@ use std::{
 use core::cell::RefCell;
 use encrustant::{
     board::Board,
+    perft::{PerftSuiteEntry, parallel_perft_root},
     search::{Search, time_manager::TimeManager, transposition::megabytes_to_capacity},
     timer::Time,
     uci::{GoParameters, SpinU16, UCIProcessor},
@@ -615,6 +616,65 @@ fn bench() {
     ));
 }
 
+/// Runs every position of an EPD perft suite up to `max_depth`, returning whether they all passed.
+#[cfg(not(target_arch = "wasm32"))]
+fn perft_suite(path: &str, max_depth: u16) -> bool {
+    let contents = std::fs::read_to_string(path).expect("Could not read perft suite");
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let suite_start = Time::now();
+    for (line_index, line) in contents.lines().enumerate() {
+        let line_number = line_index + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let entry = match PerftSuiteEntry::parse(line) {
+            Ok(entry) => entry,
+            Err(error) => {
+                out(&format!("FAIL line {line_number}: {error}"));
+                failed += 1;
+                continue;
+            }
+        };
+
+        let board = Board::from_fen(&entry.fen).unwrap();
+        let mut mismatches = Vec::new();
+        let position_start = Time::now();
+        for &(depth, expected_nodes) in &entry.expected {
+            if depth > max_depth {
+                continue;
+            }
+            let nodes = parallel_perft_root(&board, depth, threads, 0, |_| {});
+            if nodes != expected_nodes {
+                mismatches.push(format!("D{depth} expected {expected_nodes} got {nodes}"));
+            }
+        }
+        let time = position_start.milliseconds();
+
+        if mismatches.is_empty() {
+            out(&format!(
+                "PASS line {line_number} {} ({time} ms)",
+                entry.fen
+            ));
+            passed += 1;
+        } else {
+            out(&format!(
+                "FAIL line {line_number} {} ({time} ms): {}",
+                entry.fen,
+                mismatches.join(", ")
+            ));
+            failed += 1;
+        }
+    }
+    out(&format!(
+        "{passed} passed, {failed} failed in {} milliseconds",
+        suite_start.milliseconds()
+    ));
+    failed == 0
+}
+
 fn process_input(input: &str) -> bool {
     let mut quit = false;
     let mut args = input.split_whitespace();
@@ -641,6 +701,7 @@ fn process_input(input: &str) -> bool {
         "bench" => {
             bench();
         }
+        "eval" => uci_processor.borrow().eval(),
 
         _ => panic!("Unrecognised command"),
     });
@@ -657,6 +718,18 @@ fn main() {
             bench();
             return;
         }
+        if target.is_some_and(|arg| arg == "perft-suite") {
+            let path = args
+                .get(2)
+                .expect("Usage: perft-suite <file.epd> [max depth]");
+            let max_depth = args.get(3).map_or(u16::MAX, |depth| {
+                depth.parse().expect("Max depth is not a number")
+            });
+            if !perft_suite(path, max_depth) {
+                std::process::exit(1);
+            }
+            return;
+        }
     }
 
     loop {
@@ -0,0 +1,365 @@
+use crate::{
+    board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+    consume_bit_board,
+};
+
+use super::{
+    move_data::{Flag, Move},
+    pawn_move_generator,
+    precomputed::{KING_MOVES_AT_SQUARE, KNIGHT_MOVES_AT_SQUARE, get_between_rays},
+    slider_lookup::{
+        get_bishop_moves, get_rook_moves, relevant_bishop_blockers, relevant_rook_blockers,
+    },
+};
+
+impl Board {
+    fn diagonal_sliders(&self, white: bool) -> BitBoard {
+        if white {
+            *self.get_bit_board(Piece::WhiteBishop) | *self.get_bit_board(Piece::WhiteQueen)
+        } else {
+            *self.get_bit_board(Piece::BlackBishop) | *self.get_bit_board(Piece::BlackQueen)
+        }
+    }
+
+    fn orthogonal_sliders(&self, white: bool) -> BitBoard {
+        if white {
+            *self.get_bit_board(Piece::WhiteRook) | *self.get_bit_board(Piece::WhiteQueen)
+        } else {
+            *self.get_bit_board(Piece::BlackRook) | *self.get_bit_board(Piece::BlackQueen)
+        }
+    }
+
+    const fn king_square(&self, white: bool) -> Square {
+        self.get_bit_board(if white {
+            Piece::WhiteKing
+        } else {
+            Piece::BlackKing
+        })
+        .first_square()
+    }
+
+    /// Returns the pieces of either colour that attack a square, if only the squares in `occupancy` were occupied.
+    ///
+    /// Removing pieces from `occupancy` reveals the sliders behind them, which is useful for static exchange evaluation.
+    /// Pieces not in `occupancy` are not returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1").unwrap();
+    /// let d5 = Square::from_notation("d5").unwrap();
+    /// let d2 = Square::from_notation("d2").unwrap();
+    /// assert_eq!(board.attackers_to(d5, board.occupied()).count(), 1);
+    ///
+    /// // Without the rook on d2, the rook on d1 attacks d5
+    /// let mut occupancy = board.occupied();
+    /// occupancy.unset(&d2);
+    /// assert_eq!(board.attackers_to(d5, occupancy).count(), 1);
+    /// assert!(board.attackers_to(d5, occupancy).get(&Square::from_notation("d1").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn attackers_to(&self, square: Square, occupancy: BitBoard) -> BitBoard {
+        let diagonal = get_bishop_moves(square, occupancy & relevant_bishop_blockers(square))
+            & (self.diagonal_sliders(true) | self.diagonal_sliders(false));
+        let orthogonal = get_rook_moves(square, occupancy & relevant_rook_blockers(square))
+            & (self.orthogonal_sliders(true) | self.orthogonal_sliders(false));
+
+        // A white pawn attacks the square if a black pawn on the square would attack it
+        let pawns = (pawn_move_generator::attack_bit_board(square, false)
+            & *self.get_bit_board(Piece::WhitePawn))
+            | (pawn_move_generator::attack_bit_board(square, true)
+                & *self.get_bit_board(Piece::BlackPawn));
+
+        let knights = KNIGHT_MOVES_AT_SQUARE[square.usize()]
+            & (*self.get_bit_board(Piece::WhiteKnight) | *self.get_bit_board(Piece::BlackKnight));
+        let kings = KING_MOVES_AT_SQUARE[square.usize()]
+            & (*self.get_bit_board(Piece::WhiteKing) | *self.get_bit_board(Piece::BlackKing));
+
+        (diagonal | orthogonal | pawns | knights | kings) & occupancy
+    }
+
+    /// Returns whether a square is attacked by a side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// assert!(board.is_square_attacked(Square::from_notation("f3").unwrap(), true));
+    /// assert!(!board.is_square_attacked(Square::from_notation("e4").unwrap(), true));
+    /// assert!(board.is_square_attacked(Square::from_notation("e6").unwrap(), false));
+    /// ```
+    #[must_use]
+    pub fn is_square_attacked(&self, square: Square, by_white: bool) -> bool {
+        let attackers = if by_white {
+            self.white_occupancy()
+        } else {
+            self.black_occupancy()
+        };
+        self.attackers_to(square, self.occupied())
+            .overlaps(&attackers)
+    }
+
+    /// Returns the enemy pieces giving check to the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    ///
+    /// // Double check from the bishop and the rook
+    /// let board = Board::from_fen("4k3/8/8/8/1b6/8/4r3/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.checkers().count(), 2);
+    /// ```
+    #[must_use]
+    pub fn checkers(&self) -> BitBoard {
+        self.attackers_to(self.king_square(self.white_to_move), self.occupied())
+            & self.enemy_occupancy()
+    }
+
+    /// Returns the pieces of either colour that are the only piece between a king and a slider of the other colour given.
+    fn king_blockers(&self, king_white: bool, slider_white: bool) -> BitBoard {
+        let king_square = self.king_square(king_white);
+        let occupied = self.occupied();
+
+        let mut snipers = (get_bishop_moves(king_square, BitBoard::EMPTY)
+            & self.diagonal_sliders(slider_white))
+            | (get_rook_moves(king_square, BitBoard::EMPTY)
+                & self.orthogonal_sliders(slider_white));
+
+        let mut blockers = BitBoard::EMPTY;
+        consume_bit_board!(snipers, sniper {
+            let between = get_between_rays(king_square, sniper) & occupied & !sniper.bit_board();
+            if between.is_not_empty() && !between.more_than_one_bit_set() {
+                blockers |= between;
+            }
+        });
+        blockers
+    }
+
+    /// Returns whether the side to move wins at least `threshold` centipawns of material if both sides keep
+    /// recapturing on the square the move goes to with their least valuable piece, and can stop at any point.
+    ///
+    /// Promotions and en passant are assumed to be good. Pins are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there is no piece on the square the move is from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{
+    ///     board::{Board, square::Square},
+    ///     move_generator::move_data::{Flag, Move},
+    /// };
+    ///
+    /// // The pawn on d5 is defended by the pawn on e6
+    /// let board = Board::from_fen("4k3/8/4p3/3p4/8/8/3Q4/3RK3 w - - 0 1").unwrap();
+    /// let queen_takes_pawn = Move {
+    ///     from: Square::from_notation("d2").unwrap(),
+    ///     to: Square::from_notation("d5").unwrap(),
+    ///     flag: Flag::None,
+    /// };
+    /// assert!(!board.static_exchange_at_least(queen_takes_pawn, 0));
+    /// assert!(board.static_exchange_at_least(queen_takes_pawn, -800));
+    /// ```
+    #[must_use]
+    pub fn static_exchange_at_least(&self, move_data: Move, threshold: i32) -> bool {
+        const PIECE_VALUES: [i32; 6] = [100, 300, 300, 500, 900, 0];
+
+        if move_data.flag != Flag::None {
+            return true;
+        }
+        let Move { from, to, .. } = move_data;
+
+        // Material gained if the exchange stops here, minus the threshold, from the point of view of the side that
+        // just captured
+        let mut swap = self
+            .piece_at(to)
+            .map_or(0, |captured| PIECE_VALUES[captured as usize % 6])
+            - threshold;
+        if swap < 0 {
+            return false;
+        }
+
+        // Material gained if the piece is recaptured and nothing else happens, from the point of view of the
+        // opponent
+        swap = PIECE_VALUES[self.piece_at(from).unwrap() as usize % 6] - swap;
+        if swap <= 0 {
+            return true;
+        }
+
+        let mut occupied = self.occupied() ^ from.bit_board() ^ to.bit_board();
+        let mut white = self.white_to_move;
+        let mut wins = true;
+        loop {
+            white = !white;
+
+            let attackers = self.attackers_to(to, occupied)
+                & if white {
+                    self.white_occupancy()
+                } else {
+                    self.black_occupancy()
+                };
+            if attackers.is_empty() {
+                break;
+            }
+            wins = !wins;
+
+            let pieces = if white {
+                Piece::WHITE_PIECES
+            } else {
+                Piece::BLACK_PIECES
+            };
+            let Some(attacker) = pieces
+                .into_iter()
+                .find(|&piece| self.get_bit_board(piece).overlaps(&attackers))
+            else {
+                break;
+            };
+
+            if matches!(attacker, Piece::WhiteKing | Piece::BlackKing) {
+                // The king can only recapture if the opponent has no attackers left
+                let opponent_attackers = self.attackers_to(to, occupied) & !attackers;
+                return if opponent_attackers.is_not_empty() {
+                    !wins
+                } else {
+                    wins
+                };
+            }
+
+            swap = PIECE_VALUES[attacker as usize % 6] - swap;
+            if swap < i32::from(wins) {
+                break;
+            }
+            occupied ^= (*self.get_bit_board(attacker) & attackers)
+                .first_square()
+                .bit_board();
+        }
+        wins
+    }
+
+    /// Returns the pieces of the side to move that are pinned to their king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    ///
+    /// let board = Board::from_fen("4k3/4r3/8/8/1b6/8/3PN3/4K3 w - - 0 1").unwrap();
+    /// let pinned = board.pinned_pieces();
+    /// assert_eq!(pinned.count(), 2);
+    /// assert!(pinned.get(&Square::from_notation("e2").unwrap()));
+    /// assert!(pinned.get(&Square::from_notation("d2").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn pinned_pieces(&self) -> BitBoard {
+        self.king_blockers(self.white_to_move, !self.white_to_move) & self.friendly_occupancy()
+    }
+
+    /// Returns the pieces of the side to move that would give check by moving out of the way of a friendly slider.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/4N3/4R1K1 w - - 0 1").unwrap();
+    /// let candidates = board.discovered_check_candidates();
+    /// assert_eq!(candidates.count(), 1);
+    /// assert!(candidates.get(&Square::from_notation("e2").unwrap()));
+    /// ```
+    #[must_use]
+    pub fn discovered_check_candidates(&self) -> BitBoard {
+        self.king_blockers(!self.white_to_move, self.white_to_move) & self.friendly_occupancy()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+        move_generator::{
+            MoveGenerator,
+            move_data::{Flag, Move},
+        },
+        tests::TEST_FENS,
+    };
+
+    #[test]
+    fn checkers_match_move_generator() {
+        for (_, _, fen) in TEST_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                board.checkers().is_not_empty(),
+                MoveGenerator::calculate_is_in_check(&board),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn static_exchange() {
+        let rook_takes_d5 = Move {
+            from: Square::from_notation("d2").unwrap(),
+            to: Square::from_notation("d5").unwrap(),
+            flag: Flag::None,
+        };
+        for (fen, threshold, expected) in [
+            // Undefended pawn
+            ("4k3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", 100, true),
+            ("4k3/8/8/3p4/8/8/3R4/3RK3 w - - 0 1", 101, false),
+            // Defended pawn, the rook on d1 recaptures
+            ("4k3/8/4p3/3p4/8/8/3R4/3RK3 w - - 0 1", 0, false),
+            ("4k3/8/4p3/3p4/8/8/3R4/3RK3 w - - 0 1", -300, true),
+            // The king can not recapture because of the rook on d1
+            ("8/8/8/3p4/4k3/8/3R4/3RK3 w - - 0 1", 100, true),
+            ("8/8/8/3p4/4k3/8/3R4/4K3 w - - 0 1", 0, false),
+        ] {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(
+                board.static_exchange_at_least(rook_takes_d5, threshold),
+                expected,
+                "{fen} {threshold}"
+            );
+        }
+    }
+
+    #[test]
+    fn pinned_pieces_stay_on_line() {
+        for (_, _, fen) in TEST_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            let pinned = board.pinned_pieces();
+            let king = board
+                .get_bit_board(if board.white_to_move {
+                    Piece::WhiteKing
+                } else {
+                    Piece::BlackKing
+                })
+                .first_square();
+
+            let mut moves: Vec<Move> = Vec::new();
+            MoveGenerator::new(&board).generate(|move_data| moves.push(move_data), false);
+            for move_data in moves {
+                if !pinned.get(&move_data.from) {
+                    continue;
+                }
+                // The pinned piece, the square it moves to and the king are still in a line
+                let on_line = |square: Square| {
+                    (square.rank() - king.rank()) * (move_data.from.file() - king.file())
+                        == (square.file() - king.file()) * (move_data.from.rank() - king.rank())
+                };
+                assert!(on_line(move_data.to), "{fen} {move_data}");
+            }
+            assert_eq!(
+                pinned & !board.friendly_occupancy(),
+                BitBoard::EMPTY,
+                "{fen}"
+            );
+        }
+    }
+}
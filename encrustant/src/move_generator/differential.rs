@@ -0,0 +1,284 @@
+use core::fmt;
+
+use crate::{
+    board::{
+        Board,
+        builder::{BoardBuilder, PositionError},
+        game_state::CastlingRights,
+        piece::Piece,
+        square::Square,
+    },
+    uci,
+};
+
+use super::{move_data::Move, reference};
+
+/// A position where [`super::MoveGenerator`] and [`reference::legal_moves`] disagree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mismatch {
+    /// The position.
+    pub fen: String,
+
+    /// Moves that only the reference generator found.
+    pub missing: Vec<Move>,
+
+    /// Moves that only [`super::MoveGenerator`] found, or found more than once.
+    pub extra: Vec<Move>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |moves: &[Move]| {
+            moves
+                .iter()
+                .map(|move_data| uci::encode_move(*move_data))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        write!(
+            f,
+            "{}: missing [{}], extra [{}]",
+            self.fen,
+            list(&self.missing),
+            list(&self.extra)
+        )
+    }
+}
+
+impl std::error::Error for Mismatch {}
+
+/// Compares the legal moves of [`super::MoveGenerator`] with the reference generator.
+///
+/// # Errors
+///
+/// Will return `Err` with the moves they disagree on.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::{board::Board, move_generator::differential};
+///
+/// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// assert_eq!(differential::compare(&board), Ok(()));
+/// ```
+pub fn compare(board: &Board) -> Result<(), Mismatch> {
+    let mut generated = board.legal_moves().as_slice().to_vec();
+    let expected = reference::legal_moves(board);
+
+    let mut missing = Vec::new();
+    for move_data in expected {
+        if let Some(index) = generated.iter().position(|other| *other == move_data) {
+            generated.swap_remove(index);
+        } else {
+            missing.push(move_data);
+        }
+    }
+
+    if missing.is_empty() && generated.is_empty() {
+        Ok(())
+    } else {
+        Err(Mismatch {
+            fen: board.to_fen(),
+            missing,
+            extra: generated,
+        })
+    }
+}
+
+/// Compares the generators in every position reachable within `depth` moves.
+///
+/// # Errors
+///
+/// Will return `Err` with the first position they disagree on.
+pub fn compare_to_depth(board: &mut Board, depth: u16) -> Result<(), Mismatch> {
+    compare(board)?;
+    if depth == 0 {
+        return Ok(());
+    }
+    for move_data in board.legal_moves() {
+        let old_state = board.make_move(&move_data);
+        let result = compare_to_depth(board, depth - 1);
+        board.unmake_move(&move_data, &old_state);
+        result?;
+    }
+    Ok(())
+}
+
+/// A castling right as its FEN character, and the function that removes it.
+type CastlingRight = (char, fn(&mut CastlingRights));
+
+const UNSET_CASTLING_RIGHTS: [CastlingRight; 4] = [
+    ('K', CastlingRights::unset_white_king_side),
+    ('Q', CastlingRights::unset_white_queen_side),
+    ('k', CastlingRights::unset_black_king_side),
+    ('q', CastlingRights::unset_black_queen_side),
+];
+
+/// Removes the castling rights whose king or rook is not on its starting square.
+fn without_invalid_castling_rights(mut builder: BoardBuilder) -> BoardBuilder {
+    for (right, unset) in UNSET_CASTLING_RIGHTS {
+        if let Err(PositionError::InvalidCastlingRights(invalid)) = builder.build()
+            && invalid == right
+        {
+            let mut castling_rights = builder.build_unchecked().game_state.castling_rights;
+            unset(&mut castling_rights);
+            builder = builder.castling_rights(castling_rights);
+        }
+    }
+    builder
+}
+
+/// Builds a valid position from arbitrary bytes, for fuzzing and random testing.
+///
+/// The first two bytes place the kings, the third picks the side to move, castling rights and whether there is an
+/// en passant square, the fourth picks its file, and every following pair of bytes places a piece.
+/// Returns `None` if the result is not a valid position.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::move_generator::differential;
+///
+/// // White king on e1, black king on e8, white to move, then a white pawn on e2
+/// let board = differential::position_from_bytes(&[4, 60, 0, 0, 12, 0]).unwrap();
+/// assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1");
+/// ```
+#[must_use]
+pub fn position_from_bytes(bytes: &[u8]) -> Option<Board> {
+    let square = |byte: u8| Square::from_index((byte % 64).cast_signed());
+    let [white_king, black_king, flags, en_passant_file, pieces @ ..] = bytes else {
+        return None;
+    };
+
+    let mut builder = BoardBuilder::new()
+        .piece(square(*white_king), Piece::WhiteKing)
+        .piece(square(*black_king), Piece::BlackKing);
+    for pair in pieces.chunks_exact(2) {
+        let target = square(pair[0]);
+        if target != square(*white_king) && target != square(*black_king) {
+            // Kings are already placed, so only pick from the other ten pieces
+            let piece = [0, 1, 2, 3, 4, 6, 7, 8, 9, 10][usize::from(pair[1] % 10)];
+            builder = builder.piece(target, Piece::ALL_PIECES[piece]);
+        }
+    }
+
+    let white_to_move = flags & 1 == 0;
+    builder = builder.white_to_move(white_to_move);
+
+    // Only keep castling rights whose king and rook are in place, so most inputs stay valid
+    builder = without_invalid_castling_rights(builder.castling_rights(CastlingRights::new(
+        flags & 2 != 0,
+        flags & 4 != 0,
+        flags & 8 != 0,
+        flags & 16 != 0,
+    )));
+
+    if flags & 32 != 0 {
+        let en_passant_rank = if white_to_move { 5 } else { 2 };
+        let en_passant_square =
+            Square::from_coords(en_passant_rank, (en_passant_file % 8).cast_signed());
+        let with_en_passant = builder.en_passant_square(Some(en_passant_square));
+        if with_en_passant.build().is_ok() {
+            builder = with_en_passant;
+        }
+    }
+
+    builder.build().ok()
+}
+
+/// Shrinks a position the generators disagree on, by removing pieces, castling rights and the en passant square
+/// for as long as they still disagree.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::{board::Board, move_generator::differential};
+///
+/// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// // The generators agree, so nothing can be removed
+/// assert_eq!(differential::minimise(&board).to_fen(), Board::START_POSITION_FEN);
+/// ```
+#[must_use]
+pub fn minimise(board: &Board) -> Board {
+    let mut board = board.clone();
+    if compare(&board).is_ok() {
+        return board;
+    }
+
+    loop {
+        let builder = BoardBuilder::from_board(&board);
+        let mut candidates = vec![builder.en_passant_square(None)];
+        for (_, unset) in UNSET_CASTLING_RIGHTS {
+            let mut castling_rights = board.game_state.castling_rights;
+            unset(&mut castling_rights);
+            candidates.push(builder.castling_rights(castling_rights));
+        }
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            if board
+                .piece_at(square)
+                .is_some_and(|piece| !matches!(piece, Piece::WhiteKing | Piece::BlackKing))
+            {
+                candidates.push(without_invalid_castling_rights(builder.remove(square)));
+            }
+        }
+
+        let smaller = candidates.into_iter().find_map(|candidate| {
+            let candidate = candidate.build().ok()?;
+            (candidate.to_fen() != board.to_fen() && compare(&candidate).is_err())
+                .then_some(candidate)
+        });
+        match smaller {
+            Some(smaller) => board = smaller,
+            None => return board,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, tests::TEST_FENS};
+
+    use super::{Mismatch, compare, compare_to_depth, minimise, position_from_bytes};
+
+    fn minimised(mismatch: &Mismatch) -> Mismatch {
+        compare(&minimise(&Board::from_fen(&mismatch.fen).unwrap())).unwrap_err()
+    }
+
+    #[test]
+    fn matches_reference_on_test_positions() {
+        for (depth, _, fen) in TEST_FENS {
+            if depth != 1 {
+                continue;
+            }
+            let mut board = Board::from_fen(fen).unwrap();
+            if let Err(mismatch) = compare_to_depth(&mut board, 2) {
+                panic!("{}", minimised(&mismatch));
+            }
+        }
+    }
+
+    #[test]
+    fn matches_reference_on_random_positions() {
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut random_byte = || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state.to_le_bytes()[0]
+        };
+
+        let mut positions = 0;
+        while positions < 5000 {
+            let bytes: Vec<u8> = (0..26).map(|_| random_byte()).collect();
+            let Some(board) = position_from_bytes(&bytes) else {
+                continue;
+            };
+            positions += 1;
+            if let Err(mismatch) = compare(&board) {
+                panic!("{}", minimised(&mismatch));
+            }
+        }
+    }
+}
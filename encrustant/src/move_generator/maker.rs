@@ -9,6 +9,7 @@ impl Board {
     /// Will panic if it is en passant and `self.game_state.en_passant_square` is `None`.
     pub fn make_move(&mut self, move_data: &Move) -> GameState {
         let old_state = self.game_state;
+        self.xor_game_state_zobrist();
 
         let white_to_move = self.white_to_move;
         let flag = move_data.flag;
@@ -39,9 +40,6 @@ impl Board {
                     self.game_state.castling_rights.unset_black_king_side();
                 }
 
-                let moving_bit_board = self.get_bit_board_mut(piece);
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
-
                 self.game_state.en_passant_square = None;
 
                 self.game_state.captured = self.enemy_piece_at(move_data.to);
@@ -55,11 +53,12 @@ impl Board {
                     } else if move_data.to == Square::from_index(63) {
                         self.game_state.castling_rights.unset_black_king_side();
                     }
-                    let capturing_bit_board = self.get_bit_board_mut(captured);
-                    capturing_bit_board.toggle(&move_data.to);
+                    self.remove_piece(captured, move_data.to);
 
                     self.game_state.half_move_clock = 0;
                 }
+
+                self.move_piece(piece, move_data.from, move_data.to);
             }
             Flag::PawnTwoUp => {
                 let piece = if white_to_move {
@@ -70,8 +69,7 @@ impl Board {
 
                 self.game_state.half_move_clock = 0;
 
-                let moving_bit_board = self.get_bit_board_mut(piece);
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                self.move_piece(piece, move_data.from, move_data.to);
 
                 let en_passant_square = move_data.from.up(if white_to_move { 1 } else { -1 });
                 self.game_state.en_passant_square = Some(en_passant_square);
@@ -94,8 +92,7 @@ impl Board {
                     self.game_state.castling_rights.unset_black_queen_side();
                 }
 
-                let moving_bit_board = self.get_bit_board_mut(piece);
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                self.move_piece(piece, move_data.from, move_data.to);
 
                 self.game_state.en_passant_square = None;
 
@@ -107,10 +104,9 @@ impl Board {
                 } else {
                     Piece::BlackRook
                 };
-                let rook_bit_board = self.get_bit_board_mut(rook);
                 let rook_from = move_data.to.offset(rook_from_offset);
                 let rook_to = move_data.to.offset(rook_to_offset);
-                rook_bit_board.toggle_two(&rook_from, &rook_to);
+                self.move_piece(rook, rook_from, rook_to);
             }
             Flag::EnPassant => {
                 let piece = if white_to_move {
@@ -121,8 +117,7 @@ impl Board {
 
                 self.game_state.half_move_clock = 0;
 
-                let moving_bit_board = self.get_bit_board_mut(piece);
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                self.move_piece(piece, move_data.from, move_data.to);
 
                 let capture_position = self
                     .game_state
@@ -136,8 +131,7 @@ impl Board {
                 };
                 self.game_state.captured = Some(captured);
 
-                let capturing_bit_board = self.get_bit_board_mut(captured);
-                capturing_bit_board.toggle(&capture_position);
+                self.remove_piece(captured, capture_position);
 
                 self.game_state.en_passant_square = None;
             }
@@ -155,9 +149,7 @@ impl Board {
 
                 let promotion_piece = flag.get_promotion_piece(white_to_move).unwrap();
 
-                let moving_bit_board = self.get_bit_board_mut(piece);
-                moving_bit_board.toggle(&move_data.from);
-                self.get_bit_board_mut(promotion_piece).set(&move_data.to);
+                self.remove_piece(piece, move_data.from);
 
                 self.game_state.en_passant_square = None;
 
@@ -172,13 +164,19 @@ impl Board {
                     } else if move_data.to == Square::from_index(63) {
                         self.game_state.castling_rights.unset_black_king_side();
                     }
-                    let capturing_bit_board = self.get_bit_board_mut(captured);
-                    capturing_bit_board.toggle(&move_data.to);
+                    self.remove_piece(captured, move_data.to);
                 }
+
+                self.add_piece(promotion_piece, move_data.to);
             }
         }
 
-        self.white_to_move = !white_to_move;
+        self.flip_side_to_move();
+        self.xor_game_state_zobrist();
+
+        self.debug_assert_zobrist();
+        #[cfg(feature = "validate")]
+        self.validate_incremental_state(*move_data, "making");
 
         old_state
     }
@@ -189,49 +187,50 @@ impl Board {
     /// Will panic if it is en passant and `self.game_state.captured` is `None`.
     pub fn unmake_move(&mut self, move_data: &Move, old_state: &GameState) {
         let capture = self.game_state.captured;
+        self.xor_game_state_zobrist();
         self.game_state = *old_state;
+        self.xor_game_state_zobrist();
 
-        let white_to_move = !self.white_to_move;
-        self.white_to_move = white_to_move;
+        self.flip_side_to_move();
+        let white_to_move = self.white_to_move;
 
         let flag = move_data.flag;
         match flag {
             Flag::None => {
-                let moving_bit_board =
-                    self.get_bit_board_mut(self.friendly_piece_at(move_data.to).unwrap());
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                let piece = self.friendly_piece_at(move_data.to).unwrap();
+                self.move_piece(piece, move_data.to, move_data.from);
 
                 if let Some(capture) = capture {
-                    let capturing_bit_board = self.get_bit_board_mut(capture);
-                    capturing_bit_board.set(&move_data.to);
+                    self.add_piece(capture, move_data.to);
                 }
             }
 
             Flag::PawnTwoUp => {
-                let moving_bit_board = self.get_bit_board_mut(if white_to_move {
+                let piece = if white_to_move {
                     Piece::WhitePawn
                 } else {
                     Piece::BlackPawn
-                });
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                };
+                self.move_piece(piece, move_data.to, move_data.from);
             }
 
             Flag::RookPromotion
             | Flag::BishopPromotion
             | Flag::KnightPromotion
             | Flag::QueenPromotion => {
-                let moving_bit_board = self.get_bit_board_mut(if white_to_move {
+                let piece = if white_to_move {
                     Piece::WhitePawn
                 } else {
                     Piece::BlackPawn
-                });
-                moving_bit_board.set(&move_data.from);
-                self.get_bit_board_mut(flag.get_promotion_piece(white_to_move).unwrap())
-                    .toggle(&move_data.to);
+                };
+                self.remove_piece(
+                    flag.get_promotion_piece(white_to_move).unwrap(),
+                    move_data.to,
+                );
+                self.add_piece(piece, move_data.from);
 
                 if let Some(capture) = capture {
-                    let capturing_bit_board = self.get_bit_board_mut(capture);
-                    capturing_bit_board.set(&move_data.to);
+                    self.add_piece(capture, move_data.to);
                 }
             }
 
@@ -242,38 +241,130 @@ impl Board {
                         .unwrap()
                         .down(if white_to_move { 1 } else { -1 })
                 };
-                let capturing_bit_board = self.get_bit_board_mut(capture.unwrap());
-                capturing_bit_board.set(&capture_position);
+                self.add_piece(capture.unwrap(), capture_position);
 
-                let moving_bit_board = self.get_bit_board_mut(if white_to_move {
+                let piece = if white_to_move {
                     Piece::WhitePawn
                 } else {
                     Piece::BlackPawn
-                });
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+                };
+                self.move_piece(piece, move_data.to, move_data.from);
             }
 
             Flag::Castle => {
                 let is_king_side = move_data.to.file() == 6;
                 let rook_to_offset = if is_king_side { -1 } else { 1 };
                 let rook_from_offset = if is_king_side { 1 } else { -2 };
-                let rook_bit_board = if white_to_move {
-                    self.get_bit_board_mut(Piece::WhiteRook)
+                let (rook, king) = if white_to_move {
+                    (Piece::WhiteRook, Piece::WhiteKing)
                 } else {
-                    self.get_bit_board_mut(Piece::BlackRook)
+                    (Piece::BlackRook, Piece::BlackKing)
                 };
-                rook_bit_board.toggle_two(
-                    &move_data.to.offset(rook_from_offset),
-                    &move_data.to.offset(rook_to_offset),
+                self.move_piece(
+                    rook,
+                    move_data.to.offset(rook_to_offset),
+                    move_data.to.offset(rook_from_offset),
                 );
+                self.move_piece(king, move_data.to, move_data.from);
+            }
+        }
 
-                let moving_bit_board = self.get_bit_board_mut(if white_to_move {
-                    Piece::WhiteKing
-                } else {
-                    Piece::BlackKing
-                });
-                moving_bit_board.toggle_two(&move_data.from, &move_data.to);
+        self.debug_assert_zobrist();
+        #[cfg(feature = "validate")]
+        self.validate_incremental_state(*move_data, "unmaking");
+    }
+
+    /// Passes the turn without moving a piece.
+    pub const fn make_null_move(&mut self) -> GameState {
+        let old_state = self.game_state;
+        self.xor_game_state_zobrist();
+
+        self.game_state.half_move_clock = 0;
+        self.game_state.en_passant_square = None;
+        self.game_state.captured = None;
+        self.flip_side_to_move();
+
+        self.xor_game_state_zobrist();
+
+        old_state
+    }
+
+    /// Takes back a null move.
+    pub const fn unmake_null_move(&mut self, old_state: &GameState) {
+        self.xor_game_state_zobrist();
+        self.game_state = *old_state;
+        self.xor_game_state_zobrist();
+
+        self.flip_side_to_move();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, bit_board::BitBoard, piece::Piece, square::Square},
+        move_generator::{MoveGenerator, move_data::Flag},
+        tests::test_positions,
+        uci::decode_move,
+    };
+
+    fn assert_mailbox_matches(board: &Board) {
+        let mut occupancy = [BitBoard::EMPTY; 2];
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            let piece = Piece::ALL_PIECES
+                .into_iter()
+                .find(|piece| board.get_bit_board(*piece).get(&square));
+            assert_eq!(board.piece_at(square), piece, "{board} {square}");
+            if let Some(piece) = piece {
+                occupancy[usize::from(!piece.is_white())].set(&square);
             }
         }
+        assert_eq!(board.white_occupancy(), occupancy[0], "{board}");
+        assert_eq!(board.black_occupancy(), occupancy[1], "{board}");
+    }
+
+    fn make_unmake(board: &mut Board, depth: u8) {
+        assert_mailbox_matches(board);
+        if depth == 0 {
+            return;
+        }
+
+        let mut moves = Vec::new();
+        MoveGenerator::new(board).generate(|move_data| moves.push(move_data), false);
+        for move_data in moves {
+            let old_state = board.make_move(&move_data);
+            make_unmake(board, depth - 1);
+            board.unmake_move(&move_data, &old_state);
+        }
+        assert_mailbox_matches(board);
+    }
+
+    #[test]
+    fn zobrist_key_after_transposition() {
+        let play = |moves: [(&str, &str); 3]| {
+            let mut board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+            for (from, to) in moves {
+                let from = Square::from_notation(from).unwrap();
+                let to = Square::from_notation(to).unwrap();
+                board.make_move(&decode_move(&board, from, to, Flag::None));
+            }
+            board.zobrist()
+        };
+        assert_eq!(
+            play([("g1", "f3"), ("g8", "f6"), ("b1", "c3")]),
+            play([("b1", "c3"), ("g8", "f6"), ("g1", "f3")])
+        );
+        assert_ne!(
+            play([("g1", "f3"), ("g8", "f6"), ("b1", "c3")]),
+            play([("g1", "f3"), ("b8", "c6"), ("b1", "c3")])
+        );
+    }
+
+    #[test]
+    fn mailbox_stays_in_sync() {
+        for mut board in test_positions() {
+            make_unmake(&mut board, 2);
+        }
     }
 }
@@ -8,13 +8,26 @@ use crate::board::piece::Piece;
 use crate::board::square::Square;
 use crate::consume_bit_board;
 
+mod attacks;
 mod maker;
 mod pawn_move_generator;
 mod precomputed;
+mod validation;
+
+pub(crate) use pawn_move_generator::PAWN_ATTACKS;
+
+/// Compares the move generator with the reference generator.
+pub mod differential;
 
 /// Move data.
 pub mod move_data;
 
+/// Fixed-capacity list of moves.
+pub mod move_list;
+
+/// Slow but simple legal move generator.
+pub mod reference;
+
 /// Magic keys used for slider lookup.
 pub mod slider_keys;
 
@@ -22,11 +35,36 @@ pub mod slider_keys;
 pub mod slider_lookup;
 
 use self::move_data::{Flag, Move};
-use self::precomputed::{KING_MOVES_AT_SQUARE, KNIGHT_MOVES_AT_SQUARE};
+pub(crate) use self::precomputed::{KING_MOVES_AT_SQUARE, KNIGHT_MOVES_AT_SQUARE};
 use self::slider_lookup::{
     get_bishop_moves, get_rook_moves, relevant_bishop_blockers, relevant_rook_blockers,
 };
 
+/// Which kinds of moves to generate.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MoveTypes {
+    All,
+
+    /// Captures, capture promotions and en passant.
+    Captures,
+
+    /// Everything that is not a capture, including promotions without a capture and castling.
+    Quiets,
+
+    /// Moves out of check: king moves, captures of a single checker and moves that block its line to the king.
+    Evasions,
+}
+
+impl MoveTypes {
+    const fn captures(self) -> bool {
+        matches!(self, Self::All | Self::Captures | Self::Evasions)
+    }
+
+    const fn quiets(self) -> bool {
+        matches!(self, Self::All | Self::Quiets | Self::Evasions)
+    }
+}
+
 /// Legal move generator.
 #[allow(clippy::struct_excessive_bools)]
 pub struct MoveGenerator {
@@ -65,8 +103,18 @@ pub struct MoveGenerator {
 }
 
 impl MoveGenerator {
-    fn gen_pawns<F: FnMut(Move)>(&self, add_move: &mut F, captures_only: bool) {
-        pawn_move_generator::generate(self, add_move, captures_only);
+    fn gen_pawns<F: FnMut(Move)>(&self, add_move: &mut F, move_types: MoveTypes) {
+        pawn_move_generator::generate(self, add_move, move_types);
+    }
+
+    /// Returns the squares that moves of this type can go to, ignoring checks and pins.
+    fn targets(&self, move_types: MoveTypes) -> BitBoard {
+        match move_types {
+            MoveTypes::All => self.enemy_pieces | self.empty,
+            MoveTypes::Captures => self.enemy_pieces,
+            MoveTypes::Quiets => self.empty,
+            MoveTypes::Evasions => self.check_mask & !self.friendly_pieces,
+        }
     }
 }
 
@@ -75,14 +123,11 @@ impl MoveGenerator {
         KNIGHT_MOVES_AT_SQUARE[square.usize()]
     }
 
-    fn gen_knights<F: FnMut(Move)>(&self, add_move: &mut F, captures_only: bool) {
+    fn gen_knights<F: FnMut(Move)>(&self, add_move: &mut F, move_types: MoveTypes) {
         let mut non_pinned_knights =
             self.friendly_knights & !(self.diagonal_pin_rays | self.orthogonal_pin_rays);
 
-        let mut mask = (self.empty | self.enemy_pieces) & self.check_mask;
-        if captures_only {
-            mask &= self.enemy_pieces;
-        }
+        let mask = self.targets(move_types) & self.check_mask;
 
         consume_bit_board!(non_pinned_knights, from {
             let mut knight_moves = Self::knight_attack_bit_board(from) & mask;
@@ -99,13 +144,10 @@ impl MoveGenerator {
 }
 
 impl MoveGenerator {
-    fn gen_bishop<F: FnMut(Move)>(&self, from: Square, add_move: &mut F, captures_only: bool) {
+    fn gen_bishop<F: FnMut(Move)>(&self, from: Square, add_move: &mut F, move_types: MoveTypes) {
         let blockers = self.occupied & relevant_bishop_blockers(from);
         let possible_moves = get_bishop_moves(from, blockers);
-        let mut legal_moves = possible_moves & ((self.enemy_pieces | self.empty) & self.check_mask);
-        if captures_only {
-            legal_moves &= self.enemy_pieces;
-        }
+        let mut legal_moves = possible_moves & self.targets(move_types) & self.check_mask;
         if self.diagonal_pin_rays.get(&from) {
             legal_moves &= self.diagonal_pin_rays;
         }
@@ -118,13 +160,10 @@ impl MoveGenerator {
             });
         });
     }
-    fn gen_rook<F: FnMut(Move)>(&self, from: Square, add_move: &mut F, captures_only: bool) {
+    fn gen_rook<F: FnMut(Move)>(&self, from: Square, add_move: &mut F, move_types: MoveTypes) {
         let blockers = self.occupied & relevant_rook_blockers(from);
         let possible_moves = get_rook_moves(from, blockers);
-        let mut legal_moves = possible_moves & ((self.enemy_pieces | self.empty) & self.check_mask);
-        if captures_only {
-            legal_moves &= self.enemy_pieces;
-        }
+        let mut legal_moves = possible_moves & self.targets(move_types) & self.check_mask;
         if self.orthogonal_pin_rays.get(&from) {
             legal_moves &= self.orthogonal_pin_rays;
         }
@@ -220,13 +259,15 @@ impl MoveGenerator {
     }
 
     #[allow(clippy::unreadable_literal)]
-    fn gen_king<F: FnMut(Move)>(&self, add_move: &mut F, captures_only: bool) {
-        let mut king_moves = Self::king_attack_bit_board(self.friendly_king_square)
-            & !self.friendly_pieces
-            & !self.king_danger;
-        if captures_only {
-            king_moves &= self.enemy_pieces;
-        }
+    fn gen_king<F: FnMut(Move)>(&self, add_move: &mut F, move_types: MoveTypes) {
+        let targets = if move_types == MoveTypes::Evasions {
+            // The king can step out of check, not just capture or block
+            !self.friendly_pieces
+        } else {
+            self.targets(move_types)
+        };
+        let mut king_moves =
+            Self::king_attack_bit_board(self.friendly_king_square) & targets & !self.king_danger;
 
         consume_bit_board!(king_moves, to {
             add_move(Move {
@@ -236,7 +277,7 @@ impl MoveGenerator {
             });
         });
 
-        if self.is_in_check || captures_only {
+        if self.is_in_check || !move_types.quiets() {
             return;
         }
 
@@ -497,26 +538,58 @@ impl MoveGenerator {
         }
     }
 
-    /// Generates all friendly piece moves
-    pub fn generate(&self, mut add_move: impl FnMut(Move), captures_only: bool) {
-        self.gen_king(&mut add_move, captures_only);
+    fn generate_move_types(&self, mut add_move: impl FnMut(Move), move_types: MoveTypes) {
+        self.gen_king(&mut add_move, move_types);
         if self.is_in_double_check {
             // Only king can move in double check
             return;
         }
 
-        self.gen_pawns(&mut add_move, captures_only);
-        self.gen_knights(&mut add_move, captures_only);
+        self.gen_pawns(&mut add_move, move_types);
+        self.gen_knights(&mut add_move, move_types);
         let mut friendly_diagonal = self.friendly_diagonal & !self.orthogonal_pin_rays;
         consume_bit_board!(friendly_diagonal, from {
-            self.gen_bishop(from, &mut add_move, captures_only);
+            self.gen_bishop(from, &mut add_move, move_types);
         });
         let mut friendly_orthogonal = self.friendly_orthogonal & !self.diagonal_pin_rays;
         consume_bit_board!(friendly_orthogonal, from {
-            self.gen_rook(from, &mut add_move, captures_only);
+            self.gen_rook(from, &mut add_move, move_types);
         });
     }
 
+    /// Generates all friendly piece moves
+    ///
+    /// Only legal moves are generated, so when in check these are the moves that get out of check.
+    pub fn generate(&self, add_move: impl FnMut(Move), captures_only: bool) {
+        self.generate_move_types(
+            add_move,
+            if captures_only {
+                MoveTypes::Captures
+            } else {
+                MoveTypes::All
+            },
+        );
+    }
+
+    /// Generates captures, capture promotions and en passant.
+    pub fn generate_captures(&self, add_move: impl FnMut(Move)) {
+        self.generate_move_types(add_move, MoveTypes::Captures);
+    }
+
+    /// Generates every move that [`Self::generate_captures`] does not, including promotions without a capture and castling.
+    pub fn generate_quiets(&self, add_move: impl FnMut(Move)) {
+        self.generate_move_types(add_move, MoveTypes::Quiets);
+    }
+
+    /// Generates the moves that get out of check: king moves, captures of the checking piece and moves that block
+    /// it.
+    ///
+    /// Must only be used when [`Self::is_in_check`]. These are then all of the legal moves.
+    pub fn generate_evasions(&self, add_move: impl FnMut(Move)) {
+        debug_assert!(self.is_in_check);
+        self.generate_move_types(add_move, MoveTypes::Evasions);
+    }
+
     /// Calculates whether the side to move is in check.
     #[must_use]
     pub fn raw_calculate_is_in_check(
@@ -84,7 +84,7 @@ impl Flag {
 }
 
 /// A piece move.
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
 pub struct Move {
     /// Square the piece is moving from.
     pub from: Square,
@@ -96,6 +96,18 @@ pub struct Move {
     pub flag: Flag,
 }
 
+impl Move {
+    /// Returns the same move on a board with the ranks flipped.
+    #[must_use]
+    pub const fn mirrored(&self) -> Self {
+        Self {
+            from: self.from.flip(),
+            to: self.to.flip(),
+            flag: self.flag,
+        }
+    }
+}
+
 impl fmt::Display for Move {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.flag == Flag::None {
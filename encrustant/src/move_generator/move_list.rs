@@ -0,0 +1,245 @@
+use core::ops::Deref;
+
+use crate::board::{Board, square::Square};
+
+use super::{
+    MoveGenerator,
+    move_data::{Flag, Move},
+};
+
+/// The most legal moves that any chess position has.
+pub const MAX_LEGAL_MOVES: usize = 218;
+
+/// A list of moves that can hold every legal move in a position without allocating.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::board::Board;
+///
+/// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// let moves = board.legal_moves();
+/// assert_eq!(moves.len(), 20);
+/// for move_data in &moves {
+///     assert!(board.is_legal(*move_data));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MoveList {
+    moves: [Move; MAX_LEGAL_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    /// Creates an empty list.
+    #[must_use]
+    pub const fn new() -> Self {
+        const EMPTY: Move = Move {
+            from: Square::from_index(0),
+            to: Square::from_index(0),
+            flag: Flag::None,
+        };
+        Self {
+            moves: [EMPTY; MAX_LEGAL_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Adds a move to the end of the list.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the list already has [`MAX_LEGAL_MOVES`] moves.
+    pub const fn push(&mut self, move_data: Move) {
+        self.moves[self.len] = move_data;
+        self.len += 1;
+    }
+
+    /// Returns the moves as a slice.
+    #[must_use]
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for MoveList {
+    type Target = [Move];
+
+    fn deref(&self) -> &[Move] {
+        self.as_slice()
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = core::slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl IntoIterator for MoveList {
+    type Item = Move;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter {
+            list: self,
+            index: 0,
+        }
+    }
+}
+
+/// Iterator over the moves of a [`MoveList`], made by [`MoveList::into_iter`].
+pub struct IntoIter {
+    list: MoveList,
+    index: usize,
+}
+
+impl Iterator for IntoIter {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        let move_data = self.list.get(self.index).copied()?;
+        self.index += 1;
+        Some(move_data)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.list.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl Board {
+    /// Returns every legal move in the position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    ///
+    /// // Checkmate
+    /// let board = Board::from_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+    /// assert!(board.legal_moves().is_empty());
+    /// ```
+    #[must_use]
+    pub fn legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        MoveGenerator::new(self).generate(|move_data| moves.push(move_data), false);
+        moves
+    }
+
+    /// Returns every legal move of the piece on a square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::{Board, square::Square};
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// assert_eq!(board.legal_moves_from(Square::from_notation("g1").unwrap()).len(), 2);
+    /// assert!(board.legal_moves_from(Square::from_notation("e4").unwrap()).is_empty());
+    /// ```
+    #[must_use]
+    pub fn legal_moves_from(&self, square: Square) -> MoveList {
+        let mut moves = MoveList::new();
+        MoveGenerator::new(self).generate(
+            |move_data| {
+                if move_data.from == square {
+                    moves.push(move_data);
+                }
+            },
+            false,
+        );
+        moves
+    }
+
+    /// Returns every legal capture, including capture promotions and en passant.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1").unwrap();
+    /// assert_eq!(board.captures().len(), 1);
+    /// ```
+    #[must_use]
+    pub fn captures(&self) -> MoveList {
+        let mut moves = MoveList::new();
+        MoveGenerator::new(self).generate_captures(|move_data| moves.push(move_data));
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, square::Square},
+        move_generator::MoveGenerator,
+        tests::TEST_FENS,
+    };
+
+    #[test]
+    fn legal_moves_match_perft() {
+        for (depth, expected_nodes, fen) in TEST_FENS {
+            if depth != 1 {
+                continue;
+            }
+            let board = Board::from_fen(fen).unwrap();
+            let moves = board.legal_moves();
+            assert_eq!(moves.len() as u64, expected_nodes, "{fen}");
+            assert_eq!(moves.clone().into_iter().len(), moves.len());
+
+            let from_each_square: usize = (0..64)
+                .map(|index| board.legal_moves_from(Square::from_index(index)).len())
+                .sum();
+            assert_eq!(from_each_square, moves.len(), "{fen}");
+
+            for capture in board.captures() {
+                assert!(moves.contains(&capture), "{fen} {capture}");
+            }
+        }
+    }
+
+    #[test]
+    fn evasions_are_the_legal_moves_in_check() {
+        let mut positions_in_check = 0;
+        for (depth, _, fen) in TEST_FENS {
+            if depth != 1 {
+                continue;
+            }
+            let mut board = Board::from_fen(fen).unwrap();
+            for move_data in board.legal_moves() {
+                let old_state = board.make_move(&move_data);
+                if board.checkers().is_not_empty() {
+                    positions_in_check += 1;
+
+                    let legal_moves = board.legal_moves();
+                    let mut evasions = Vec::new();
+                    MoveGenerator::new(&board).generate_evasions(|evasion| evasions.push(evasion));
+                    assert_eq!(evasions.len(), legal_moves.len(), "{}", board.to_fen());
+                    for evasion in evasions {
+                        assert!(
+                            legal_moves.contains(&evasion),
+                            "{} {evasion}",
+                            board.to_fen()
+                        );
+                    }
+                }
+                board.unmake_move(&move_data, &old_state);
+            }
+        }
+        assert!(positions_in_check > 0);
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
 };
 
 use super::{
-    MoveGenerator,
+    MoveGenerator, MoveTypes,
     move_data::{Flag, Move},
     slider_lookup::{get_rook_moves, relevant_rook_blockers},
 };
@@ -85,7 +85,7 @@ pub const fn attack_bit_board(from: Square, white: bool) -> BitBoard {
 pub fn generate<F: FnMut(Move)>(
     move_generator: &MoveGenerator,
     add_move: &mut F,
-    captures_only: bool,
+    move_types: MoveTypes,
 ) {
     let promotion_rank = if move_generator.white_to_move {
         BitBoard::RANK_8
@@ -93,7 +93,7 @@ pub fn generate<F: FnMut(Move)>(
         BitBoard::RANK_1
     };
 
-    {
+    if move_types.captures() {
         // Captures
 
         let non_orthogonally_pinned_pawns =
@@ -168,9 +168,11 @@ pub fn generate<F: FnMut(Move)>(
         let mut capture_left_no_promotions = capture_left & !capture_left_promotions;
         promotion_captures!(capture_left_promotions, capture_left_offset);
         captures_no_promotions!(capture_left_no_promotions, capture_left_offset);
-    };
+    }
 
-    if let Some(en_passant_square) = move_generator.en_passant_square {
+    if move_types.captures()
+        && let Some(en_passant_square) = move_generator.en_passant_square
+    {
         // En passant
 
         let capture_position =
@@ -216,7 +218,7 @@ pub fn generate<F: FnMut(Move)>(
         }
     }
 
-    if captures_only {
+    if !move_types.quiets() {
         return;
     }
 
@@ -0,0 +1,266 @@
+use crate::board::{Board, piece::Piece, square::Square};
+
+use super::move_data::{Flag, Move};
+
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const ORTHOGONAL_STEPS: [(i8, i8); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const DIAGONAL_STEPS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+const KING_STEPS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 0),
+    (0, -1),
+    (-1, 0),
+    (1, 1),
+    (1, -1),
+    (-1, -1),
+    (-1, 1),
+];
+
+/// Returns the square `rank_step` ranks up and `file_step` files right, if it is on the board.
+fn step(square: Square, (rank_step, file_step): (i8, i8)) -> Option<Square> {
+    let rank = square.rank() + rank_step;
+    let file = square.file() + file_step;
+    ((0..8).contains(&rank) && (0..8).contains(&file)).then(|| Square::from_coords(rank, file))
+}
+
+/// Returns the squares along a ray, stopping after the first occupied one.
+fn walk(board: &Board, from: Square, direction: (i8, i8)) -> impl Iterator<Item = Square> {
+    let mut next = step(from, direction);
+    core::iter::from_fn(move || {
+        let square = next?;
+        next = if board.piece_at(square).is_some() {
+            None
+        } else {
+            step(square, direction)
+        };
+        Some(square)
+    })
+}
+
+fn is_piece(board: &Board, square: Option<Square>, piece: Piece) -> bool {
+    square.is_some_and(|square| board.piece_at(square) == Some(piece))
+}
+
+/// Returns whether a piece of the given colour attacks the square, by looking outwards from it.
+#[must_use]
+pub fn is_attacked(board: &Board, square: Square, by_white: bool) -> bool {
+    let [pawn, knight, bishop, rook, queen, king] = if by_white {
+        Piece::WHITE_PIECES
+    } else {
+        Piece::BLACK_PIECES
+    };
+
+    // Pawns attack diagonally forwards, so look diagonally backwards for them
+    let pawn_rank_step = if by_white { -1 } else { 1 };
+    if [-1, 1]
+        .into_iter()
+        .any(|file_step| is_piece(board, step(square, (pawn_rank_step, file_step)), pawn))
+    {
+        return true;
+    }
+
+    KNIGHT_STEPS
+        .into_iter()
+        .any(|direction| is_piece(board, step(square, direction), knight))
+        || KING_STEPS
+            .into_iter()
+            .any(|direction| is_piece(board, step(square, direction), king))
+        || ORTHOGONAL_STEPS.into_iter().any(|direction| {
+            walk(board, square, direction)
+                .any(|square| [rook, queen].contains(&board.piece_at(square).unwrap_or(pawn)))
+        })
+        || DIAGONAL_STEPS.into_iter().any(|direction| {
+            walk(board, square, direction)
+                .any(|square| [bishop, queen].contains(&board.piece_at(square).unwrap_or(pawn)))
+        })
+}
+
+fn pawn_moves(board: &Board, from: Square, moves: &mut Vec<Move>) {
+    let white = board.white_to_move;
+    let rank_step = if white { 1 } else { -1 };
+    let promotion_rank = if white { 7 } else { 0 };
+    let mut add = |to: Square, flag: Flag| {
+        if to.rank() == promotion_rank {
+            for flag in Flag::PROMOTIONS {
+                moves.push(Move { from, to, flag });
+            }
+        } else {
+            moves.push(Move { from, to, flag });
+        }
+    };
+
+    if let Some(forward) = step(from, (rank_step, 0))
+        && board.piece_at(forward).is_none()
+    {
+        add(forward, Flag::None);
+
+        let starting_rank = if white { 1 } else { 6 };
+        if from.rank() == starting_rank
+            && let Some(two_forward) = step(forward, (rank_step, 0))
+            && board.piece_at(two_forward).is_none()
+        {
+            add(two_forward, Flag::PawnTwoUp);
+        }
+    }
+
+    for file_step in [-1, 1] {
+        let Some(to) = step(from, (rank_step, file_step)) else {
+            continue;
+        };
+        if board.enemy_piece_at(to).is_some() {
+            add(to, Flag::None);
+        } else if board.game_state.en_passant_square == Some(to) {
+            add(to, Flag::EnPassant);
+        }
+    }
+}
+
+fn castling_moves(board: &Board, moves: &mut Vec<Move>) {
+    let white = board.white_to_move;
+    let rank = if white { 0 } else { 7 };
+    let castling_rights = board.game_state.castling_rights;
+    let (king, rook) = if white {
+        (Piece::WhiteKing, Piece::WhiteRook)
+    } else {
+        (Piece::BlackKing, Piece::BlackRook)
+    };
+    let from = Square::from_coords(rank, 4);
+    if board.piece_at(from) != Some(king) {
+        return;
+    }
+
+    for (has_right, rook_file, king_file, passing_file) in [
+        (
+            if white {
+                castling_rights.get_white_king_side()
+            } else {
+                castling_rights.get_black_king_side()
+            },
+            7,
+            6,
+            5,
+        ),
+        (
+            if white {
+                castling_rights.get_white_queen_side()
+            } else {
+                castling_rights.get_black_queen_side()
+            },
+            0,
+            2,
+            3,
+        ),
+    ] {
+        let between = (rook_file.min(4) + 1)..rook_file.max(4);
+        if has_right
+            && board.piece_at(Square::from_coords(rank, rook_file)) == Some(rook)
+            && between
+                .into_iter()
+                .all(|file| board.piece_at(Square::from_coords(rank, file)).is_none())
+            && [4, passing_file, king_file]
+                .into_iter()
+                .all(|file| !is_attacked(board, Square::from_coords(rank, file), !white))
+        {
+            moves.push(Move {
+                from,
+                to: Square::from_coords(rank, king_file),
+                flag: Flag::Castle,
+            });
+        }
+    }
+}
+
+/// Generates the moves of the side to move without checking whether they leave the king attacked.
+fn pseudo_legal_moves(board: &Board) -> Vec<Move> {
+    let mut moves = Vec::new();
+    for index in 0..64 {
+        let from = Square::from_index(index);
+        let Some(piece) = board.friendly_piece_at(from) else {
+            continue;
+        };
+
+        let add_if_not_friendly = |to: Square| {
+            if board.friendly_piece_at(to).is_none() {
+                moves.push(Move {
+                    from,
+                    to,
+                    flag: Flag::None,
+                });
+            }
+        };
+        match piece {
+            Piece::WhitePawn | Piece::BlackPawn => pawn_moves(board, from, &mut moves),
+            Piece::WhiteKnight | Piece::BlackKnight => KNIGHT_STEPS
+                .into_iter()
+                .filter_map(|direction| step(from, direction))
+                .for_each(add_if_not_friendly),
+            Piece::WhiteKing | Piece::BlackKing => KING_STEPS
+                .into_iter()
+                .filter_map(|direction| step(from, direction))
+                .for_each(add_if_not_friendly),
+            Piece::WhiteBishop | Piece::BlackBishop => DIAGONAL_STEPS
+                .into_iter()
+                .flat_map(|direction| walk(board, from, direction))
+                .for_each(add_if_not_friendly),
+            Piece::WhiteRook | Piece::BlackRook => ORTHOGONAL_STEPS
+                .into_iter()
+                .flat_map(|direction| walk(board, from, direction))
+                .for_each(add_if_not_friendly),
+            Piece::WhiteQueen | Piece::BlackQueen => KING_STEPS
+                .into_iter()
+                .flat_map(|direction| walk(board, from, direction))
+                .for_each(add_if_not_friendly),
+        }
+    }
+    castling_moves(board, &mut moves);
+    moves
+}
+
+/// Generates every legal move by walking along rays one square at a time,
+/// then playing each move on a copy of the board to see if it leaves the king attacked.
+///
+/// This does not use any of the lookup tables or pin and check masks that [`super::MoveGenerator`] relies on,
+/// so it is far slower but simple enough to check the real generator against.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::{board::Board, move_generator::reference};
+///
+/// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// assert_eq!(reference::legal_moves(&board).len(), 20);
+/// ```
+///
+/// # Panics
+///
+/// Will panic if the side to move has no king.
+#[must_use]
+pub fn legal_moves(board: &Board) -> Vec<Move> {
+    let white = board.white_to_move;
+    let king = if white {
+        Piece::WhiteKing
+    } else {
+        Piece::BlackKing
+    };
+
+    let mut moves = pseudo_legal_moves(board);
+    moves.retain(|move_data| {
+        let mut board = board.clone();
+        board.make_move(move_data);
+        let king_square = (0..64)
+            .map(Square::from_index)
+            .find(|square| board.piece_at(*square) == Some(king))
+            .unwrap();
+        !is_attacked(&board, king_square, !white)
+    });
+    moves
+}
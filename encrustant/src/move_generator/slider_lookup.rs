@@ -187,18 +187,121 @@ fn slider_lookup() -> &'static [BitBoard; SLIDERS_TABLE_SIZE] {
     COMPUTATION.get_or_init(|| init_lookup(SLIDERS_TABLE_SIZE).try_into().unwrap())
 }
 
+// Only used to check the pext lookup when it is enabled
+#[cfg_attr(
+    all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"),
+    allow(dead_code)
+)]
+fn magic_rook_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
+    let key = ROOK_KEYS[square.usize()];
+    slider_lookup()[key.offset as usize + relevant_blockers.magic_index(key.magic, 64 - 12)]
+}
+
+// Only used to check the pext lookup when it is enabled
+#[cfg_attr(
+    all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"),
+    allow(dead_code)
+)]
+fn magic_bishop_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
+    let key = BISHOP_KEYS[square.usize()];
+    slider_lookup()[key.offset as usize + relevant_blockers.magic_index(key.magic, 64 - 9)]
+}
+
+/// Slider lookup indexed with the BMI2 `pext` instruction, which packs the relevant blockers into the low bits of
+/// the index so that no magic numbers are needed.
+///
+/// `pext` is slow on AMD processors before Zen 3, so this is only used with the `pext` feature, and only when BMI2 is
+/// enabled at compile time, for example with `-C target-cpu=native`.
+#[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+mod pext {
+    use std::sync::OnceLock;
+
+    use crate::board::{bit_board::BitBoard, square::Square};
+
+    use super::{
+        RELEVANT_BISHOP_BLOCKERS, RELEVANT_ROOK_BLOCKERS, gen_rook_or_bishop, iterate_combinations,
+    };
+
+    /// Index of the first entry of each square, and the lookup table.
+    struct PextTable {
+        offsets: [usize; 64],
+        lookup: Box<[BitBoard]>,
+    }
+
+    impl PextTable {
+        fn new(blockers: &[BitBoard; 64], direction_offset: usize) -> Self {
+            let mut offsets = [0; 64];
+            let mut lookup = Vec::new();
+            for square in (0..64).map(Square::from_index) {
+                let square_index = square.usize();
+                offsets[square_index] = lookup.len();
+                lookup.resize(
+                    lookup.len() + (1 << blockers[square_index].count()),
+                    BitBoard::EMPTY,
+                );
+
+                for blocker_combination in iterate_combinations(blockers[square_index]) {
+                    lookup[offsets[square_index]
+                        + blocker_combination.pext_index(blockers[square_index])] =
+                        gen_rook_or_bishop(square, &blocker_combination, direction_offset);
+                }
+            }
+            Self {
+                offsets,
+                lookup: lookup.into_boxed_slice(),
+            }
+        }
+
+        fn get(&self, square: Square, blockers: BitBoard, relevant_blockers: BitBoard) -> BitBoard {
+            self.lookup[self.offsets[square.usize()] + blockers.pext_index(relevant_blockers)]
+        }
+    }
+
+    fn rook_table() -> &'static PextTable {
+        static TABLE: OnceLock<PextTable> = OnceLock::new();
+        TABLE.get_or_init(|| PextTable::new(&RELEVANT_ROOK_BLOCKERS, 0))
+    }
+
+    fn bishop_table() -> &'static PextTable {
+        static TABLE: OnceLock<PextTable> = OnceLock::new();
+        TABLE.get_or_init(|| PextTable::new(&RELEVANT_BISHOP_BLOCKERS, 4))
+    }
+
+    pub fn rook_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
+        rook_table().get(
+            square,
+            relevant_blockers,
+            RELEVANT_ROOK_BLOCKERS[square.usize()],
+        )
+    }
+
+    pub fn bishop_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
+        bishop_table().get(
+            square,
+            relevant_blockers,
+            RELEVANT_BISHOP_BLOCKERS[square.usize()],
+        )
+    }
+}
+
 /// Returns possible rook moves at a square given blockers which are relevant.
 #[must_use]
 pub fn get_rook_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
-    let key = ROOK_KEYS[square.usize()];
-    slider_lookup()[key.offset as usize + relevant_blockers.magic_index(key.magic, 64 - 12)]
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    return pext::rook_moves(square, relevant_blockers);
+
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2")))]
+    magic_rook_moves(square, relevant_blockers)
 }
 
 /// Returns possible bishop moves at a square given blockers which are relevant.
 #[must_use]
 pub fn get_bishop_moves(square: Square, relevant_blockers: BitBoard) -> BitBoard {
-    let key = BISHOP_KEYS[square.usize()];
-    slider_lookup()[key.offset as usize + relevant_blockers.magic_index(key.magic, 64 - 9)]
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    return pext::bishop_moves(square, relevant_blockers);
+
+    #[cfg(not(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2")))]
+    magic_bishop_moves(square, relevant_blockers)
 }
 
 #[cfg(test)]
@@ -253,4 +356,29 @@ mod tests {
         println!("{legal_moves}");
         assert_eq!(legal_moves.count(), 25);
     }
+
+    #[test]
+    #[cfg(all(feature = "pext", target_arch = "x86_64", target_feature = "bmi2"))]
+    fn pext_matches_magic() {
+        use crate::move_generator::slider_lookup::{
+            magic_bishop_moves, magic_rook_moves,
+            pext::{bishop_moves, rook_moves},
+        };
+
+        for index in 0..64 {
+            let square = Square::from_index(index);
+            for blockers in iterate_combinations(RELEVANT_ROOK_BLOCKERS[square.usize()]) {
+                assert_eq!(
+                    rook_moves(square, blockers),
+                    magic_rook_moves(square, blockers)
+                );
+            }
+            for blockers in iterate_combinations(RELEVANT_BISHOP_BLOCKERS[square.usize()]) {
+                assert_eq!(
+                    bishop_moves(square, blockers),
+                    magic_bishop_moves(square, blockers)
+                );
+            }
+        }
+    }
 }
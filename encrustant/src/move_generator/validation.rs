@@ -0,0 +1,241 @@
+use crate::board::{Board, bit_board::BitBoard, piece::Piece, square::Square};
+
+use super::{
+    move_data::{Flag, Move},
+    pawn_move_generator,
+    precomputed::{KING_MOVES_AT_SQUARE, KNIGHT_MOVES_AT_SQUARE},
+    slider_lookup::{
+        get_bishop_moves, get_rook_moves, relevant_bishop_blockers, relevant_rook_blockers,
+    },
+};
+
+impl Board {
+    /// Returns whether the move could be generated in this position if the king's safety was ignored.
+    ///
+    /// This is much faster than generating every move, so moves that were saved with only part of the zobrist key,
+    /// such as transposition table and killer moves, can be checked before they are played.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{
+    ///     board::{Board, square::Square},
+    ///     move_generator::move_data::{Flag, Move},
+    /// };
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// let e2 = Square::from_notation("e2").unwrap();
+    /// let e4 = Square::from_notation("e4").unwrap();
+    /// assert!(board.is_pseudo_legal(Move { from: e2, to: e4, flag: Flag::PawnTwoUp }));
+    /// assert!(!board.is_pseudo_legal(Move { from: e2, to: e4, flag: Flag::None }));
+    /// assert!(!board.is_pseudo_legal(Move { from: e4, to: e2, flag: Flag::None }));
+    /// ```
+    #[must_use]
+    pub fn is_pseudo_legal(&self, move_data: Move) -> bool {
+        let Move { from, to, flag } = move_data;
+        let Some(piece) = self.friendly_piece_at(from) else {
+            return false;
+        };
+        if self.friendly_occupancy().get(&to) {
+            return false;
+        }
+
+        let occupied = self.occupied();
+        match piece {
+            Piece::WhitePawn | Piece::BlackPawn => self.is_pseudo_legal_pawn_move(move_data),
+            Piece::WhiteKing | Piece::BlackKing if flag == Flag::Castle => {
+                self.is_pseudo_legal_castle(from, to)
+            }
+            _ if flag != Flag::None => false,
+            Piece::WhiteKnight | Piece::BlackKnight => {
+                KNIGHT_MOVES_AT_SQUARE[from.usize()].get(&to)
+            }
+            Piece::WhiteBishop | Piece::BlackBishop => {
+                get_bishop_moves(from, occupied & relevant_bishop_blockers(from)).get(&to)
+            }
+            Piece::WhiteRook | Piece::BlackRook => {
+                get_rook_moves(from, occupied & relevant_rook_blockers(from)).get(&to)
+            }
+            Piece::WhiteQueen | Piece::BlackQueen => {
+                (get_bishop_moves(from, occupied & relevant_bishop_blockers(from))
+                    | get_rook_moves(from, occupied & relevant_rook_blockers(from)))
+                .get(&to)
+            }
+            Piece::WhiteKing | Piece::BlackKing => KING_MOVES_AT_SQUARE[from.usize()].get(&to),
+        }
+    }
+
+    fn is_pseudo_legal_pawn_move(&self, move_data: Move) -> bool {
+        let Move { from, to, flag } = move_data;
+        let white = self.white_to_move;
+        let occupied = self.occupied();
+        let forward = if white { from.up(1) } else { from.down(1) };
+
+        match flag {
+            Flag::EnPassant => {
+                self.game_state.en_passant_square == Some(to)
+                    && pawn_move_generator::attack_bit_board(from, white).get(&to)
+            }
+            Flag::PawnTwoUp => {
+                let starting_rank = if white { 1 } else { 6 };
+                let two_forward = if white { from.up(2) } else { from.down(2) };
+                from.rank() == starting_rank
+                    && to == two_forward
+                    && !occupied.get(&forward)
+                    && !occupied.get(&to)
+            }
+            Flag::Castle => false,
+            _ => {
+                let last_rank = if white { 7 } else { 0 };
+                if (to.rank() == last_rank) != flag.get_promotion_piece(white).is_some() {
+                    return false;
+                }
+                if to == forward {
+                    !occupied.get(&to)
+                } else {
+                    pawn_move_generator::attack_bit_board(from, white).get(&to)
+                        && self.enemy_occupancy().get(&to)
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::unreadable_literal)]
+    fn is_pseudo_legal_castle(&self, from: Square, to: Square) -> bool {
+        let white = self.white_to_move;
+        if from != Square::from_coords(if white { 0 } else { 7 }, 4) {
+            return false;
+        }
+
+        let castling_rights = self.game_state.castling_rights;
+        let shift = if white { 0 } else { 56 };
+        let (has_right, castle_block_mask) = if to == from.right(2) {
+            (
+                if white {
+                    castling_rights.get_white_king_side()
+                } else {
+                    castling_rights.get_black_king_side()
+                },
+                BitBoard::new(0b01100000 << shift),
+            )
+        } else if to == from.left(2) {
+            (
+                if white {
+                    castling_rights.get_white_queen_side()
+                } else {
+                    castling_rights.get_black_queen_side()
+                },
+                BitBoard::new(0b00001110 << shift),
+            )
+        } else {
+            return false;
+        };
+
+        has_right && !castle_block_mask.overlaps(&self.occupied())
+    }
+
+    /// Returns whether the move is legal in this position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{
+    ///     board::{Board, square::Square},
+    ///     move_generator::move_data::{Flag, Move},
+    /// };
+    ///
+    /// // The knight on e2 is pinned
+    /// let board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    /// let knight_move = Move {
+    ///     from: Square::from_notation("e2").unwrap(),
+    ///     to: Square::from_notation("c3").unwrap(),
+    ///     flag: Flag::None,
+    /// };
+    /// assert!(board.is_pseudo_legal(knight_move));
+    /// assert!(!board.is_legal(knight_move));
+    /// ```
+    #[must_use]
+    pub fn is_legal(&self, move_data: Move) -> bool {
+        if !self.is_pseudo_legal(move_data) {
+            return false;
+        }
+
+        let Move { from, to, flag } = move_data;
+        let white = self.white_to_move;
+
+        if flag == Flag::Castle {
+            // The king can not castle out of, through, or into check
+            let passing = if to.file() > from.file() {
+                from.right(1)
+            } else {
+                from.left(1)
+            };
+            return [from, passing, to]
+                .into_iter()
+                .all(|square| !self.is_square_attacked(square, !white));
+        }
+
+        let mut occupancy = self.occupied();
+        occupancy.unset(&from);
+        occupancy.set(&to);
+
+        let mut captured = to.bit_board();
+        if flag == Flag::EnPassant {
+            let captured_square = if white { to.down(1) } else { to.up(1) };
+            occupancy.unset(&captured_square);
+            captured.set(&captured_square);
+        }
+
+        let king = *self.get_bit_board(if white {
+            Piece::WhiteKing
+        } else {
+            Piece::BlackKing
+        });
+        let king_square = if king.get(&from) {
+            to
+        } else {
+            king.first_square()
+        };
+
+        (self.attackers_to(king_square, occupancy) & self.enemy_occupancy() & !captured).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, square::Square},
+        move_generator::{
+            MoveGenerator,
+            move_data::{Flag, Move},
+        },
+        tests::TEST_FENS,
+    };
+
+    #[test]
+    fn matches_move_generator() {
+        for (_, _, fen) in TEST_FENS {
+            let board = Board::from_fen(fen).unwrap();
+            let mut legal_moves = Vec::new();
+            MoveGenerator::new(&board).generate(|move_data| legal_moves.push(move_data), false);
+
+            // Try every possible move, including ones with nonsense flags
+            for from in 0..64 {
+                for to in 0..64 {
+                    for flag in Flag::ALL {
+                        let move_data = Move {
+                            from: Square::from_index(from),
+                            to: Square::from_index(to),
+                            flag,
+                        };
+                        let is_legal = legal_moves.contains(&move_data);
+                        assert_eq!(board.is_legal(move_data), is_legal, "{fen} {move_data}");
+                        if is_legal {
+                            assert!(board.is_pseudo_legal(move_data), "{fen} {move_data}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
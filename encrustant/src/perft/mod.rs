@@ -1,7 +1,20 @@
 //! Perft testing.
 
+mod stats;
+mod suite;
+mod table;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::{board::Board, move_generator::MoveGenerator, uci};
 
+use self::table::PerftTable;
+
+pub use self::{
+    stats::{PerftStats, detailed_perft_root},
+    suite::{EpdError, PerftSuiteEntry},
+};
+
 fn perft(board: &mut Board, depth: u16) -> u64 {
     #[cfg(test)]
     {
@@ -55,11 +68,118 @@ pub fn perft_root(board: &mut Board, depth: u16, log: fn(&str)) -> u64 {
     move_count
 }
 
+fn hashed_perft(board: &mut Board, depth: u16, table: Option<&PerftTable>) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if depth == 1 {
+        return board.legal_moves().len() as u64;
+    }
+    if let Some(table) = table
+        && let Some(move_count) = table.get(board.zobrist(), depth)
+    {
+        return move_count;
+    }
+
+    let mut move_count = 0;
+    for move_data in board.legal_moves() {
+        let old_state = board.make_move(&move_data);
+        move_count += hashed_perft(board, depth - 1, table);
+        board.unmake_move(&move_data, &old_state);
+    }
+
+    if let Some(table) = table {
+        table.insert(board.zobrist(), depth, move_count);
+    }
+    move_count
+}
+
+/// Starts a perft test that splits the root moves between `threads` threads,
+/// caching the counts of positions in a table of `hash_megabytes` megabytes shared by every thread.
+///
+/// A `hash_megabytes` of 0 disables the cache. The count of every root move is logged in move generation order
+/// once all of them are done.
+///
+/// # Panics
+///
+/// Will panic if one of the threads panics.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::{board::Board, perft::parallel_perft_root};
+///
+/// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// assert_eq!(parallel_perft_root(&board, 4, 2, 1, |_| {}), 197_281);
+/// ```
+#[must_use]
+pub fn parallel_perft_root(
+    board: &Board,
+    depth: u16,
+    threads: usize,
+    hash_megabytes: usize,
+    log: fn(&str),
+) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let root_moves = board.legal_moves();
+    let table = PerftTable::new(hash_megabytes);
+    let next_move = AtomicUsize::new(0);
+
+    // Each worker takes the next unsearched root move until there are none left
+    let worker = || {
+        let mut board = board.clone();
+        let mut counts = Vec::new();
+        loop {
+            let index = next_move.fetch_add(1, Ordering::Relaxed);
+            let Some(move_data) = root_moves.get(index) else {
+                return counts;
+            };
+            let old_state = board.make_move(move_data);
+            counts.push((index, hashed_perft(&mut board, depth - 1, table.as_ref())));
+            board.unmake_move(move_data, &old_state);
+        }
+    };
+
+    let mut move_counts = vec![0; root_moves.len()];
+    #[cfg(target_arch = "wasm32")]
+    let results = {
+        let _ = threads;
+        vec![worker()]
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    #[allow(clippy::needless_collect)]
+    let results: Vec<_> = std::thread::scope(|scope| {
+        // Every thread has to be spawned before any are joined
+        let handles: Vec<_> = (0..threads.clamp(1, root_moves.len().max(1)))
+            .map(|_| scope.spawn(worker))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    for (index, move_count) in results.into_iter().flatten() {
+        move_counts[index] = move_count;
+    }
+
+    for (move_data, move_count) in root_moves.iter().zip(&move_counts) {
+        log(&format!("{}: {move_count}", uci::encode_move(*move_data)));
+    }
+    move_counts.iter().sum()
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Instant;
 
-    use crate::{board::Board, perft::perft_root, tests::TEST_FENS};
+    use crate::{
+        board::Board,
+        perft::{parallel_perft_root, perft_root},
+        tests::TEST_FENS,
+    };
 
     fn debug_perft(board: &mut Board, depth: u16, expected_move_count: u64) {
         let start = Instant::now();
@@ -84,6 +204,12 @@ mod tests {
         let mut fens = TEST_FENS;
         fens.sort_by_key(|v| v.1);
         for (depth, expected_move_count, fen) in fens {
+            // Checking the incrementally updated state after every move is too slow for the largest counts
+            #[cfg(feature = "validate")]
+            if expected_move_count > 1_000_000 {
+                continue;
+            }
+
             let mut board = Board::from_fen(fen).unwrap();
 
             println!("{fen}");
@@ -91,4 +217,21 @@ mod tests {
             println!();
         }
     }
+
+    #[test]
+    fn parallel_perft_matches_expected() {
+        for (depth, expected_move_count, fen) in TEST_FENS {
+            if expected_move_count > 200_000 {
+                continue;
+            }
+            let board = Board::from_fen(fen).unwrap();
+            for (threads, hash_megabytes) in [(1, 0), (4, 0), (4, 1)] {
+                assert_eq!(
+                    parallel_perft_root(&board, depth, threads, hash_megabytes, |_| {}),
+                    expected_move_count,
+                    "{fen} with {threads} threads and {hash_megabytes} MB"
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,230 @@
+use core::{fmt, ops::AddAssign};
+
+use crate::{
+    board::Board,
+    move_generator::move_data::{Flag, Move},
+    uci,
+};
+
+/// Counts of the different kinds of leaf moves, laid out like the published perft tables.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PerftStats {
+    /// Leaf positions.
+    pub nodes: u64,
+
+    /// Leaf moves that captured a piece, including en passant.
+    pub captures: u64,
+
+    /// Leaf moves that captured en passant.
+    pub en_passants: u64,
+
+    /// Leaf moves that castled.
+    pub castles: u64,
+
+    /// Leaf moves that promoted a pawn.
+    pub promotions: u64,
+
+    /// Leaf moves that gave check.
+    pub checks: u64,
+
+    /// Leaf moves that gave check with only a piece other than the one that moved.
+    ///
+    /// Like the published tables, double checks are not counted here.
+    pub discovered_checks: u64,
+
+    /// Leaf moves that gave check with two pieces.
+    pub double_checks: u64,
+
+    /// Leaf moves that gave checkmate.
+    pub checkmates: u64,
+}
+
+impl AddAssign for PerftStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes += rhs.nodes;
+        self.captures += rhs.captures;
+        self.en_passants += rhs.en_passants;
+        self.castles += rhs.castles;
+        self.promotions += rhs.promotions;
+        self.checks += rhs.checks;
+        self.discovered_checks += rhs.discovered_checks;
+        self.double_checks += rhs.double_checks;
+        self.checkmates += rhs.checkmates;
+    }
+}
+
+impl fmt::Display for PerftStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Nodes: {}", self.nodes)?;
+        writeln!(f, "Captures: {}", self.captures)?;
+        writeln!(f, "En passants: {}", self.en_passants)?;
+        writeln!(f, "Castles: {}", self.castles)?;
+        writeln!(f, "Promotions: {}", self.promotions)?;
+        writeln!(f, "Checks: {}", self.checks)?;
+        writeln!(f, "Discovered checks: {}", self.discovered_checks)?;
+        writeln!(f, "Double checks: {}", self.double_checks)?;
+        write!(f, "Checkmates: {}", self.checkmates)
+    }
+}
+
+impl PerftStats {
+    /// Classifies a leaf move, which must be legal in the position.
+    fn leaf(board: &mut Board, move_data: Move) -> Self {
+        let Move { from, to, flag } = move_data;
+        let is_capture = flag == Flag::EnPassant || board.enemy_piece_at(to).is_some();
+
+        // A castling move gives check directly with the rook, which lands next to the king's starting square
+        let moved_piece_square = if flag == Flag::Castle {
+            if to.file() > from.file() {
+                from.right(1)
+            } else {
+                from.left(1)
+            }
+        } else {
+            to
+        };
+
+        let old_state = board.make_move(&move_data);
+        let checkers = board.checkers();
+        let checkmate = !checkers.is_empty() && board.legal_moves().is_empty();
+        board.unmake_move(&move_data, &old_state);
+
+        let mut discovered_checkers = checkers;
+        discovered_checkers.unset(&moved_piece_square);
+        Self {
+            nodes: 1,
+            captures: u64::from(is_capture),
+            en_passants: u64::from(flag == Flag::EnPassant),
+            castles: u64::from(flag == Flag::Castle),
+            promotions: u64::from(flag.get_promotion_piece(true).is_some()),
+            checks: u64::from(!checkers.is_empty()),
+            discovered_checks: u64::from(checkers.count() == 1 && !discovered_checkers.is_empty()),
+            double_checks: u64::from(checkers.count() > 1),
+            checkmates: u64::from(checkmate),
+        }
+    }
+}
+
+fn detailed_perft(board: &mut Board, depth: u16) -> PerftStats {
+    let mut stats = PerftStats::default();
+    for move_data in board.legal_moves() {
+        if depth == 1 {
+            stats += PerftStats::leaf(board, move_data);
+            continue;
+        }
+        let old_state = board.make_move(&move_data);
+        stats += detailed_perft(board, depth - 1);
+        board.unmake_move(&move_data, &old_state);
+    }
+    stats
+}
+
+/// Starts a perft test that also counts the kinds of leaf moves, logging the leaf nodes under every root move.
+///
+/// This is much slower than [`super::perft_root`], but narrows down which kind of move a wrong count comes from.
+///
+/// # Examples
+///
+/// ```
+/// use encrustant::{board::Board, perft::detailed_perft_root};
+///
+/// let mut board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+/// let stats = detailed_perft_root(&mut board, 3, |_| {});
+/// assert_eq!(stats.nodes, 8902);
+/// assert_eq!(stats.captures, 34);
+/// assert_eq!(stats.checks, 12);
+/// ```
+pub fn detailed_perft_root(board: &mut Board, depth: u16, log: fn(&str)) -> PerftStats {
+    if depth == 0 {
+        return PerftStats {
+            nodes: 1,
+            ..PerftStats::default()
+        };
+    }
+
+    let mut stats = PerftStats::default();
+    for move_data in board.legal_moves() {
+        let inner = if depth == 1 {
+            PerftStats::leaf(board, move_data)
+        } else {
+            let old_state = board.make_move(&move_data);
+            let inner = detailed_perft(board, depth - 1);
+            board.unmake_move(&move_data, &old_state);
+            inner
+        };
+        log(&format!("{}: {}", uci::encode_move(move_data), inner.nodes));
+        stats += inner;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::Board;
+
+    use super::{PerftStats, detailed_perft_root};
+
+    /// Depth, nodes, captures, en passants, castles, promotions, checks, discovered checks, double checks, checkmates.
+    type Row = (u16, [u64; 9]);
+
+    fn check_table(fen: &str, table: &[Row]) {
+        let mut board = Board::from_fen(fen).unwrap();
+        for &(
+            depth,
+            [
+                nodes,
+                captures,
+                en_passants,
+                castles,
+                promotions,
+                checks,
+                discovered_checks,
+                double_checks,
+                checkmates,
+            ],
+        ) in table
+        {
+            let expected = PerftStats {
+                nodes,
+                captures,
+                en_passants,
+                castles,
+                promotions,
+                checks,
+                discovered_checks,
+                double_checks,
+                checkmates,
+            };
+            assert_eq!(
+                detailed_perft_root(&mut board, depth, |_| {}),
+                expected,
+                "{fen} depth {depth}"
+            );
+        }
+    }
+
+    #[test]
+    fn start_position_table() {
+        check_table(
+            Board::START_POSITION_FEN,
+            &[
+                (1, [20, 0, 0, 0, 0, 0, 0, 0, 0]),
+                (2, [400, 0, 0, 0, 0, 0, 0, 0, 0]),
+                (3, [8902, 34, 0, 0, 0, 12, 0, 0, 0]),
+                (4, [197_281, 1576, 0, 0, 0, 469, 0, 0, 8]),
+            ],
+        );
+    }
+
+    #[test]
+    fn kiwipete_table() {
+        check_table(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[
+                (1, [48, 8, 0, 2, 0, 0, 0, 0, 0]),
+                (2, [2039, 351, 1, 91, 0, 3, 0, 0, 0]),
+                (3, [97862, 17102, 45, 3162, 0, 993, 0, 0, 1]),
+            ],
+        );
+    }
+}
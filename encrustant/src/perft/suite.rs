@@ -0,0 +1,135 @@
+use core::fmt;
+
+use crate::board::{Board, fen::FenError};
+
+/// Why a line of a perft suite could not be read.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EpdError {
+    /// The line has no `;D<depth> <nodes>` fields.
+    MissingDepths,
+
+    /// A field is not written as `D<depth> <nodes>`.
+    InvalidDepth,
+
+    /// The position is not a valid FEN.
+    InvalidFen(FenError),
+}
+
+impl fmt::Display for EpdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingDepths => write!(f, "no expected node counts"),
+            Self::InvalidDepth => write!(f, "expected node counts written as \"D<depth> <nodes>\""),
+            Self::InvalidFen(error) => write!(f, "invalid fen: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for EpdError {}
+
+/// A position of a perft suite and the node counts it should have at each depth.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PerftSuiteEntry {
+    /// The position, with move clocks added if the line left them out.
+    pub fen: String,
+
+    /// Depths and their expected node counts, in the order they were written.
+    pub expected: Vec<(u16, u64)>,
+}
+
+impl PerftSuiteEntry {
+    /// Reads a line written in the format used by the Ethereal and Stockfish perft suites,
+    /// such as `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ;D1 20 ;D2 400`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the position or any of the node counts are malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::perft::PerftSuiteEntry;
+    ///
+    /// let entry = PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - ;D1 15 ;D2 66").unwrap();
+    /// assert_eq!(entry.fen, "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    /// assert_eq!(entry.expected, [(1, 15), (2, 66)]);
+    /// ```
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        // A terminating `;` does not start another field
+        let line = line.trim_end();
+        let mut fields = line.strip_suffix(';').unwrap_or(line).split(';');
+        let position = fields.next().unwrap_or_default().trim();
+
+        // EPD positions usually leave out the move clocks
+        let fen = if position.split_whitespace().count() == 4 {
+            format!("{position} 0 1")
+        } else {
+            position.to_owned()
+        };
+        Board::from_fen(&fen).map_err(EpdError::InvalidFen)?;
+
+        let expected = fields
+            .map(|field| {
+                let (depth, nodes) = field
+                    .trim()
+                    .strip_prefix('D')
+                    .and_then(|field| field.split_once(char::is_whitespace))
+                    .ok_or(EpdError::InvalidDepth)?;
+                Ok((
+                    depth.parse().map_err(|_| EpdError::InvalidDepth)?,
+                    nodes.trim().parse().map_err(|_| EpdError::InvalidDepth)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if expected.is_empty() {
+            return Err(EpdError::MissingDepths);
+        }
+
+        Ok(Self { fen, expected })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::fen::FenParseErr, tests::TEST_FENS};
+
+    use super::{EpdError, PerftSuiteEntry};
+
+    #[test]
+    fn parses_test_fens() {
+        for (depth, expected_nodes, fen) in TEST_FENS {
+            let entry =
+                PerftSuiteEntry::parse(&format!("{fen} ;D{depth} {expected_nodes}")).unwrap();
+            assert_eq!(entry.fen, fen);
+            assert_eq!(entry.expected, [(depth, expected_nodes)]);
+        }
+    }
+
+    #[test]
+    fn accepts_terminating_semicolon() {
+        let entry = PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K2R w K - ;D1 15 ;D2 66;\n").unwrap();
+        assert_eq!(entry.expected, [(1, 15), (2, 66)]);
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        let fen = "4k3/8/8/8/8/8/8/4K3 w - -";
+        assert_eq!(PerftSuiteEntry::parse(fen), Err(EpdError::MissingDepths));
+        assert_eq!(
+            PerftSuiteEntry::parse(&format!("{fen} ;D1")),
+            Err(EpdError::InvalidDepth)
+        );
+        assert_eq!(
+            PerftSuiteEntry::parse(&format!("{fen} ;1 5")),
+            Err(EpdError::InvalidDepth)
+        );
+        assert_eq!(
+            PerftSuiteEntry::parse(&format!("{fen} ;D1 x")),
+            Err(EpdError::InvalidDepth)
+        );
+        assert!(matches!(
+            PerftSuiteEntry::parse("4k3/8/8/8/8/8/8/4K3 x - - ;D1 5"),
+            Err(EpdError::InvalidFen(error)) if error.kind == FenParseErr::InvalidSideToMove
+        ));
+    }
+}
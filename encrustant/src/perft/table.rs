@@ -0,0 +1,63 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::search::zobrist::Zobrist;
+
+/// One slot of the table.
+///
+/// The key is stored xored with the data, so an entry torn by two threads writing at once fails the key check
+/// instead of returning a count for the wrong position.
+struct Entry {
+    key: AtomicU64,
+    data: AtomicU64,
+}
+
+/// A lockless `(zobrist, depth) -> count` cache that can be shared between perft threads.
+pub struct PerftTable {
+    entries: Box<[Entry]>,
+}
+
+impl PerftTable {
+    /// Creates a table using about `megabytes` megabytes, or `None` if that is too small for a single entry.
+    pub fn new(megabytes: usize) -> Option<Self> {
+        let capacity = (megabytes * 1_000_000) / size_of::<Entry>();
+        if capacity == 0 {
+            return None;
+        }
+        Some(Self {
+            entries: (0..capacity)
+                .map(|_| Entry {
+                    key: AtomicU64::new(0),
+                    data: AtomicU64::new(0),
+                })
+                .collect(),
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn entry(&self, zobrist_key: Zobrist) -> &Entry {
+        &self.entries[zobrist_key.distribute(self.entries.len()) as usize]
+    }
+
+    /// Returns the stored count for the position at this depth.
+    pub fn get(&self, zobrist_key: Zobrist, depth: u16) -> Option<u64> {
+        let entry = self.entry(zobrist_key);
+        let data = entry.data.load(Ordering::Relaxed);
+        let key = entry.key.load(Ordering::Relaxed);
+        if key ^ data != zobrist_key.as_u64() || data & 0xFF != u64::from(depth) {
+            return None;
+        }
+        Some(data >> 8)
+    }
+
+    /// Stores the count for the position at this depth, replacing whatever was in its slot.
+    pub fn insert(&self, zobrist_key: Zobrist, depth: u16, count: u64) {
+        // Depths above 255 or counts above 2^56 would not fit, but neither can be searched in practice
+        debug_assert!(depth <= 0xFF && count < 1 << 56);
+        let data = count << 8 | u64::from(depth);
+        let entry = self.entry(zobrist_key);
+        entry
+            .key
+            .store(zobrist_key.as_u64() ^ data, Ordering::Relaxed);
+        entry.data.store(data, Ordering::Relaxed);
+    }
+}
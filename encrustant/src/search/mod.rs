@@ -4,6 +4,7 @@ pub mod encoded_move;
 mod move_ordering;
 pub mod pv;
 mod repetition_table;
+mod score_hash_table;
 pub mod search_params;
 pub mod time_manager;
 pub mod transposition;
@@ -15,11 +16,16 @@ use pv::Pv;
 use time_manager::TimeManager;
 use zobrist::Zobrist;
 
+use std::sync::Arc;
+
 use crate::{
     board::{Board, game_state::GameState, piece::Piece, square::Square},
     evaluation::{
-        Eval,
+        Eval, endgame,
         eval_data::{self, Score},
+        material::Material,
+        nnue::{Accumulators, Network},
+        pawn_structure::PawnStructure,
     },
     move_generator::{
         MoveGenerator,
@@ -30,8 +36,9 @@ use crate::{
 
 use self::{
     encoded_move::EncodedMove,
-    move_ordering::MoveOrderer,
+    move_ordering::{MoveOrderer, MovePicker},
     repetition_table::RepetitionTable,
+    score_hash_table::{ActivityHashTable, MaterialHashTable, PawnHashTable},
     transposition::{NodeType, NodeValue},
 };
 
@@ -95,15 +102,6 @@ fn history_gravity(current_value: i16, history_bonus: i32) -> i16 {
 pub struct SearchState {
     total_middle_game_score: Score,
     total_end_game_score: Score,
-
-    /// Position zobrist key.
-    pub position_zobrist_key: Zobrist,
-
-    /// Pawn zobrist key.
-    pub pawn_zobrist_key: Zobrist,
-
-    /// Minor piece (knight, bishop, king) zobrist key.
-    pub minor_piece_zobrist_key: Zobrist,
 }
 
 /// A combination of `GameState` and `SearchState`.
@@ -120,7 +118,8 @@ pub struct Search {
 
     transposition_table: Vec<Option<NodeValue>>,
 
-    quiet_history: Box<[[i16; 64 * 64]; 2]>,
+    /// Indexed by the side to move, then whether the piece is moving from a threatened square.
+    quiet_history: Box<[[[i16; 64 * 64]; 2]; 2]>,
     capture_history: Box<[[[i16; 6]; 64]; 12]>, // Inner table length is 6 because outer table already gives information about the piece colour
 
     continuation_history: ContinuationHistory,
@@ -135,6 +134,18 @@ pub struct Search {
 
     search_state: SearchState,
 
+    pawn_hash_table: PawnHashTable,
+
+    material_hash_table: MaterialHashTable,
+
+    activity_hash_table: ActivityHashTable,
+
+    /// Used instead of the piece-square tables when set.
+    network: Option<Arc<Network>>,
+
+    /// Accumulators of every position since the root, which are only updated when there is a network.
+    accumulators: Vec<Accumulators>,
+
     pub pv: Pv,
     pub highest_depth: Ply,
 
@@ -153,9 +164,11 @@ impl Search {
         #[cfg(feature = "spsa")] tunable: crate::search::search_params::Tunable,
     ) -> Self {
         let (total_middle_game_score, total_end_game_score) = Eval::raw_evaluate(&board);
-        let position_zobrist_key = Zobrist::compute(&board);
-        let pawn_zobrist_key = Zobrist::pawn_key(&board);
-        let minor_piece_zobrist_key = Zobrist::minor_piece_key(&board);
+        let network = Network::embedded();
+        let mut accumulators = Vec::with_capacity(256);
+        if let Some(network) = &network {
+            accumulators.push(network.refresh(&board));
+        }
 
         Self {
             board,
@@ -165,7 +178,7 @@ impl Search {
             transposition_table: vec![None; transposition_capacity],
 
             killer_moves: [EncodedMove::NONE; 64],
-            quiet_history: vec![[0; 64 * 64]; 2].try_into().unwrap(),
+            quiet_history: vec![[[0; 64 * 64]; 2]; 2].try_into().unwrap(),
             capture_history: vec![[[0; 6]; 64]; 12].try_into().unwrap(),
 
             continuation_history: ContinuationHistory::new(),
@@ -181,11 +194,15 @@ impl Search {
             search_state: SearchState {
                 total_middle_game_score,
                 total_end_game_score,
-                position_zobrist_key,
-                pawn_zobrist_key,
-                minor_piece_zobrist_key,
             },
 
+            pawn_hash_table: PawnHashTable::new(),
+            material_hash_table: MaterialHashTable::new(),
+            activity_hash_table: ActivityHashTable::new(),
+
+            network,
+            accumulators,
+
             pv: Pv::new(),
             highest_depth: 0,
 
@@ -200,33 +217,16 @@ impl Search {
     pub fn make_null_move(&mut self) -> ExtendedState {
         self.repetition_table.push(self.position_zobrist_key());
 
-        let old_search_state = self.search_state;
-        let old_game_state = self.board.game_state;
-
-        self.board.white_to_move = !self.board.white_to_move;
-        self.search_state.position_zobrist_key.flip_side_to_move();
-
-        self.board.game_state.half_move_clock = 0;
-
-        let en_passant_square = self.board.game_state.en_passant_square;
-        if let Some(en_passant_square) = en_passant_square {
-            self.search_state
-                .position_zobrist_key
-                .xor_en_passant(&en_passant_square);
-        }
-        self.board.game_state.en_passant_square = None;
-
         ExtendedState {
-            search_state: old_search_state,
-            game_state: old_game_state,
+            search_state: self.search_state,
+            game_state: self.board.make_null_move(),
         }
     }
 
     /// Unskips the turn
     pub fn unmake_null_move(&mut self, old_state: &ExtendedState) {
         self.search_state = old_state.search_state;
-        self.board.game_state = old_state.game_state;
-        self.board.white_to_move = !self.board.white_to_move;
+        self.board.unmake_null_move(&old_state.game_state);
         assert_eq!(self.repetition_table.pop(), self.position_zobrist_key());
     }
 
@@ -246,15 +246,24 @@ impl Search {
         self.board = board;
         self.repetition_table.clear();
 
-        let position_zobrist_key = Zobrist::compute(&self.board);
-        let pawn_zobrist_key = Zobrist::pawn_key(&self.board);
-        let minor_piece_zobrist_key = Zobrist::minor_piece_key(&self.board);
         let (total_middle_game_score, total_end_game_score) = Eval::raw_evaluate(&self.board);
         self.search_state.total_middle_game_score = total_middle_game_score;
         self.search_state.total_end_game_score = total_end_game_score;
-        self.search_state.position_zobrist_key = position_zobrist_key;
-        self.search_state.pawn_zobrist_key = pawn_zobrist_key;
-        self.search_state.minor_piece_zobrist_key = minor_piece_zobrist_key;
+
+        self.refresh_accumulators();
+    }
+
+    /// Evaluates with a neural network, or with the piece-square tables if `None`.
+    pub fn set_network(&mut self, network: Option<Arc<Network>>) {
+        self.network = network;
+        self.refresh_accumulators();
+    }
+
+    fn refresh_accumulators(&mut self) {
+        self.accumulators.clear();
+        if let Some(network) = &self.network {
+            self.accumulators.push(network.refresh(&self.board));
+        }
     }
 
     /// Another search.
@@ -265,10 +274,7 @@ impl Search {
         self.highest_depth = 0;
         self.killer_moves.fill(EncodedMove::NONE);
 
-        for value in &mut self.quiet_history[0] {
-            *value /= param!(self).history_decay;
-        }
-        for value in &mut self.quiet_history[1] {
+        for value in self.quiet_history.as_flattened_mut().as_flattened_mut() {
             *value /= param!(self).history_decay;
         }
     }
@@ -286,8 +292,10 @@ impl Search {
             }
         }
 
-        self.quiet_history[0].fill(0);
-        self.quiet_history[1].fill(0);
+        self.quiet_history
+            .as_flattened_mut()
+            .as_flattened_mut()
+            .fill(0);
 
         self.transposition_table.fill(None);
     }
@@ -301,8 +309,8 @@ impl Search {
             .minor_piece_zobrist_key()
             .modulo(MINOR_PIECE_CORRECTION_HISTORY_LENGTH as u64);
 
-        let mut best_score =
-            self.get_correction(self.static_evaluate(), pawn_index, minor_piece_index);
+        let static_eval = self.static_evaluate();
+        let mut best_score = self.get_correction(static_eval, pawn_index, minor_piece_index);
 
         if best_score > alpha {
             alpha = best_score;
@@ -381,6 +389,10 @@ impl Search {
             self.search_state.total_middle_game_score += i32::from(middle_game_value);
             self.search_state.total_end_game_score += i32::from(end_game_value);
         }
+
+        if let Some(network) = &self.network {
+            network.remove_piece(self.accumulators.last_mut().unwrap(), piece, square);
+        }
     }
     fn evaluation_add_piece(&mut self, piece: Piece, square: Square) {
         let is_white = match piece {
@@ -416,45 +428,95 @@ impl Search {
             self.search_state.total_middle_game_score -= i32::from(middle_game_value);
             self.search_state.total_end_game_score -= i32::from(end_game_value);
         }
+
+        if let Some(network) = &self.network {
+            network.add_piece(self.accumulators.last_mut().unwrap(), piece, square);
+        }
     }
 
     /// Returns the current position zobrist key
     #[must_use]
     pub const fn position_zobrist_key(&self) -> Zobrist {
-        self.search_state.position_zobrist_key
+        self.board.zobrist()
     }
 
     /// Returns the current pawn zobrist key
     #[must_use]
     pub const fn pawn_zobrist_key(&self) -> Zobrist {
-        self.search_state.pawn_zobrist_key
+        self.board.pawn_zobrist()
     }
 
     /// Returns the current minor piece (knight, bishop, king) zobrist key
     #[must_use]
     pub const fn minor_piece_zobrist_key(&self) -> Zobrist {
-        self.search_state.minor_piece_zobrist_key
+        self.board.minor_piece_zobrist()
     }
 
+    /// Returns the evaluation of the current position for the side to move, from the network if one is set and
+    /// otherwise from the incrementally updated piece-square tables and the other evaluation terms.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a network is set but there are no accumulators, or in debug builds if the incremental
+    /// evaluation differs from evaluating the board from scratch.
     #[must_use]
     pub fn static_evaluate(&self) -> Score {
+        if let Some(network) = &self.network {
+            let static_eval =
+                network.evaluate(self.accumulators.last().unwrap(), self.board.white_to_move);
+
+            #[cfg(debug_assertions)]
+            {
+                assert_eq!(static_eval, network.evaluate_board(&self.board));
+            };
+
+            return static_eval;
+        }
+
+        if let Some(static_eval) = endgame::evaluate(&self.board) {
+            return static_eval;
+        }
+
         let phases = eval_data::PHASE_WEIGHTS;
-        #[rustfmt::skip]
-        let total_phase = {
-            phases[0] * 16
-            + phases[1] * 4
-            + phases[2] * 4
-            + phases[3] * 4
-            + phases[4] * 2
-        };
         let phase = Eval::get_phase(&self.board, &phases);
 
-        let static_eval = Eval::calculate_score(
+        let board = &self.board;
+        let (pawn_middle_game_score, pawn_end_game_score) = self
+            .pawn_hash_table
+            .get_or_insert_with(board.pawn_zobrist(), || {
+                PawnStructure::new(board).score(&eval_data::PAWN_STRUCTURE)
+            });
+
+        let (activity_middle_game_score, activity_end_game_score) = self
+            .activity_hash_table
+            .get_or_insert_with(board.zobrist(), || {
+                Eval::piece_activity_with_parameters(
+                    &eval_data::MOBILITY,
+                    &eval_data::KING_SAFETY,
+                    &eval_data::THREATS,
+                    board,
+                )
+            });
+        let (material_middle_game_score, material_end_game_score) = self
+            .material_hash_table
+            .get_or_insert_with(board.material_zobrist(), || {
+                Material::new(board).score(&eval_data::MATERIAL)
+            });
+
+        let score = Eval::calculate_score(
             phase,
-            total_phase,
-            self.search_state.total_middle_game_score,
-            self.search_state.total_end_game_score,
-        ) * if self.board.white_to_move { 1 } else { -1 };
+            Eval::total_phase(&phases),
+            self.search_state.total_middle_game_score
+                + pawn_middle_game_score
+                + activity_middle_game_score
+                + material_middle_game_score,
+            self.search_state.total_end_game_score
+                + pawn_end_game_score
+                + activity_end_game_score
+                + material_end_game_score,
+        );
+        let static_eval =
+            endgame::scale(&self.board, score) * if self.board.white_to_move { 1 } else { -1 };
 
         #[cfg(debug_assertions)]
         {
@@ -464,132 +526,48 @@ impl Search {
         static_eval
     }
 
+    /// Checks that the incremental evaluation agrees with a search of the mirrored position.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the scores of `mirrored` are not exactly negated.
+    pub fn assert_mirrors(&self, mirrored: &Self) {
+        assert_eq!(
+            (
+                mirrored.search_state.total_middle_game_score,
+                mirrored.search_state.total_end_game_score
+            ),
+            (
+                -self.search_state.total_middle_game_score,
+                -self.search_state.total_end_game_score
+            ),
+            "{} is not evaluated as the opposite of {}",
+            self.board,
+            mirrored.board
+        );
+        assert_eq!(
+            mirrored.static_evaluate(),
+            self.static_evaluate(),
+            "{} is not evaluated as the opposite of {}",
+            self.board,
+            mirrored.board
+        );
+    }
+
     /// Makes a move and updates the evaluation.
     pub fn make_move<const PREFETCH: bool>(&mut self, move_data: &Move) -> ExtendedState {
-        debug_assert!(Zobrist::pawn_key(&self.board) == self.pawn_zobrist_key());
-        debug_assert!(Zobrist::minor_piece_key(&self.board) == self.minor_piece_zobrist_key());
-        debug_assert!(Zobrist::compute(&self.board) == self.position_zobrist_key());
-
         let search_state = self.search_state;
-
-        self.search_state.position_zobrist_key.flip_side_to_move();
+        if self.network.is_some() {
+            self.accumulators.push(*self.accumulators.last().unwrap());
+        }
 
         let piece = self.board.friendly_piece_at(move_data.from).unwrap();
-
-        self.search_state
-            .position_zobrist_key
-            .xor_piece(piece as usize, move_data.from.usize());
-        match piece {
-            Piece::WhitePawn | Piece::BlackPawn => {
-                self.search_state
-                    .pawn_zobrist_key
-                    .xor_piece(piece as usize, move_data.from.usize());
-            }
-
-            Piece::BlackKnight
-            | Piece::WhiteKnight
-            | Piece::BlackBishop
-            | Piece::WhiteBishop
-            | Piece::WhiteKing
-            | Piece::BlackKing => {
-                self.search_state
-                    .minor_piece_zobrist_key
-                    .xor_piece(piece as usize, move_data.from.usize());
-            }
-
-            _ => {}
-        }
         self.evaluation_remove_piece(piece, move_data.from);
 
-        let flag = move_data.flag;
-
-        self.search_state
-            .position_zobrist_key
-            .xor_castling_rights(&self.board.game_state.castling_rights);
-        {
-            let mut castling_rights = self.board.game_state.castling_rights;
-            if piece == Piece::WhiteKing {
-                castling_rights.unset_white_king_side();
-                castling_rights.unset_white_queen_side();
-            } else if piece == Piece::BlackKing {
-                castling_rights.unset_black_king_side();
-                castling_rights.unset_black_queen_side();
-            }
-            if move_data.from == Square::from_index(0) || move_data.to == Square::from_index(0) {
-                castling_rights.unset_white_queen_side();
-            }
-            if move_data.from == Square::from_index(7) || move_data.to == Square::from_index(7) {
-                castling_rights.unset_white_king_side();
-            }
-            if move_data.from == Square::from_index(56) || move_data.to == Square::from_index(56) {
-                castling_rights.unset_black_queen_side();
-            }
-            if move_data.from == Square::from_index(63) || move_data.to == Square::from_index(63) {
-                castling_rights.unset_black_king_side();
-            }
-            self.search_state
-                .position_zobrist_key
-                .xor_castling_rights(&castling_rights);
-        }
-
-        let promotion_piece = flag.get_promotion_piece(self.board.white_to_move);
-
-        if let Some(promotion_piece) = promotion_piece {
-            self.evaluation_add_piece(promotion_piece, move_data.to);
-            self.search_state
-                .position_zobrist_key
-                .xor_piece(promotion_piece as usize, move_data.to.usize());
-
-            if matches!(
-                promotion_piece,
-                Piece::BlackKnight | Piece::WhiteKnight | Piece::BlackBishop | Piece::WhiteBishop
-            ) {
-                self.search_state
-                    .minor_piece_zobrist_key
-                    .xor_piece(promotion_piece as usize, move_data.to.usize());
-            }
-        } else {
-            self.evaluation_add_piece(piece, move_data.to);
-            self.search_state
-                .position_zobrist_key
-                .xor_piece(piece as usize, move_data.to.usize());
-
-            match piece {
-                Piece::WhitePawn | Piece::BlackPawn => {
-                    self.search_state
-                        .pawn_zobrist_key
-                        .xor_piece(piece as usize, move_data.to.usize());
-                }
-
-                Piece::BlackKnight
-                | Piece::WhiteKnight
-                | Piece::BlackBishop
-                | Piece::WhiteBishop
-                | Piece::WhiteKing
-                | Piece::BlackKing => self
-                    .search_state
-                    .minor_piece_zobrist_key
-                    .xor_piece(piece as usize, move_data.to.usize()),
-
-                _ => {}
-            }
-        }
+        let promotion_piece = move_data.flag.get_promotion_piece(self.board.white_to_move);
+        self.evaluation_add_piece(promotion_piece.unwrap_or(piece), move_data.to);
 
-        if let Some(en_passant_square) = self.board.game_state.en_passant_square {
-            self.search_state
-                .position_zobrist_key
-                .xor_en_passant(&en_passant_square);
-        }
-        match flag {
-            Flag::PawnTwoUp => {
-                let en_passant_square =
-                    move_data
-                        .from
-                        .up(if self.board.white_to_move { 1 } else { -1 });
-                self.search_state
-                    .position_zobrist_key
-                    .xor_en_passant(&en_passant_square);
-            }
+        match move_data.flag {
             Flag::Castle => {
                 let is_king_side = move_data.to.file() == 6;
                 let rook_to_offset = if is_king_side { -1 } else { 1 };
@@ -600,18 +578,8 @@ impl Search {
                     Piece::BlackRook
                 };
 
-                let rook_from = move_data.to.offset(rook_from_offset);
-                let rook_to = move_data.to.offset(rook_to_offset);
-
-                self.evaluation_remove_piece(rook, rook_from);
-                self.evaluation_add_piece(rook, rook_to);
-
-                self.search_state
-                    .position_zobrist_key
-                    .xor_piece(rook as usize, rook_from.usize());
-                self.search_state
-                    .position_zobrist_key
-                    .xor_piece(rook as usize, rook_to.usize());
+                self.evaluation_remove_piece(rook, move_data.to.offset(rook_from_offset));
+                self.evaluation_add_piece(rook, move_data.to.offset(rook_to_offset));
             }
             Flag::EnPassant => {
                 let capture_position = self
@@ -627,44 +595,18 @@ impl Search {
                 };
 
                 self.evaluation_remove_piece(captured, capture_position);
-                self.search_state
-                    .position_zobrist_key
-                    .xor_piece(captured as usize, capture_position.usize());
-                self.search_state
-                    .pawn_zobrist_key
-                    .xor_piece(captured as usize, capture_position.usize());
             }
             _ => {
                 if let Some(captured) = self.board.enemy_piece_at(move_data.to) {
                     self.evaluation_remove_piece(captured, move_data.to);
-                    self.search_state
-                        .position_zobrist_key
-                        .xor_piece(captured as usize, move_data.to.usize());
-
-                    match captured {
-                        Piece::WhitePawn | Piece::BlackPawn => {
-                            self.search_state
-                                .pawn_zobrist_key
-                                .xor_piece(captured as usize, move_data.to.usize());
-                        }
-
-                        Piece::BlackKnight
-                        | Piece::WhiteKnight
-                        | Piece::BlackBishop
-                        | Piece::WhiteBishop
-                        | Piece::WhiteKing
-                        | Piece::BlackKing => {
-                            self.search_state
-                                .minor_piece_zobrist_key
-                                .xor_piece(captured as usize, move_data.to.usize());
-                        }
-
-                        _ => {}
-                    }
                 }
             }
         }
 
+        let game_state = self.board.make_move(move_data);
+        #[cfg(feature = "validate")]
+        self.validate_evaluation(*move_data, "making");
+
         if PREFETCH {
             #[cfg(target_feature = "sse")]
             {
@@ -692,12 +634,6 @@ impl Search {
             }
         }
 
-        let game_state = self.board.make_move(move_data);
-
-        debug_assert!(Zobrist::pawn_key(&self.board) == self.pawn_zobrist_key());
-        debug_assert!(Zobrist::minor_piece_key(&self.board) == self.minor_piece_zobrist_key());
-        debug_assert!(Zobrist::compute(&self.board) == self.position_zobrist_key());
-
         ExtendedState {
             game_state,
             search_state,
@@ -728,11 +664,36 @@ impl Search {
     /// Unmakes a move and updates the evaluation.
     pub fn unmake_move(&mut self, move_data: &Move, old_state: &ExtendedState) {
         self.search_state = old_state.search_state;
+        if self.network.is_some() {
+            self.accumulators.pop();
+        }
         self.board.unmake_move(move_data, &old_state.game_state);
+        #[cfg(feature = "validate")]
+        self.validate_evaluation(*move_data, "unmaking");
+    }
 
-        debug_assert!(Zobrist::compute(&self.board) == self.position_zobrist_key());
-        debug_assert!(Zobrist::pawn_key(&self.board) == self.pawn_zobrist_key());
-        debug_assert!(Zobrist::minor_piece_key(&self.board) == self.minor_piece_zobrist_key());
+    /// Checks that the incrementally updated evaluation matches one computed from scratch.
+    #[cfg(feature = "validate")]
+    fn validate_evaluation(&self, move_data: Move, action: &str) {
+        assert_eq!(
+            (
+                self.search_state.total_middle_game_score,
+                self.search_state.total_end_game_score
+            ),
+            Eval::raw_evaluate(&self.board),
+            "evaluation is wrong after {action} {} in {}",
+            crate::uci::encode_move(move_data),
+            self.board
+        );
+        if let Some(network) = &self.network {
+            assert_eq!(
+                self.accumulators.last(),
+                Some(&network.refresh(&self.board)),
+                "accumulators are wrong after {action} {} in {}",
+                crate::uci::encode_move(move_data),
+                self.board
+            );
+        }
     }
 
     fn update_continuation_history(
@@ -806,8 +767,14 @@ impl Search {
         // Get value from transposition table
         let mut saved = None;
         if let Some(entry) = self.transposition_table[zobrist_index] {
-            // Check if it's actually the same position
-            if entry.zobrist_key_32 == zobrist_key.lower_u32() {
+            // Check if it's actually the same position. Only part of the key is saved, so a different position
+            // could have the same key, in which case the saved move is unlikely to be playable.
+            if entry.zobrist_key_32 == zobrist_key.lower_u32()
+                && (entry.transposition_move.is_none()
+                    || self
+                        .board
+                        .is_pseudo_legal(entry.transposition_move.decode()))
+            {
                 let value = transposition::retrieve_mate_score(entry.value, ply_from_root);
 
                 // Check if the saved depth is as high as the depth now
@@ -937,10 +904,8 @@ impl Search {
             }
         }
 
-        // Get legal moves and their estimated value
-        let (mut move_guesses, move_count) = MoveOrderer::get_move_guesses(
-            self,
-            &move_generator,
+        // Legal moves are generated in stages, from most to least promising
+        let mut move_picker = MovePicker::new(
             hash_move,
             if USE_KILLER_MOVE && (ply_from_root as usize) < self.killer_moves.len() {
                 self.killer_moves[ply_from_root as usize]
@@ -950,32 +915,16 @@ impl Search {
             ply_from_root,
         );
 
-        if move_count == 0 {
-            // No moves
-            let score = if move_generator.is_in_check() {
-                // Checkmate
-                -IMMEDIATE_CHECKMATE_SCORE + Score::from(ply_from_root)
-            } else {
-                // Stalemate
-                0
-            };
-            return score;
-        }
-
         let mut node_type = NodeType::Alpha;
         let (mut best_move, mut best_score) = (EncodedMove::NONE, -Score::MAX);
 
         let mut quiets_evaluated: Vec<EncodedMove> = Vec::new();
         let mut captures_evaluated: Vec<EncodedMove> = Vec::new();
-        let mut index = 0;
-        loop {
-            let encoded_move_data = unsafe {
-                // SAFETY: `get_move_guesses` guarantees that `move_guesses[0..move_count]` are initialised.
-                // `index` can not be higher than `move_count`, due to the loop condition.
+        let mut move_count = 0;
+        while let Some(encoded_move_data) = move_picker.next(self, &move_generator) {
+            let index = move_count;
+            move_count += 1;
 
-                MoveOrderer::put_highest_guessed_move(&mut move_guesses, index, move_count)
-            }
-            .move_data;
             let move_data = encoded_move_data.decode();
 
             // This won't consider en passant
@@ -1111,8 +1060,10 @@ impl Search {
                                 - param!(self).quiet_history_subtraction_bonus)
                                 .min(MAX_HISTORY);
 
+                            let threatened_pieces = move_picker.threatened_pieces(&self.board);
                             let history = &mut self.quiet_history
                                 [usize::from(self.board.white_to_move)]
+                                [usize::from(threatened_pieces.get(&move_data.from))]
                                 [encoded_move_data.without_flag() as usize];
                             *history += history_gravity(*history, history_bonus);
 
@@ -1142,6 +1093,7 @@ impl Search {
 
                                 let history = &mut self.quiet_history
                                     [usize::from(self.board.white_to_move)]
+                                    [usize::from(threatened_pieces.get(&previous_quiet.from()))]
                                     [previous_quiet.without_flag() as usize];
                                 *history += history_gravity(*history, quiet_history_malus);
                             }
@@ -1195,11 +1147,18 @@ impl Search {
                 }
                 quiets_evaluated.push(encoded_move_data);
             }
+        }
 
-            index += 1;
-            if index == move_count {
-                break;
-            }
+        if move_count == 0 {
+            // No moves
+            let score = if move_generator.is_in_check() {
+                // Checkmate
+                -IMMEDIATE_CHECKMATE_SCORE + Score::from(ply_from_root)
+            } else {
+                // Stalemate
+                0
+            };
+            return score;
         }
 
         if !move_generator.is_in_check() {
@@ -1421,12 +1380,42 @@ impl Search {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
     use crate::{
         board::Board,
-        evaluation::{Eval, eval_data::Score},
+        evaluation::{Eval, eval_data::Score, nnue::Network},
+        move_generator::MoveGenerator,
         search::{Search, transposition::megabytes_to_capacity},
+        tests::test_positions,
     };
 
+    fn new_search(board: Board) -> Search {
+        Search::new(
+            board,
+            megabytes_to_capacity(1),
+            #[cfg(feature = "spsa")]
+            crate::search::search_params::DEFAULT_TUNABLES,
+        )
+    }
+
+    fn assert_mirrors_after_moves(search: &mut Search, mirrored: &mut Search, depth: u8) {
+        search.assert_mirrors(mirrored);
+        if depth == 0 {
+            return;
+        }
+
+        let mut moves = Vec::new();
+        MoveGenerator::new(search.board()).generate(|move_data| moves.push(move_data), false);
+        for move_data in moves {
+            let old_state = search.make_move::<false>(&move_data);
+            let mirrored_old_state = mirrored.make_move::<false>(&move_data.mirrored());
+            assert_mirrors_after_moves(search, mirrored, depth - 1);
+            mirrored.unmake_move(&move_data.mirrored(), &mirrored_old_state);
+            search.unmake_move(&move_data, &old_state);
+        }
+    }
+
     #[test]
     fn quiescence_search_works() {
         let board =
@@ -1446,4 +1435,39 @@ mod tests {
             Eval::evaluate(&quiet)
         );
     }
+
+    #[test]
+    fn incremental_evaluation_is_symmetric() {
+        for board in test_positions() {
+            let mut mirrored = new_search(board.mirrored());
+            let mut search = new_search(board);
+            assert_mirrors_after_moves(&mut search, &mut mirrored, 2);
+        }
+    }
+
+    fn evaluate_after_moves(search: &mut Search, depth: u8) {
+        let _ = search.static_evaluate();
+        if depth == 0 {
+            return;
+        }
+
+        let mut moves = Vec::new();
+        MoveGenerator::new(search.board()).generate(|move_data| moves.push(move_data), false);
+        for move_data in moves {
+            let old_state = search.make_move::<false>(&move_data);
+            evaluate_after_moves(search, depth - 1);
+            search.unmake_move(&move_data, &old_state);
+        }
+    }
+
+    #[test]
+    fn incremental_network_evaluation_matches_refresh() {
+        // Static evaluation checks the accumulators against a refresh in debug builds
+        let network = Arc::new(Network::random(4));
+        for board in test_positions() {
+            let mut search = new_search(board);
+            search.set_network(Some(Arc::clone(&network)));
+            evaluate_after_moves(&mut search, 2);
+        }
+    }
 }
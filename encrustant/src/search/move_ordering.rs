@@ -1,10 +1,12 @@
 use core::mem::MaybeUninit;
 
 use crate::{
-    evaluation::eval_data::Score,
+    board::{Board, bit_board::BitBoard},
+    evaluation::{eval_data::Score, threats::Threats},
     move_generator::{
         MoveGenerator,
         move_data::{Flag, Move},
+        move_list::MAX_LEGAL_MOVES,
     },
     search::Ply,
 };
@@ -112,12 +114,12 @@ pub struct MoveGuess {
     pub move_data: EncodedMove,
 }
 
-const MAX_LEGAL_MOVES: usize = 218;
 const MAX_CAPTURES: usize = 74;
 
-const HASH_MOVE_BONUS: MoveGuessNum = MoveGuessNum::MAX;
+/// Captures that lose more material than this in the static exchange are tried after quiet moves.
+const GOOD_CAPTURE_THRESHOLD: i32 = -100;
+
 const CAPTURE_BONUS: MoveGuessNum = 50_000_000;
-const KILLER_MOVE_BONUS: MoveGuessNum = 40_000_000;
 const QUEEN_PROMOTION_BONUS: MoveGuessNum = 30_000_000;
 const KNIGHT_PROMOTION_BONUS: MoveGuessNum = 20_000_000;
 const ROOK_PROMOTION_BONUS: MoveGuessNum = 0;
@@ -142,14 +144,21 @@ const CAPTURING_SCORE: [i32; 12] = {
 
 pub struct MoveOrderer;
 impl MoveOrderer {
-    fn guess_move_value(search: &Search, move_data: Move, ply_from_root: Ply) -> MoveGuessNum {
+    fn guess_move_value(
+        search: &Search,
+        move_data: Move,
+        ply_from_root: Ply,
+        threatened_pieces: BitBoard,
+    ) -> MoveGuessNum {
         let moving_from = move_data.from;
         let moving_to = move_data.to;
+        let from_threatened = threatened_pieces.get(&moving_from);
 
         match move_data.flag {
             Flag::EnPassant | Flag::Castle | Flag::PawnTwoUp => {
                 return MoveGuessNum::from(
                     search.quiet_history[usize::from(search.board.white_to_move)]
+                        [usize::from(from_threatened)]
                         [moving_from.usize() + moving_to.usize() * 64],
                 );
             }
@@ -183,7 +192,7 @@ impl MoveOrderer {
         } else {
             score += MoveGuessNum::from(
                 search.quiet_history[usize::from(search.board.white_to_move)]
-                    [moving_from.usize() + moving_to.usize() * 64],
+                    [usize::from(from_threatened)][moving_from.usize() + moving_to.usize() * 64],
             );
 
             if ply_from_root != 0 {
@@ -310,53 +319,225 @@ impl MoveOrderer {
         let mut move_guesses = [MaybeUninit::uninit(); MAX_CAPTURES];
 
         let mut index = 0;
-        move_generator.generate(
-            &mut |move_data| {
-                let encoded = EncodedMove::new(move_data);
-                move_guesses[index].write(MoveGuess {
-                    move_data: encoded,
-                    guess: Self::guess_capture_value(search, move_data),
-                });
-                index += 1;
-            },
-            true,
-        );
+        move_generator.generate_captures(&mut |move_data| {
+            let encoded = EncodedMove::new(move_data);
+            move_guesses[index].write(MoveGuess {
+                move_data: encoded,
+                guess: Self::guess_capture_value(search, move_data),
+            });
+            index += 1;
+        });
 
         (move_guesses, index)
     }
+}
 
-    pub fn get_move_guesses(
-        search: &Search,
-        move_generator: &MoveGenerator,
-        hash_move: EncodedMove,
-        killer_move: EncodedMove,
-        ply_from_root: Ply,
-    ) -> ([MaybeUninit<MoveGuess>; MAX_LEGAL_MOVES], usize) {
-        let mut move_guesses = [MaybeUninit::uninit(); MAX_LEGAL_MOVES];
+/// The stages of [`MovePicker`], in the order that they happen.
+///
+/// When in check, the hash move is followed by the evasion stages instead of the capture and quiet stages.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    HashMove,
+    GenerateCaptures,
+    GoodCaptures,
+    KillerMove,
+    GenerateQuiets,
+    Quiets,
+    BadCaptures,
+    GenerateEvasions,
+    Evasions,
+    Done,
+}
 
-        let mut index = 0;
-        move_generator.generate(
-            &mut |move_data| {
-                let encoded = EncodedMove::new(move_data);
-
-                let guess = if encoded == hash_move {
-                    HASH_MOVE_BONUS
-                } else if encoded == killer_move {
-                    KILLER_MOVE_BONUS
-                } else {
-                    Self::guess_move_value(search, move_data, ply_from_root)
-                };
-
-                move_guesses[index].write(MoveGuess {
-                    move_data: encoded,
-                    guess,
-                });
-                index += 1;
-            },
-            false,
-        );
+/// Returns moves from most to least promising, only generating moves when they are needed.
+///
+/// If the hash move causes a cutoff, no moves are generated at all.
+pub struct MovePicker {
+    stage: Stage,
 
-        (move_guesses, index)
+    hash_move: EncodedMove,
+    killer_move: EncodedMove,
+    ply_from_root: Ply,
+
+    /// Found the first time that a move is ordered or rewarded by the quiet history.
+    threatened_pieces: Option<BitBoard>,
+
+    move_guesses: [MaybeUninit<MoveGuess>; MAX_LEGAL_MOVES],
+    index: usize,
+    move_count: usize,
+
+    bad_captures: [MaybeUninit<EncodedMove>; MAX_CAPTURES],
+    bad_capture_index: usize,
+    bad_capture_count: usize,
+}
+
+impl MovePicker {
+    /// Creates a move picker for the current position. The hash move and killer move are checked before they are
+    /// returned.
+    pub const fn new(hash_move: EncodedMove, killer_move: EncodedMove, ply_from_root: Ply) -> Self {
+        Self {
+            stage: Stage::HashMove,
+            hash_move,
+            killer_move,
+            ply_from_root,
+            threatened_pieces: None,
+            move_guesses: [MaybeUninit::uninit(); MAX_LEGAL_MOVES],
+            index: 0,
+            move_count: 0,
+            bad_captures: [MaybeUninit::uninit(); MAX_CAPTURES],
+            bad_capture_index: 0,
+            bad_capture_count: 0,
+        }
+    }
+
+    /// Pieces of the side to move that are attacked by a less valuable piece or are hanging, which index the
+    /// quiet history.
+    pub fn threatened_pieces(&mut self, board: &Board) -> BitBoard {
+        *self
+            .threatened_pieces
+            .get_or_insert_with(|| Threats::threatened_pieces(board, board.white_to_move))
+    }
+
+    /// Generates and guesses the values of the moves for `stage`, which is one of the generate stages.
+    fn generate(&mut self, search: &Search, move_generator: &MoveGenerator, stage: Stage) {
+        // Of the captures, only en passant is ordered by the quiet history
+        let threatened_pieces = if stage == Stage::GenerateCaptures
+            && search.board.game_state.en_passant_square.is_none()
+        {
+            BitBoard::EMPTY
+        } else {
+            self.threatened_pieces(&search.board)
+        };
+
+        let mut move_count = 0;
+        let add_move = |move_data| {
+            self.move_guesses[move_count].write(MoveGuess {
+                move_data: EncodedMove::new(move_data),
+                guess: MoveOrderer::guess_move_value(
+                    search,
+                    move_data,
+                    self.ply_from_root,
+                    threatened_pieces,
+                ),
+            });
+            move_count += 1;
+        };
+        match stage {
+            Stage::GenerateCaptures => move_generator.generate_captures(add_move),
+            Stage::GenerateQuiets => move_generator.generate_quiets(add_move),
+            Stage::GenerateEvasions => move_generator.generate_evasions(add_move),
+            _ => unreachable!(),
+        }
+        self.index = 0;
+        self.move_count = move_count;
+    }
+
+    /// Returns the next highest guessed move that has not already been returned.
+    fn next_guessed(&mut self) -> Option<MoveGuess> {
+        if self.index == self.move_count {
+            return None;
+        }
+        let move_guess = unsafe {
+            // SAFETY: `generate` initialises `move_guesses[0..move_count]`, and `index` is lower than `move_count`.
+
+            MoveOrderer::put_highest_guessed_move(
+                &mut self.move_guesses,
+                self.index,
+                self.move_count,
+            )
+        };
+        self.index += 1;
+        Some(move_guess)
+    }
+
+    /// Returns the next legal move to search, or `None` if every move has been returned.
+    pub fn next(&mut self, search: &Search, move_generator: &MoveGenerator) -> Option<EncodedMove> {
+        loop {
+            match self.stage {
+                Stage::HashMove => {
+                    self.stage = if move_generator.is_in_check() {
+                        Stage::GenerateEvasions
+                    } else {
+                        Stage::GenerateCaptures
+                    };
+                    if !self.hash_move.is_none() && search.board.is_legal(self.hash_move.decode()) {
+                        return Some(self.hash_move);
+                    }
+                }
+                Stage::GenerateCaptures => {
+                    self.generate(search, move_generator, Stage::GenerateCaptures);
+                    self.stage = Stage::GoodCaptures;
+                }
+                Stage::GoodCaptures => {
+                    while let Some(MoveGuess { move_data, .. }) = self.next_guessed() {
+                        if move_data == self.hash_move {
+                            continue;
+                        }
+                        if !search
+                            .board
+                            .static_exchange_at_least(move_data.decode(), GOOD_CAPTURE_THRESHOLD)
+                        {
+                            // Losing captures are tried after the quiet moves
+                            self.bad_captures[self.bad_capture_count].write(move_data);
+                            self.bad_capture_count += 1;
+                            continue;
+                        }
+                        return Some(move_data);
+                    }
+                    self.stage = Stage::KillerMove;
+                }
+                Stage::KillerMove => {
+                    self.stage = Stage::GenerateQuiets;
+                    let killer_move = self.killer_move.decode();
+                    if !self.killer_move.is_none()
+                        && self.killer_move != self.hash_move
+                        && killer_move.flag != Flag::EnPassant
+                        && !move_generator.enemy_piece_bit_board().get(&killer_move.to)
+                        && search.board.is_legal(killer_move)
+                    {
+                        return Some(self.killer_move);
+                    }
+                }
+                Stage::GenerateQuiets => {
+                    self.generate(search, move_generator, Stage::GenerateQuiets);
+                    self.stage = Stage::Quiets;
+                }
+                Stage::Quiets => {
+                    while let Some(MoveGuess { move_data, .. }) = self.next_guessed() {
+                        if move_data != self.hash_move && move_data != self.killer_move {
+                            return Some(move_data);
+                        }
+                    }
+                    self.stage = Stage::BadCaptures;
+                }
+                Stage::BadCaptures => {
+                    if self.bad_capture_index == self.bad_capture_count {
+                        self.stage = Stage::Done;
+                        continue;
+                    }
+                    let move_data = unsafe {
+                        // SAFETY: `bad_captures[0..bad_capture_count]` are initialised in the good captures stage.
+
+                        self.bad_captures[self.bad_capture_index].assume_init()
+                    };
+                    self.bad_capture_index += 1;
+                    return Some(move_data);
+                }
+                Stage::GenerateEvasions => {
+                    self.generate(search, move_generator, Stage::GenerateEvasions);
+                    self.stage = Stage::Evasions;
+                }
+                Stage::Evasions => {
+                    while let Some(MoveGuess { move_data, .. }) = self.next_guessed() {
+                        if move_data != self.hash_move {
+                            return Some(move_data);
+                        }
+                    }
+                    self.stage = Stage::Done;
+                }
+                Stage::Done => return None,
+            }
+        }
     }
 }
 
@@ -369,9 +550,10 @@ mod tests {
             move_data::{Flag, Move},
         },
         search::{
-            Search, encoded_move::EncodedMove, move_ordering::MoveOrderer,
+            Search, encoded_move::EncodedMove, move_ordering::MovePicker,
             transposition::megabytes_to_capacity,
         },
+        tests::TEST_FENS,
     };
 
     #[test]
@@ -379,37 +561,82 @@ mod tests {
         let board = Board::from_fen("8/P6p/6r1/1q1n4/2P3R1/8/2K2k2/8 w - - 0 1").unwrap();
         let move_generator = MoveGenerator::new(&board);
 
-        let (mut move_guesses, move_count) = MoveOrderer::get_move_guesses(
-            &Search::new(
-                board,
-                megabytes_to_capacity(8),
-                #[cfg(feature = "spsa")]
-                crate::search::search_params::DEFAULT_TUNABLES,
-            ),
-            &move_generator,
-            EncodedMove::NONE,
-            EncodedMove::NONE,
-            0,
+        let search = Search::new(
+            board,
+            megabytes_to_capacity(8),
+            #[cfg(feature = "spsa")]
+            crate::search::search_params::DEFAULT_TUNABLES,
         );
+        let mut move_picker = MovePicker::new(EncodedMove::NONE, EncodedMove::NONE, 0);
 
-        let mut index = 0;
-        let mut next_move = || {
-            let move_guess = unsafe {
-                MoveOrderer::put_highest_guessed_move(&mut move_guesses, index, move_count)
-            };
-            println!("{index} {} {}", move_guess.move_data, move_guess.guess);
-            index += 1;
-            (move_guess.move_data, index != move_count)
-        };
+        let mut moves = Vec::new();
+        while let Some(move_data) = move_picker.next(&search, &move_generator) {
+            println!("{} {move_data}", moves.len());
+            moves.push(move_data);
+        }
+        let mut legal_moves = Vec::new();
+        move_generator.generate(|move_data| legal_moves.push(move_data), false);
+        assert_eq!(moves.len(), legal_moves.len());
 
         assert!(
-            next_move().0.decode()
+            moves[0].decode()
                 == Move {
                     from: Square::from_notation("c4").unwrap(),
                     to: Square::from_notation("b5").unwrap(),
                     flag: Flag::None
                 }
         );
-        while next_move().1 {}
+    }
+
+    #[test]
+    fn picks_every_move_once() {
+        // Include the positions in check after a move, which use the evasion stages
+        let mut positions = Vec::new();
+        for (_, _, fen) in TEST_FENS {
+            let mut board = Board::from_fen(fen).unwrap();
+            positions.push(fen.to_owned());
+            for move_data in board.legal_moves() {
+                let old_state = board.make_move(&move_data);
+                if board.checkers().is_not_empty() {
+                    positions.push(board.to_fen());
+                }
+                board.unmake_move(&move_data, &old_state);
+            }
+        }
+
+        for fen in &positions {
+            let board = Board::from_fen(fen).unwrap();
+            let move_generator = MoveGenerator::new(&board);
+            let mut legal_moves = Vec::new();
+            move_generator.generate(
+                |move_data| legal_moves.push(EncodedMove::new(move_data)),
+                false,
+            );
+
+            let search = Search::new(
+                board,
+                megabytes_to_capacity(1),
+                #[cfg(feature = "spsa")]
+                crate::search::search_params::DEFAULT_TUNABLES,
+            );
+
+            // Use legal moves as the hash and killer moves, as well as a move that is not legal here
+            let not_legal = EncodedMove::new(Move {
+                from: Square::from_notation("a1").unwrap(),
+                to: Square::from_notation("h8").unwrap(),
+                flag: Flag::None,
+            });
+            let hash_move = legal_moves.first().copied().unwrap_or(not_legal);
+            for killer_move in [legal_moves.last().copied().unwrap_or(not_legal), not_legal] {
+                let mut move_picker = MovePicker::new(hash_move, killer_move, 1);
+                let mut moves = Vec::new();
+                while let Some(move_data) = move_picker.next(&search, &move_generator) {
+                    assert!(!moves.contains(&move_data), "{fen} {move_data}");
+                    assert!(legal_moves.contains(&move_data), "{fen} {move_data}");
+                    moves.push(move_data);
+                }
+                assert_eq!(moves.len(), legal_moves.len(), "{fen}");
+            }
+        }
     }
 }
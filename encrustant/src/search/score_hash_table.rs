@@ -0,0 +1,84 @@
+use core::cell::Cell;
+
+use crate::evaluation::eval_data::Score;
+
+use super::zobrist::Zobrist;
+
+#[derive(Clone, Copy)]
+struct ScoreHashEntry {
+    key: Zobrist,
+    score: (Score, Score),
+}
+
+/// Caches middlegame and endgame scores of part of the evaluation by a zobrist key, keeping one entry for
+/// each of `LENGTH` indices.
+///
+/// Entries are cells so that evaluating a position does not need a mutable search.
+pub struct ScoreHashTable<const LENGTH: usize> {
+    entries: Box<[Cell<Option<ScoreHashEntry>>]>,
+}
+
+/// Caches the pawn structure score, which only changes when a pawn moves or is captured. 16384 entries is
+/// 384 KiB.
+pub type PawnHashTable = ScoreHashTable<16384>;
+
+/// Caches the bishop pair and imbalance scores, which only change when a piece is captured or promoted. 4096
+/// entries is 96 KiB.
+pub type MaterialHashTable = ScoreHashTable<4096>;
+
+/// Caches the mobility, king safety and threat scores of positions. 65536 entries is 1.5 MiB.
+pub type ActivityHashTable = ScoreHashTable<65536>;
+
+impl<const LENGTH: usize> ScoreHashTable<LENGTH> {
+    pub fn new() -> Self {
+        Self {
+            entries: (0..LENGTH).map(|_| Cell::new(None)).collect(),
+        }
+    }
+
+    /// Returns the cached score with `key`, calculating and storing it if it was not found.
+    #[allow(clippy::cast_possible_truncation)] // The index is less than the table length
+    pub fn get_or_insert_with(
+        &self,
+        key: Zobrist,
+        calculate: impl FnOnce() -> (Score, Score),
+    ) -> (Score, Score) {
+        let entry = &self.entries[key.modulo(LENGTH as u64) as usize];
+        if let Some(entry) = entry.get()
+            && entry.key == key
+        {
+            return entry.score;
+        }
+
+        let score = calculate();
+        entry.set(Some(ScoreHashEntry { key, score }));
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, search::zobrist::Zobrist};
+
+    use super::PawnHashTable;
+
+    #[test]
+    fn returns_cached_score() {
+        let board = Board::from_fen("4k3/8/2p5/8/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let key = Zobrist::pawn_key(&board);
+
+        let pawn_hash_table = PawnHashTable::new();
+        assert_eq!(pawn_hash_table.get_or_insert_with(key, || (1, 2)), (1, 2));
+        assert_eq!(
+            pawn_hash_table.get_or_insert_with(key, || unreachable!()),
+            (1, 2)
+        );
+
+        let other_key =
+            Zobrist::pawn_key(&Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+        assert_eq!(
+            pawn_hash_table.get_or_insert_with(other_key, || (3, 4)),
+            (3, 4)
+        );
+    }
+}
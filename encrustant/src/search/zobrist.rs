@@ -53,6 +53,12 @@ impl Zobrist {
         self.0 ^= ZOBRIST_RANDOMS.side_to_move;
     }
 
+    /// Returns the whole hash.
+    #[must_use]
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
     /// Take lower 32 bits from hash.
     #[must_use]
     pub const fn lower_u32(&self) -> u32 {
@@ -76,10 +82,10 @@ impl Zobrist {
     pub fn compute(board: &Board) -> Self {
         let mut key = Self::EMPTY;
 
-        for (piece, bit_board) in board.bit_boards.iter().enumerate() {
-            let mut bit_board = *bit_board;
+        for piece in Piece::ALL_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
             consume_bit_board!(bit_board, square {
-                key.xor_piece(piece, square.usize());
+                key.xor_piece(piece as usize, square.usize());
             });
         }
 
@@ -134,4 +140,44 @@ impl Zobrist {
 
         key
     }
+
+    /// Computes the material key, which only depends on the number of each piece.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    /// use encrustant::search::zobrist::Zobrist;
+    ///
+    /// let board = Board::from_fen("4k3/8/8/8/8/8/8/2B1KN2 w - - 0 1").unwrap();
+    /// let same_material = Board::from_fen("4k3/8/8/5N2/8/5B2/8/4K3 b - - 0 1").unwrap();
+    /// assert_eq!(Zobrist::material_key(&board), Zobrist::material_key(&same_material));
+    /// ```
+    #[must_use]
+    pub fn material_key(board: &Board) -> Self {
+        let mut piece_counts = [0; 12];
+        for piece in Piece::ALL_PIECES {
+            piece_counts[piece as usize] = board.get_bit_board(piece).count() as usize;
+        }
+        Self::material_key_from_counts(&piece_counts)
+    }
+
+    /// Computes the material key from the number of each piece, indexed by `Piece`.
+    #[must_use]
+    pub const fn material_key_from_counts(piece_counts: &[usize; 12]) -> Self {
+        let mut key = Self::EMPTY;
+
+        let mut piece_index = 0;
+        while piece_index < 12 {
+            // The random integer of the n-th square stands for the n-th piece
+            let mut count = 0;
+            while count < piece_counts[piece_index] {
+                key.xor_piece(piece_index, count);
+                count += 1;
+            }
+            piece_index += 1;
+        }
+
+        key
+    }
 }
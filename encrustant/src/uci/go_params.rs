@@ -79,7 +79,12 @@ impl SearchTime {
 
 pub enum SearchType {
     Normal(SearchTime),
-    Perft(u16),
+    Perft {
+        depth: u16,
+        threads: usize,
+        hash_megabytes: usize,
+        detailed: bool,
+    },
 }
 
 pub struct GoParameters {
@@ -108,7 +113,7 @@ impl GoParameters {
                             self.search_type = Some(SearchType::Normal(SearchTime::default()));
                         }
                         Some(SearchType::Normal(_)) => {}
-                        Some(SearchType::Perft(_)) => {
+                        Some(SearchType::Perft { .. }) => {
                             panic!(
                                 "search parameters specified when search type already determined"
                             )
@@ -185,11 +190,16 @@ impl GoParameters {
                         matches!(self.search_type, None | Some(SearchType::Normal(_))),
                         "perft specified when search type already determined"
                     );
-                    self.search_type = Some(SearchType::Perft(
-                        parse_number!().expect("Perft depth not specified"),
-                    ));
+                    self.search_type = Some(SearchType::Perft {
+                        depth: parse_number!().expect("Perft depth not specified"),
+                        threads: 1,
+                        hash_megabytes: 0,
+                        detailed: false,
+                    });
                 }
 
+                "threads" | "hash" | "stats" => self.parse_perft_option(label, args),
+
                 "searchmoves" => todo!(),
 
                 _ => panic!("Unknown parameter"),
@@ -197,6 +207,32 @@ impl GoParameters {
         }
     }
 
+    fn parse_perft_option(&mut self, label: &str, args: &mut SplitWhitespace) {
+        let Some(SearchType::Perft {
+            ref mut threads,
+            ref mut hash_megabytes,
+            ref mut detailed,
+            ..
+        }) = self.search_type
+        else {
+            panic!("{label} specified without perft")
+        };
+        if label == "stats" {
+            *detailed = true;
+            return;
+        }
+
+        let value = args
+            .next()
+            .and_then(|value| value.parse().ok())
+            .expect("Perft option value not specified");
+        if label == "threads" {
+            *threads = value;
+        } else {
+            *hash_megabytes = value;
+        }
+    }
+
     #[must_use]
     pub const fn search_type(self) -> Option<SearchType> {
         self.search_type
@@ -10,13 +10,14 @@ mod move_encoding;
 mod search_controller;
 
 use go_params::SearchType;
-pub use move_encoding::{decode_move, encode_move};
+pub use move_encoding::{UciMoveError, decode_move, encode_move};
 use search_controller::SearchController;
 
 use crate::{
     board::{Board, square::Square},
+    evaluation::{nnue::Network, trace::Trace},
     move_generator::move_data::Flag,
-    perft::perft_root,
+    perft::{detailed_perft_root, parallel_perft_root},
     search::transposition::megabytes_to_capacity,
     timer::Time,
 };
@@ -63,6 +64,12 @@ pub struct UCIProcessor {
     /// Maximum entry count of the transposition table.
     transposition_capacity: usize,
 
+    /// Network loaded from `EvalFile`, or the embedded one.
+    network: Option<Arc<Network>>,
+
+    /// Whether to evaluate with `network` instead of the piece-square tables.
+    use_nnue: bool,
+
     stopped: Bool,
 
     ponder_info: PonderInfo,
@@ -226,6 +233,8 @@ impl UCIProcessor {
                 is_pondering: false,
             },
             transposition_capacity,
+            network: Network::embedded(),
+            use_nnue: true,
             search_controller: None,
             #[cfg(feature = "spsa")]
             tunables: crate::search::search_params::DEFAULT_TUNABLES,
@@ -237,6 +246,28 @@ impl UCIProcessor {
             search_controller.set_transposition_capacity(transposition_capacity);
         }
     }
+
+    /// Returns the network to evaluate with, or `None` to use the piece-square tables.
+    fn evaluation_network(&self) -> Option<Arc<Network>> {
+        self.network.clone().filter(|_| self.use_nnue)
+    }
+    /// Loads the network at `path`, or goes back to the embedded network if there is no path.
+    fn load_network(&mut self, path: Option<&str>) {
+        match path.filter(|path| *path != "<empty>") {
+            None => self.network = Network::embedded(),
+            Some(path) => match Network::load(path) {
+                Ok(network) => self.network = Some(Arc::new(network)),
+                Err(error) => (self.out)(&format!("info string {path}: {error}")),
+            },
+        }
+        self.update_network();
+    }
+    fn update_network(&mut self) {
+        let network = self.evaluation_network();
+        if let Some(search_controller) = &mut self.search_controller {
+            search_controller.set_network(network);
+        }
+    }
 }
 
 impl UCIProcessor {
@@ -248,7 +279,9 @@ impl UCIProcessor {
         let mut options = format!(
             "option name Hash type spin default {default_hash} min {min_hash} max {max_hash}
 option name Ponder type check default false
-option name Threads type spin default 1 min 1 max 1"
+option name Threads type spin default 1 min 1 max 1
+option name EvalFile type string default <empty>
+option name UseNNUE type check default true"
         );
 
         #[cfg(feature = "spsa")]
@@ -391,6 +424,11 @@ uciok",
                 let ponder_allowed: bool = value.expect("Missing value").parse().unwrap();
                 self.ponder_info.ponder_allowed = ponder_allowed;
             }
+            "evalfile" => self.load_network(value),
+            "usennue" => {
+                self.use_nnue = value.expect("Missing value").parse().unwrap();
+                self.update_network();
+            }
 
             option_name => handle_option!(
                 option_name,
@@ -503,13 +541,24 @@ uciok",
         let mut board = Board::from_fen(self.fen.as_ref().unwrap()).unwrap();
 
         match parameters.search_type().unwrap() {
-            SearchType::Perft(depth) => {
+            SearchType::Perft {
+                depth,
+                threads,
+                hash_megabytes,
+                detailed,
+            } => {
                 for (from, to, promotion) in &self.moves {
                     board.make_move(&decode_move(&board, *from, *to, *promotion));
                 }
 
                 let search_start = Time::now();
-                let nodes = perft_root(&mut board, depth, self.out);
+                let nodes = if detailed {
+                    let stats = detailed_perft_root(&mut board, depth, self.out);
+                    (self.out)(&stats.to_string());
+                    stats.nodes
+                } else {
+                    parallel_perft_root(&board, depth, threads, hash_megabytes, self.out)
+                };
                 let time = search_start.milliseconds();
                 let nodes_per_second = if time == 0 { 0 } else { (nodes * 1000) / time };
                 (self.out)(&format!(
@@ -532,8 +581,11 @@ uciok",
                 }
 
                 if self.search_controller.is_none() {
-                    self.search_controller =
-                        Some(SearchController::new(self.out, self.transposition_capacity));
+                    self.search_controller = Some(SearchController::new(
+                        self.out,
+                        self.transposition_capacity,
+                        self.evaluation_network(),
+                    ));
                 }
                 let search_controller = self.search_controller.as_mut().unwrap();
                 search_controller.set_position(board, self.moves.clone());
@@ -554,6 +606,32 @@ uciok",
         }
     }
 
+    /// Outputs the hand-crafted evaluation of the current position term by term, and the network
+    /// evaluation if there is one.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no position has been set.
+    pub fn eval(&self) {
+        let mut board = Board::from_fen(self.fen.as_ref().unwrap()).unwrap();
+        for (from, to, promotion) in &self.moves {
+            board.make_move(&decode_move(&board, *from, *to, *promotion));
+        }
+
+        (self.out)(&Trace::new(&board).to_string());
+        if let Some(network) = self.evaluation_network() {
+            let evaluation = network.evaluate_board(&board);
+            (self.out)(&format!(
+                "NNUE evaluation: {} (white's perspective)",
+                if board.white_to_move {
+                    evaluation
+                } else {
+                    -evaluation
+                }
+            ));
+        }
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     /// Stop calculating as soon as possible.
     pub fn stop(&self) {
@@ -1,8 +1,9 @@
 use crate::{
-    board::{Board, piece::Piece, square::Square},
+    board::{Board, game_state::GameState, piece::Piece, square::Square},
     move_generator::move_data::{Flag, Move},
     search::encoded_move::EncodedMove,
 };
+use core::fmt;
 
 /// Encodes a move in uci notation.
 #[must_use]
@@ -50,3 +51,99 @@ pub fn decode_move(board: &Board, from: Square, to: Square, promotion: Flag) ->
 
     Move { from, to, flag }
 }
+
+/// Why a move in UCI notation could not be played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UciMoveError {
+    /// The move is not written as two squares followed by an optional promotion piece, such as `e7e8q`.
+    InvalidNotation,
+
+    /// The move is not legal in the position.
+    IllegalMove,
+}
+
+impl fmt::Display for UciMoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidNotation => write!(f, "invalid move notation"),
+            Self::IllegalMove => write!(f, "illegal move"),
+        }
+    }
+}
+
+impl std::error::Error for UciMoveError {}
+
+impl Board {
+    /// Finds the legal move written in UCI notation, such as `e2e4` or `e7e8q`.
+    ///
+    /// Unlike [`decode_move`], the move is checked against the legal moves in the position.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the notation is invalid or the move is not legal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::{board::Board, move_generator::move_data::Flag, uci::UciMoveError};
+    ///
+    /// let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// assert_eq!(board.parse_uci_move("e2e4").unwrap().flag, Flag::PawnTwoUp);
+    /// assert_eq!(board.parse_uci_move("e2e5").unwrap_err(), UciMoveError::IllegalMove);
+    /// assert_eq!(board.parse_uci_move("e2").unwrap_err(), UciMoveError::InvalidNotation);
+    /// ```
+    pub fn parse_uci_move(&self, uci_move: &str) -> Result<Move, UciMoveError> {
+        let square = |range| {
+            uci_move
+                .get(range)
+                .and_then(|notation| Square::from_notation(notation).ok())
+                .ok_or(UciMoveError::InvalidNotation)
+        };
+        let (from, to) = (square(0..2)?, square(2..4)?);
+        let promotion = match uci_move.get(4..) {
+            Some("") => Flag::None,
+            Some("q") => Flag::QueenPromotion,
+            Some("r") => Flag::RookPromotion,
+            Some("n") => Flag::KnightPromotion,
+            Some("b") => Flag::BishopPromotion,
+            _ => return Err(UciMoveError::InvalidNotation),
+        };
+
+        self.legal_moves_from(from)
+            .into_iter()
+            .find(|move_data| {
+                move_data.to == to
+                    && if promotion == Flag::None {
+                        move_data.flag.get_promotion_piece(true).is_none()
+                    } else {
+                        move_data.flag == promotion
+                    }
+            })
+            .ok_or(UciMoveError::IllegalMove)
+    }
+
+    /// Plays a move written in UCI notation if it is legal, returning the move and the state needed to unmake it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` and leave the position unchanged if the notation is invalid or the move is not legal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use encrustant::board::Board;
+    ///
+    /// let mut board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+    /// let (move_data, old_state) = board.try_play_uci("g1f3").unwrap();
+    /// assert!(!board.white_to_move);
+    /// assert!(board.try_play_uci("f3g5").is_err());
+    ///
+    /// board.unmake_move(&move_data, &old_state);
+    /// assert_eq!(board.to_fen(), Board::START_POSITION_FEN);
+    /// ```
+    pub fn try_play_uci(&mut self, uci_move: &str) -> Result<(Move, GameState), UciMoveError> {
+        let move_data = self.parse_uci_move(uci_move)?;
+        let old_state = self.make_move(&move_data);
+        Ok((move_data, old_state))
+    }
+}
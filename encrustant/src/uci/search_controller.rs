@@ -4,6 +4,7 @@ use std::sync::atomic::AtomicBool;
 
 use crate::board::Board;
 use crate::board::square::Square;
+use crate::evaluation::nnue::Network;
 use crate::move_generator::move_data::Flag;
 use crate::search::encoded_move::EncodedMove;
 use crate::search::pv::Pv;
@@ -58,6 +59,7 @@ fn search(
     board: &mut Option<Board>,
     moves: &mut Option<Vec<(Square, Square, Flag)>>,
     transposition_capacity: usize,
+    network: Option<&Arc<Network>>,
     search_time: SearchTime,
     stopped: Bool,
     ponder_info: PonderInfo,
@@ -68,12 +70,13 @@ fn search(
 
     let search = if cached_search.is_none() {
         // First time making search
-        let search = Search::new(
+        let mut search = Search::new(
             board.take().unwrap(),
             transposition_capacity,
             #[cfg(feature = "spsa")]
             tunables,
         );
+        search.set_network(network.cloned());
         *cached_search = Some(search);
         cached_search.as_mut().unwrap()
     } else {
@@ -180,6 +183,7 @@ mod search_controller {
 
     use crate::board::Board;
     use crate::board::square::Square;
+    use crate::evaluation::nnue::Network;
     use crate::move_generator::move_data::Flag;
 
     use crate::search::{Ply, Search};
@@ -199,16 +203,22 @@ mod search_controller {
             tunables: crate::search::search_params::Tunable,
         },
         SetTranspositionCapacity(usize),
+        SetNetwork(Option<Arc<Network>>),
         ClearCacheForNewGame,
     }
 
     pub struct SearchController(Sender<SearchCommand>);
     impl SearchController {
-        pub fn new(out: fn(&str), transposition_capacity: usize) -> Self {
+        pub fn new(
+            out: fn(&str),
+            transposition_capacity: usize,
+            network: Option<Arc<Network>>,
+        ) -> Self {
             let (sender, receiver) = mpsc::channel::<SearchCommand>();
             thread::spawn(move || {
                 let mut cached_search: Option<Search> = None;
                 let mut transposition_capacity = transposition_capacity;
+                let mut network = network;
                 let mut board = None;
                 let mut moves = None;
 
@@ -220,6 +230,12 @@ mod search_controller {
                                 search.resize_transposition_table(transposition_capacity);
                             }
                         }
+                        SearchCommand::SetNetwork(new_network) => {
+                            network = new_network;
+                            if let Some(search) = &mut cached_search {
+                                search.set_network(network.clone());
+                            }
+                        }
                         SearchCommand::SetPosition((new_board, new_moves)) => {
                             board = Some(new_board);
                             moves = Some(new_moves);
@@ -243,6 +259,7 @@ mod search_controller {
                             &mut board,
                             &mut moves,
                             transposition_capacity,
+                            network.as_ref(),
                             search_time,
                             stopped,
                             ponder_info,
@@ -287,6 +304,9 @@ mod search_controller {
                 ))
                 .unwrap();
         }
+        pub fn set_network(&self, network: Option<Arc<Network>>) {
+            self.0.send(SearchCommand::SetNetwork(network)).unwrap();
+        }
         pub fn clear_cache_for_new_game(&self) {
             self.0.send(SearchCommand::ClearCacheForNewGame).unwrap();
         }
@@ -295,8 +315,11 @@ mod search_controller {
 
 #[cfg(target_arch = "wasm32")]
 mod search_controller {
+    use std::sync::Arc;
+
     use crate::board::Board;
     use crate::board::square::Square;
+    use crate::evaluation::nnue::Network;
     use crate::move_generator::move_data::Flag;
     use crate::search::{Ply, Search};
     use crate::uci::PonderInfo;
@@ -310,15 +333,21 @@ mod search_controller {
         board: Option<Board>,
         moves: Option<Vec<(Square, Square, Flag)>>,
         transposition_capacity: usize,
+        network: Option<Arc<Network>>,
     }
     impl SearchController {
-        pub fn new(out: fn(&str), transposition_capacity: usize) -> Self {
+        pub fn new(
+            out: fn(&str),
+            transposition_capacity: usize,
+            network: Option<Arc<Network>>,
+        ) -> Self {
             Self {
                 out,
                 cached_search: None,
                 board: None,
                 moves: None,
                 transposition_capacity,
+                network,
             }
         }
         pub fn search(
@@ -335,6 +364,7 @@ mod search_controller {
                 &mut self.board,
                 &mut self.moves,
                 self.transposition_capacity,
+                self.network.as_ref(),
                 search_time,
                 stopped,
                 ponder_info,
@@ -353,6 +383,12 @@ mod search_controller {
                 search.resize_transposition_table(transposition_capacity);
             }
         }
+        pub fn set_network(&mut self, network: Option<Arc<Network>>) {
+            self.network = network;
+            if let Some(search) = &mut self.cached_search {
+                search.set_network(self.network.clone());
+            }
+        }
         pub fn clear_cache_for_new_game(&mut self) {
             if let Some(search) = &mut self.cached_search {
                 search.clear_cache_for_new_game();
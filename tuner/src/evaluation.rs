@@ -1,42 +1,37 @@
 use encrustant::board::Board;
-use encrustant::board::piece::Piece;
-use encrustant::consume_bit_board;
+use encrustant::evaluation::endgame::{self, NORMAL_SCALE_FACTOR};
+use encrustant::evaluation::material::Material;
 
-pub const PARAMETER_COUNT: usize = 384;
+use crate::features::Coefficients;
 
 pub struct DataPoint {
-    /// Indices of evaluation parameters it used
-    /// One for white and black (white = add, black = subtract from evaluation)
-    pub active: [Vec<u16>; 2],
+    /// Indices of evaluation parameters it used, with how many more times white used them than black
+    pub coefficients: Coefficients,
 
     /// Used to calculate game phase
     /// King not included
     pub piece_counts: [f64; 5],
 
+    /// Share of the score that is kept when white, then black, is ahead
+    pub scale_factors: [f64; 2],
+
     /// 0.0 -> black win;
     /// 0.5 -> draw;
     /// 1.0 -> white win;
     pub result: f64,
 }
 
+/// Number of pawns, knights, bishops, rooks and queens of both sides together
 pub fn get_piece_counts(board: &Board) -> [f64; 5] {
-    [
-        (*board.get_bit_board(Piece::WhitePawn) | *board.get_bit_board(Piece::BlackPawn))
-            .count()
-            .into(),
-        (*board.get_bit_board(Piece::WhiteKnight) | *board.get_bit_board(Piece::BlackKnight))
-            .count()
-            .into(),
-        (*board.get_bit_board(Piece::WhiteBishop) | *board.get_bit_board(Piece::BlackBishop))
-            .count()
-            .into(),
-        (*board.get_bit_board(Piece::WhiteRook) | *board.get_bit_board(Piece::BlackRook))
-            .count()
-            .into(),
-        (*board.get_bit_board(Piece::WhiteQueen) | *board.get_bit_board(Piece::BlackQueen))
-            .count()
-            .into(),
-    ]
+    let [white, black] = Material::piece_counts(board);
+    std::array::from_fn(|piece_type| f64::from(white[piece_type] + black[piece_type]))
+}
+
+/// Share of the score that `endgame::scale` keeps when white, then black, is ahead
+pub fn get_scale_factors(board: &Board) -> [f64; 2] {
+    [true, false].map(|strong_white| {
+        f64::from(endgame::scale_factor(board, strong_white)) / f64::from(NORMAL_SCALE_FACTOR)
+    })
 }
 
 pub fn get_total_phase(phase_weights: &[f64]) -> f64 {
@@ -49,31 +44,6 @@ pub fn get_total_phase(phase_weights: &[f64]) -> f64 {
     )
 }
 
-pub fn get_active(board: &Board) -> [Vec<u16>; 2] {
-    let mut white = Vec::new();
-    let mut black = Vec::new();
-
-    for piece in Piece::WHITE_PIECES {
-        let mut bit_board = *board.get_bit_board(piece);
-        consume_bit_board!(bit_board, square {
-            let square_index = square.flip().usize();
-            let piece_index = piece as usize;
-            white.push((piece_index * 64 + square_index).try_into().unwrap());
-        });
-    }
-
-    for piece in Piece::BLACK_PIECES {
-        let mut bit_board = *board.get_bit_board(piece);
-        consume_bit_board!(bit_board, square {
-            let square_index = square.usize();
-            let piece_index = piece as usize - 6;
-            black.push((piece_index * 64 + square_index).try_into().unwrap());
-        });
-    }
-
-    [white, black]
-}
-
 impl DataPoint {
     /// Returns in the range of 0..=1
     pub fn get_phase(&self, phase_weights: &[f64]) -> f64 {
@@ -88,19 +58,26 @@ impl DataPoint {
     }
 
     pub fn evaluate(&self, parameters: &[(f64, f64)], phase: f64) -> f64 {
-        let (mut mid_score, mut end_score) = (0.0, 0.0);
+        let (mid_score, end_score) = self.score(parameters);
+        let score = phase.mul_add(mid_score, (1.0 - phase) * end_score);
+        score * self.scale_factor(score)
+    }
 
-        for &used_index in &self.active[0] {
-            mid_score += parameters[usize::from(used_index)].0;
-            end_score += parameters[usize::from(used_index)].1;
-        }
+    /// Share of the tapered score that is kept, which depends on the side that is ahead like in
+    /// `endgame::scale`
+    pub fn scale_factor(&self, score: f64) -> f64 {
+        self.scale_factors[usize::from(score <= 0.0)]
+    }
 
-        for &used_index in &self.active[1] {
-            mid_score -= parameters[usize::from(used_index)].0;
-            end_score -= parameters[usize::from(used_index)].1;
+    /// Middlegame and endgame score from white's perspective
+    pub fn score(&self, parameters: &[(f64, f64)]) -> (f64, f64) {
+        let (mut mid_score, mut end_score) = (0.0, 0.0);
+        for &(index, coefficient) in &self.coefficients {
+            let parameter = parameters[usize::from(index)];
+            mid_score = f64::from(coefficient).mul_add(parameter.0, mid_score);
+            end_score = f64::from(coefficient).mul_add(parameter.1, end_score);
         }
-
-        phase.mul_add(mid_score, (1.0 - phase) * end_score)
+        (mid_score, end_score)
     }
 }
 
@@ -109,14 +86,14 @@ mod tests {
     use encrustant::{
         board::Board,
         evaluation::{
-            Eval,
-            eval_data::{PHASE_WEIGHTS, PIECE_SQUARE_TABLE},
+            Eval, endgame,
+            eval_data::{PAWN_STRUCTURE, PHASE_WEIGHTS},
         },
     };
 
-    use crate::PARAMETER_COUNT;
+    use crate::features::{FEATURES, all_parameters, extract};
 
-    use super::{DataPoint, get_active, get_piece_counts};
+    use super::{DataPoint, get_piece_counts, get_scale_factors};
 
     #[test]
     fn test_evaluation() {
@@ -124,23 +101,32 @@ mod tests {
             "rnbq1bnr/pppp1ppp/8/4k3/4P3/8/PPPPKPPP/RNBQ1B1R w - - 0 5",
             "8/8/8/3K4/8/8/8/7k w - - 0 1",
             "8/8/3k4/4r3/8/8/3Q4/2K5 b - - 0 1",
+            "4k3/p7/2p3PP/2p1p3/3P4/2P5/P7/4K3 w - - 0 1",
+            "6k1/5p2/6pP/3Q1N2/8/8/8/6K1 w - - 0 1",
+            "4k3/7p/1p6/2rn4/8/1N2P3/8/4K2R b - - 0 1",
+            "4k3/pppp4/8/8/8/8/PPPP4/R3K1nb w - - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            // Scaled down for opposite-coloured bishops
+            "4k3/5p2/4b3/8/8/2B1P1P1/5P2/4K3 b - - 0 1",
         ] {
             let board = Board::from_fen(test_position).unwrap();
+            assert!(endgame::evaluate(&board).is_none(), "{test_position}");
             let true_eval = Eval::evaluate(&board) * if board.white_to_move { 1 } else { -1 };
 
             let data_point = DataPoint {
-                active: get_active(&board),
+                coefficients: extract(&board),
                 piece_counts: get_piece_counts(&board),
+                scale_factors: get_scale_factors(&board),
                 result: 0.5, // placeholder, not used
             };
 
-            let mut parameters = [(0.0, 0.0); PARAMETER_COUNT];
-            for index in 0..PARAMETER_COUNT {
-                parameters[index] = (
-                    PIECE_SQUARE_TABLE[index].0.into(),
-                    PIECE_SQUARE_TABLE[index].1.into(),
-                );
-            }
+            let parameters = all_parameters(|feature| feature.engine_parameters());
+            // The pawn structure parameters follow the piece-square tables
+            let pawn_structure_offset = FEATURES[0].parameter_count();
+            assert_eq!(
+                parameters[pawn_structure_offset].0,
+                f64::from(PAWN_STRUCTURE[0].0)
+            );
             let mut phase_weights = [0.0; 5];
             for (index, value) in PHASE_WEIGHTS.iter().enumerate() {
                 phase_weights[index] = (*value).into();
@@ -0,0 +1,370 @@
+//! Groups of evaluation parameters that are tuned, which each find how much a position uses
+//! every one of their parameters and print themselves like the tables in `eval_data.rs`
+
+use encrustant::board::Board;
+use encrustant::board::piece::Piece;
+use encrustant::consume_bit_board;
+use encrustant::evaluation::eval_data::{
+    KING_SAFETY, MATERIAL, MOBILITY, PAWN_STRUCTURE, PIECE_SQUARE_TABLE, THREATS,
+};
+use encrustant::evaluation::king_safety::{KING_SAFETY_TERM_GROUPS, KingSafety};
+use encrustant::evaluation::material::{
+    BISHOP_PAIR, Material, our_imbalance_index, their_imbalance_index,
+};
+use encrustant::evaluation::mobility::{MOBILITY_TERM_GROUPS, Mobility};
+use encrustant::evaluation::pawn_structure::{CONNECTED_PASSED_PAWN, PawnStructure};
+use encrustant::evaluation::threats::{THREAT_TERM_GROUPS, Threats};
+
+/// Index of each parameter a position uses, with how many times white uses it minus how many
+/// times black does
+pub type Coefficients = Vec<(u16, i16)>;
+
+/// A group of parameters that is one table in `eval_data.rs`
+///
+/// New terms are tuned by adding their feature to `FEATURES`, without changing how the
+/// evaluation or gradients are computed
+pub trait Feature: Sync {
+    /// Name of the table in `eval_data.rs`
+    fn name(&self) -> &'static str;
+
+    /// Number of parameters in the group
+    fn parameter_count(&self) -> usize;
+
+    /// Pushes the index within the group and the coefficient of each parameter the position uses,
+    /// where an index can be pushed more than once
+    fn coefficients(&self, board: &Board, coefficients: &mut Vec<(usize, i16)>);
+
+    /// Values that tuning starts from
+    fn initial_parameters(&self) -> Vec<(f64, f64)> {
+        vec![(0.0, 0.0); self.parameter_count()]
+    }
+
+    /// Values that the engine currently uses
+    fn engine_parameters(&self) -> Vec<(f64, f64)>;
+
+    /// Formats the declaration of the table in `eval_data.rs`
+    fn format(&self, parameters: &[(f64, f64)]) -> String;
+}
+
+/// Piece-square tables of every piece type
+pub struct PieceSquareTables;
+
+/// Formats the piece-square tables
+fn pretty_parameters(parameters: &[(f64, f64)]) -> String {
+    let mut output = String::new();
+    output.push_str("[\n");
+    for piece in 0..6 {
+        for rank in 0..8 {
+            for file in 0..8 {
+                output.push_str(&format!(
+                    "({:>4}, {:<4}), ",
+                    parameters[piece * 64 + rank * 8 + file].0 as i16,
+                    parameters[piece * 64 + rank * 8 + file].1 as i16,
+                ));
+            }
+            output.push('\n');
+        }
+        if piece != 5 {
+            output.push_str("\n\n");
+        }
+    }
+    output.push(']');
+    output
+}
+
+impl Feature for PieceSquareTables {
+    fn name(&self) -> &'static str {
+        "PIECE_SQUARE_TABLE"
+    }
+
+    fn parameter_count(&self) -> usize {
+        384
+    }
+
+    fn coefficients(&self, board: &Board, coefficients: &mut Vec<(usize, i16)>) {
+        for piece in Piece::WHITE_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                coefficients.push((piece as usize * 64 + square.flip().usize(), 1));
+            });
+        }
+
+        for piece in Piece::BLACK_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                coefficients.push(((piece as usize - 6) * 64 + square.usize(), -1));
+            });
+        }
+    }
+
+    fn initial_parameters(&self) -> Vec<(f64, f64)> {
+        let mut initial_parameters = Vec::with_capacity(self.parameter_count());
+        // Pawns are never on the first or last rank
+        initial_parameters.extend([(0.0, 0.0); 8]);
+        initial_parameters.extend([(50.0, 90.0); 48]);
+        initial_parameters.extend([(0.0, 0.0); 8]);
+
+        for value in [
+            (225.0, 270.0), // Knight
+            (250.0, 290.0), // Bishop
+            (320.0, 500.0), // Rook
+            (710.0, 920.0), // Queen
+            (-80.0, 15.0),  // King
+        ] {
+            initial_parameters.extend([value; 64]);
+        }
+        initial_parameters
+    }
+
+    fn engine_parameters(&self) -> Vec<(f64, f64)> {
+        to_parameters(&PIECE_SQUARE_TABLE)
+    }
+
+    fn format(&self, parameters: &[(f64, f64)]) -> String {
+        format!(
+            "#[rustfmt::skip]
+pub const {}: PieceSquareTable = {};",
+            self.name(),
+            pretty_parameters(parameters)
+        )
+    }
+}
+
+/// Terms counted for white and black, like those in `evaluation::mobility`
+pub struct Terms<const TERMS: usize> {
+    /// Name of the table in `eval_data.rs`
+    name: &'static str,
+
+    /// Type of the table in `eval_data.rs`
+    table_type: &'static str,
+
+    /// Index of the first term on each line of the table
+    rows: &'static [usize],
+
+    counts: fn(&Board) -> [[u8; TERMS]; 2],
+
+    table: &'static [(i16, i16); TERMS],
+}
+
+/// Formats a table of terms eight to a line, starting a new line for each row
+fn pretty_terms(parameters: &[(f64, f64)], rows: &[usize]) -> String {
+    let mut output = String::new();
+    output.push('[');
+    for (term, parameter) in parameters.iter().enumerate() {
+        let row = rows.iter().rev().find(|start| **start <= term).unwrap();
+        if (term - row) % 8 == 0 {
+            output.push('\n');
+        }
+        output.push_str(&format!(
+            "({:>4}, {:<4}), ",
+            parameter.0 as i16, parameter.1 as i16
+        ));
+    }
+    output.push_str("\n]");
+    output
+}
+
+impl<const TERMS: usize> Feature for Terms<TERMS> {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn parameter_count(&self) -> usize {
+        TERMS
+    }
+
+    fn coefficients(&self, board: &Board, coefficients: &mut Vec<(usize, i16)>) {
+        let [white, black] = (self.counts)(board);
+        for (term, (white, black)) in white.into_iter().zip(black).enumerate() {
+            if white != black {
+                coefficients.push((term, i16::from(white) - i16::from(black)));
+            }
+        }
+    }
+
+    fn engine_parameters(&self) -> Vec<(f64, f64)> {
+        to_parameters(self.table)
+    }
+
+    fn format(&self, parameters: &[(f64, f64)]) -> String {
+        format!(
+            "#[rustfmt::skip]
+pub const {}: {} = {};",
+            self.name,
+            self.table_type,
+            pretty_terms(parameters, self.rows)
+        )
+    }
+}
+
+fn to_parameters(table: &[(i16, i16)]) -> Vec<(f64, f64)> {
+    table
+        .iter()
+        .map(|(middle_game, end_game)| (f64::from(*middle_game), f64::from(*end_game)))
+        .collect()
+}
+
+/// First index of each group of terms
+const fn group_starts<const GROUPS: usize>(groups: [(&str, usize); GROUPS]) -> [usize; GROUPS] {
+    let mut starts = [0; GROUPS];
+    let mut group = 0;
+    while group < GROUPS {
+        starts[group] = groups[group].1;
+        group += 1;
+    }
+    starts
+}
+
+/// A row for each of our piece types, like in `eval_data::MATERIAL`
+const fn material_rows() -> [usize; 10] {
+    let mut rows = [BISHOP_PAIR; 10];
+    let mut piece_type = 0;
+    while piece_type < 5 {
+        rows[1 + piece_type] = our_imbalance_index(piece_type, 0);
+        if piece_type > 0 {
+            rows[5 + piece_type] = their_imbalance_index(piece_type, 0);
+        }
+        piece_type += 1;
+    }
+    rows
+}
+
+/// Every tuned group of parameters, in the order of their parameters and of `eval_data.rs`
+pub static FEATURES: [&dyn Feature; 6] = [
+    &PieceSquareTables,
+    &Terms {
+        name: "PAWN_STRUCTURE",
+        table_type: "PawnStructureTable",
+        rows: &[0, CONNECTED_PASSED_PAWN],
+        counts: |board| PawnStructure::new(board).counts,
+        table: &PAWN_STRUCTURE,
+    },
+    &Terms {
+        name: "MOBILITY",
+        table_type: "MobilityTable",
+        rows: &group_starts(MOBILITY_TERM_GROUPS),
+        counts: |board| Mobility::new(board).counts,
+        table: &MOBILITY,
+    },
+    &Terms {
+        name: "KING_SAFETY",
+        table_type: "KingSafetyTable",
+        rows: &group_starts(KING_SAFETY_TERM_GROUPS),
+        counts: |board| KingSafety::new(board).counts,
+        table: &KING_SAFETY,
+    },
+    &Terms {
+        name: "THREATS",
+        table_type: "ThreatTable",
+        rows: &group_starts(THREAT_TERM_GROUPS),
+        counts: |board| Threats::new(board).counts,
+        table: &THREATS,
+    },
+    &Terms {
+        name: "MATERIAL",
+        table_type: "MaterialTable",
+        rows: &material_rows(),
+        counts: |board| Material::new(board).counts,
+        table: &MATERIAL,
+    },
+];
+
+/// Parameters of every feature, from the values given by `feature_parameters`
+pub fn all_parameters(
+    feature_parameters: impl Fn(&dyn Feature) -> Vec<(f64, f64)>,
+) -> Vec<(f64, f64)> {
+    FEATURES
+        .iter()
+        .flat_map(|feature| feature_parameters(*feature))
+        .collect()
+}
+
+/// Each feature along with its parameters
+pub fn split_parameters(
+    parameters: &[(f64, f64)],
+) -> impl Iterator<Item = (&'static dyn Feature, &[(f64, f64)])> {
+    let mut remaining = parameters;
+    FEATURES.iter().map(move |feature| {
+        let (feature_parameters, rest) = remaining.split_at(feature.parameter_count());
+        remaining = rest;
+        (*feature, feature_parameters)
+    })
+}
+
+/// Coefficients of the parameters of every feature, sorted by index and without zeros
+pub fn extract(board: &Board) -> Coefficients {
+    let mut coefficients = Vec::new();
+    let mut offset = 0;
+    for feature in FEATURES {
+        let start = coefficients.len();
+        feature.coefficients(board, &mut coefficients);
+        for (index, _) in &mut coefficients[start..] {
+            *index += offset;
+        }
+        offset += feature.parameter_count();
+    }
+
+    // White and black can use the same parameter, such as a piece on mirrored squares
+    coefficients.sort_unstable_by_key(|(index, _)| *index);
+    let mut merged: Coefficients = Vec::with_capacity(coefficients.len());
+    for (index, coefficient) in coefficients {
+        let index = index.try_into().unwrap();
+        match merged.last_mut() {
+            Some(last) if last.0 == index => last.1 += coefficient,
+            _ => merged.push((index, coefficient)),
+        }
+    }
+    merged.retain(|(_, coefficient)| *coefficient != 0);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use encrustant::board::Board;
+
+    use super::{FEATURES, extract};
+
+    #[test]
+    fn symmetric_position_cancels_out() {
+        let board = Board::from_fen(Board::START_POSITION_FEN).unwrap();
+        assert!(extract(&board).is_empty());
+
+        let board = Board::from_fen("4k3/8/2p5/8/4P3/8/8/4K3 w - - 0 1").unwrap();
+        let coefficients = extract(&board);
+        let parameter_count: usize = FEATURES
+            .iter()
+            .map(|feature| feature.parameter_count())
+            .sum();
+        assert!(
+            coefficients
+                .iter()
+                .all(|(index, _)| usize::from(*index) < parameter_count)
+        );
+        assert!(coefficients.is_sorted_by_key(|(index, _)| *index));
+        assert!(!coefficients.is_empty());
+    }
+
+    #[test]
+    fn declarations_match_eval_data() {
+        let eval_data = include_str!("../../encrustant/src/evaluation/eval_data.rs");
+        for feature in FEATURES {
+            let output = feature.format(&feature.engine_parameters());
+            let declaration = output.lines().nth(1).unwrap().split(" = ").next().unwrap();
+            assert!(
+                eval_data.contains(&format!("\n{declaration} = ")),
+                "{declaration}"
+            );
+        }
+    }
+
+    #[test]
+    fn parameter_counts_match_tables() {
+        for feature in FEATURES {
+            assert_eq!(
+                feature.initial_parameters().len(),
+                feature.parameter_count()
+            );
+            assert_eq!(feature.engine_parameters().len(), feature.parameter_count());
+        }
+    }
+}
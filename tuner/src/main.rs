@@ -2,14 +2,94 @@
 #![warn(clippy::nursery)]
 
 mod evaluation;
+mod features;
+mod nnue;
 
 use encrustant::board::Board;
-use evaluation::{DataPoint, PARAMETER_COUNT, get_active, get_piece_counts, get_total_phase};
+use encrustant::evaluation::endgame;
+use encrustant::evaluation::eval_data::PHASE_WEIGHTS;
+use evaluation::{DataPoint, get_piece_counts, get_scale_factors, get_total_phase};
+use features::{FEATURES, all_parameters, extract, split_parameters};
 use rayon::prelude::*;
 use std::io::BufRead;
 use std::time::Instant;
 use std::{fs::File, io::BufReader};
 
+const USAGE: &str = "Usage: tuner [options]
+Tunes the tables of eval_data.rs on dataset/positions.txt, writing them to tuned.rs
+    --start <values>            initial or engine, the tables in eval_data.rs (default initial)
+    --tune <table>              only tune this table, such as MOBILITY or PHASE_WEIGHTS, where the
+                                option can be repeated (default every table)
+    --iterations <count>        optimiser steps (default 8000)
+    --k <scale>                 sigmoid scale, or find to fit it to the starting values (default 4 ln 3)
+The network trainer is run with tuner nnue, which lists its own options";
+
+/// Name of the phase weights, which are tuned along with the tables of `FEATURES`
+const PHASE_WEIGHTS_NAME: &str = "PHASE_WEIGHTS";
+
+struct Options {
+    start_from_engine: bool,
+
+    /// Names of the tables to tune, where every table is tuned if it is empty
+    tables: Vec<String>,
+
+    iterations: u32,
+
+    /// Sigmoid scale, or `None` to find the one that fits the starting values best
+    k: Option<f64>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Self {
+            start_from_engine: false,
+            tables: Vec::new(),
+            iterations: 8000,
+            k: Some(4.0 * f64::ln(3.0)),
+        };
+
+        while let Some(label) = args.next() {
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("Missing value for {label}\n{USAGE}"));
+            let invalid = || -> ! { panic!("Invalid value {value} for {label}\n{USAGE}") };
+            match label.as_str() {
+                "--start" => {
+                    options.start_from_engine = match value.as_str() {
+                        "initial" => false,
+                        "engine" => true,
+                        _ => invalid(),
+                    };
+                }
+                "--tune" => {
+                    if value != PHASE_WEIGHTS_NAME
+                        && !FEATURES.iter().any(|feature| feature.name() == value)
+                    {
+                        invalid();
+                    }
+                    options.tables.push(value.clone());
+                }
+                "--iterations" => options.iterations = value.parse().unwrap_or_else(|_| invalid()),
+                "--k" => {
+                    options.k = if value == "find" {
+                        None
+                    } else {
+                        Some(value.parse().unwrap_or_else(|_| invalid()))
+                    };
+                }
+                _ => panic!("Unknown option {label}\n{USAGE}"),
+            }
+        }
+
+        options
+    }
+
+    /// Whether the table called `name` is updated, where the others keep their starting values
+    fn tunes(&self, name: &str) -> bool {
+        self.tables.is_empty() || self.tables.iter().any(|table| table == name)
+    }
+}
+
 fn parse_data_set() -> Vec<DataPoint> {
     let file = File::open("dataset/positions.txt").expect("Failed to open file");
     let data_set = BufReader::new(file);
@@ -31,12 +111,18 @@ fn parse_data_set() -> Vec<DataPoint> {
         };
 
         let board = Board::from_fen(fen).unwrap();
-        let active = get_active(&board);
+        if endgame::evaluate(&board).is_some() {
+            // Evaluated by a dedicated function rather than the tuned tables
+            continue;
+        }
+        let coefficients = extract(&board);
         let piece_counts = get_piece_counts(&board);
+        let scale_factors = get_scale_factors(&board);
         parsed.push(DataPoint {
-            active,
+            coefficients,
             result,
             piece_counts,
+            scale_factors,
         });
     }
     parsed.shrink_to_fit();
@@ -73,39 +159,28 @@ fn mean_square_error(
 fn compute_gradients(
     data_set: &[DataPoint],
     k: f64,
-    parameters: &[(f64, f64); PARAMETER_COUNT],
+    parameters: &[(f64, f64)],
     phase_weights: &[f64; 5],
-) -> ([(f64, f64); PARAMETER_COUNT], [f64; 5]) {
-    let mut param_gradients = [(0.0, 0.0); PARAMETER_COUNT];
+) -> (Vec<(f64, f64)>, [f64; 5]) {
+    let mut param_gradients = vec![(0.0, 0.0); parameters.len()];
     let mut phase_gradients = [0.0; 5];
     let max_counts = [8.0, 2.0, 2.0, 2.0, 1.0];
 
     for data_point in data_set {
         let phase = data_point.get_phase(phase_weights);
-        let score = data_point.evaluate(parameters, phase);
-        let sigmoid_val = sigmoid(k * score);
+        let (mid_total, end_total) = data_point.score(parameters);
+        let tapered_score = phase.mul_add(mid_total, (1.0 - phase) * end_total);
+        let scale_factor = data_point.scale_factor(tapered_score);
+        let sigmoid_val = sigmoid(k * tapered_score * scale_factor);
+
+        // The scale factor multiplies the derivative of the score with respect to every parameter
+        let term = 2.0
+            * (sigmoid_val - data_point.result)
+            * sigmoid_val
+            * (1.0 - sigmoid_val)
+            * k
+            * scale_factor;
 
-        let term = 2.0 * (sigmoid_val - data_point.result) * sigmoid_val * (1.0 - sigmoid_val) * k;
-
-        // Compute mid_total and end_total
-        let white_mid: f64 = data_point.active[0]
-            .iter()
-            .map(|&i| parameters[i as usize].0)
-            .sum();
-        let white_end: f64 = data_point.active[0]
-            .iter()
-            .map(|&i| parameters[i as usize].1)
-            .sum();
-        let black_mid: f64 = data_point.active[1]
-            .iter()
-            .map(|&i| parameters[i as usize].0)
-            .sum();
-        let black_end: f64 = data_point.active[1]
-            .iter()
-            .map(|&i| parameters[i as usize].1)
-            .sum();
-        let mid_total = white_mid - black_mid;
-        let end_total = white_end - black_end;
         let error_term = term * (mid_total - end_total);
 
         let current_phase: f64 = data_point
@@ -127,13 +202,10 @@ fn compute_gradients(
 
         // Parameter gradients
         let scores = (phase * term, (1.0 - phase) * term);
-        for index in &data_point.active[0] {
-            param_gradients[*index as usize].0 += scores.0;
-            param_gradients[*index as usize].1 += scores.1;
-        }
-        for index in &data_point.active[1] {
-            param_gradients[*index as usize].0 -= scores.0;
-            param_gradients[*index as usize].1 -= scores.1;
+        for &(index, coefficient) in &data_point.coefficients {
+            let gradient = &mut param_gradients[usize::from(index)];
+            gradient.0 = f64::from(coefficient).mul_add(scores.0, gradient.0);
+            gradient.1 = f64::from(coefficient).mul_add(scores.1, gradient.1);
         }
     }
 
@@ -143,15 +215,15 @@ fn compute_gradients(
 fn compute_gradients_parallel(
     data_set: &[DataPoint],
     k: f64,
-    parameters: &[(f64, f64); PARAMETER_COUNT],
+    parameters: &[(f64, f64)],
     phase_weights: &[f64; 5],
-) -> ([(f64, f64); PARAMETER_COUNT], [f64; 5]) {
+) -> (Vec<(f64, f64)>, [f64; 5]) {
     // Split the dataset into chunks for parallel processing
     data_set
         .par_chunks(262144)
         .map(|chunk| compute_gradients(chunk, k, parameters, phase_weights))
         .reduce(
-            || ([(0.0, 0.0); PARAMETER_COUNT], [0.0; 5]),
+            || (vec![(0.0, 0.0); parameters.len()], [0.0; 5]),
             |mut a, b| {
                 let (a_params, a_phases) = &mut a;
                 let (b_params, b_phases) = b;
@@ -172,33 +244,7 @@ fn compute_gradients_parallel(
         )
 }
 
-fn pretty_parameters(parameters: &[(f64, f64); PARAMETER_COUNT]) -> String {
-    let mut output = String::new();
-    output.push_str("[\n");
-    for piece in 0..6 {
-        for rank in 0..8 {
-            for file in 0..8 {
-                output.push_str(&format!(
-                    "({:>4}, {:<4}), ",
-                    parameters[piece * 64 + rank * 8 + file].0 as i16,
-                    parameters[piece * 64 + rank * 8 + file].1 as i16,
-                ));
-            }
-            output.push('\n');
-        }
-        if piece != 5 {
-            output.push_str("\n\n");
-        }
-    }
-    output.push(']');
-    output
-}
-
-fn find_k(
-    data_set: &[DataPoint],
-    parameters: &[(f64, f64); PARAMETER_COUNT],
-    phase_weights: &[f64; 5],
-) -> f64 {
+fn find_k(data_set: &[DataPoint], parameters: &[(f64, f64)], phase_weights: &[f64; 5]) -> f64 {
     let mut min = -10.0;
     let mut max = 10.0;
     let mut delta = 1.0;
@@ -230,47 +276,59 @@ fn find_k(
 fn tune(
     data_set: &[DataPoint],
     k: f64,
-    mut parameters: [(f64, f64); 384],
+    mut parameters: Vec<(f64, f64)>,
     mut phase_weights: [f64; 5],
+    options: &Options,
 ) {
     const PARAM_LEARNING_RATE: f64 = 0.04;
     const PHASE_LEARNING_RATE: f64 = 0.001;
     const BETA1: f64 = 0.9;
     const BETA2: f64 = 0.999;
 
-    let mut param_velocity = [(0.0, 0.0); 384];
-    let mut param_momentum = [(0.0, 0.0); 384];
+    let mut param_velocity = vec![(0.0, 0.0); parameters.len()];
+    let mut param_momentum = vec![(0.0, 0.0); parameters.len()];
 
     let mut phase_velocity = [0.0; 384];
     let mut phase_momentum = [0.0; 384];
 
+    let tuned_parameters: Vec<bool> = FEATURES
+        .iter()
+        .flat_map(|feature| {
+            std::iter::repeat_n(options.tunes(feature.name()), feature.parameter_count())
+        })
+        .collect();
+    let tune_phase_weights = options.tunes(PHASE_WEIGHTS_NAME);
+
     let mut previous_error = f64::MAX;
-    let log_params = |parameters: &[(f64, f64); 384], phase_weights: &[f64; 5]| {
-        std::fs::write(
-            "tuned.rs",
+    // Every table in the layout of `eval_data.rs`, where the phase weights follow the piece-square tables
+    let log_params = |parameters: &[(f64, f64)], phase_weights: &[f64; 5]| {
+        let mut tables: Vec<String> = split_parameters(parameters)
+            .map(|(feature, parameters)| feature.format(parameters))
+            .collect();
+        tables.insert(
+            1,
             format!(
-                "#[rustfmt::skip]
-pub const PIECE_SQUARE_TABLE: PieceSquareTable = {};
-
-pub const PHASE_WEIGHTS: [i32; 5] = {:?};",
-                pretty_parameters(parameters),
+                "pub const PHASE_WEIGHTS: [i32; 5] = {:?};",
                 phase_weights
                     .iter()
                     .map(|x| *x as i32)
                     .collect::<Vec<i32>>()
             ),
-        )
-        .unwrap();
+        );
+        std::fs::write("tuned.rs", tables.join("\n\n")).unwrap();
     };
     log_params(&parameters, &phase_weights);
 
     let mut last_update = Instant::now();
-    for iteration in 0..8000 {
+    for iteration in 0..options.iterations {
         let (param_gradients, phase_gradients) =
             compute_gradients_parallel(data_set, k, &parameters, &phase_weights);
 
         // Update parameters
         for (i, gradient) in param_gradients.iter().enumerate() {
+            if !tuned_parameters[i] {
+                continue;
+            }
             param_momentum[i].0 = BETA1.mul_add(param_momentum[i].0, (1.0 - BETA1) * gradient.0);
             param_momentum[i].1 = BETA1.mul_add(param_momentum[i].1, (1.0 - BETA1) * gradient.1);
 
@@ -286,7 +344,11 @@ pub const PHASE_WEIGHTS: [i32; 5] = {:?};",
         }
 
         // Update phase weights
-        for (i, gradient) in phase_gradients.iter().enumerate() {
+        for (i, gradient) in phase_gradients
+            .iter()
+            .enumerate()
+            .filter(|_| tune_phase_weights)
+        {
             phase_momentum[i] = BETA1.mul_add(phase_momentum[i], (1.0 - BETA1) * gradient);
 
             phase_velocity[i] =
@@ -311,52 +373,23 @@ pub const PHASE_WEIGHTS: [i32; 5] = {:?};",
 }
 
 fn main() {
-    let initial_phase_weights = [0.0, 100.0, 100.0, 200.0, 400.0];
-
-    let initial_parameters = {
-        let mut initial_parameters = [(0.0, 0.0); 384];
-
-        let mut index = 0;
-
-        // Pawn
-        for pawn_index in 0..64 {
-            if pawn_index > 7 && pawn_index < 56 {
-                initial_parameters[index] = (50.0, 90.0);
-            }
-            index += 1;
-        }
-
-        // Knight
-        for _ in 0..64 {
-            initial_parameters[index] = (225.0, 270.0);
-            index += 1;
-        }
-
-        // Bishop
-        for _ in 0..64 {
-            initial_parameters[index] = (250.0, 290.0);
-            index += 1;
-        }
-
-        // Rook
-        for _ in 0..64 {
-            initial_parameters[index] = (320.0, 500.0);
-            index += 1;
-        }
-
-        // Queen
-        for _ in 0..64 {
-            initial_parameters[index] = (710.0, 920.0);
-            index += 1;
-        }
-
-        // King
-        for _ in 0..64 {
-            initial_parameters[index] = (-80.0, 15.0);
-            index += 1;
-        }
-
-        initial_parameters
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().map(String::as_str) == Some("nnue") {
+        args.next();
+        nnue::run(args);
+        return;
+    }
+    let options = Options::parse(args);
+
+    let engine_parameters = all_parameters(|feature| feature.engine_parameters());
+    let engine_phase_weights = PHASE_WEIGHTS.map(f64::from);
+    let (parameters, phase_weights) = if options.start_from_engine {
+        (engine_parameters.clone(), engine_phase_weights)
+    } else {
+        (
+            all_parameters(|feature| feature.initial_parameters()),
+            [0.0, 100.0, 100.0, 200.0, 400.0],
+        )
     };
 
     let data_set_start_time = Instant::now();
@@ -367,15 +400,21 @@ fn main() {
     );
 
     let k_start_time = Instant::now();
-    // let k = find_k(&data_set, &parameters, &initial_phase_weights);
-    let k = 4.0 * f64::ln(3.0);
+    let k = options
+        .k
+        .unwrap_or_else(|| find_k(&data_set, &parameters, &phase_weights));
     println!(
         "Found k: {k} in {:.1} seconds",
         k_start_time.elapsed().as_secs_f64()
     );
 
+    println!(
+        "Engine parameters: MSE = {}",
+        mean_square_error(&data_set, k, &engine_parameters, &engine_phase_weights)
+    );
+
     let tune_start_time = Instant::now();
-    tune(&data_set, k, initial_parameters, initial_phase_weights);
+    tune(&data_set, k, parameters, phase_weights, &options);
     println!(
         "Tuned in {:.1} seconds",
         tune_start_time.elapsed().as_secs_f64()
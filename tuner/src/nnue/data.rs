@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use encrustant::board::Board;
+use encrustant::board::piece::Piece;
+use encrustant::consume_bit_board;
+use encrustant::evaluation::nnue::{SCALE, feature_indices};
+
+use super::network::sigmoid;
+
+pub struct TrainingPosition {
+    /// Active input features from white's and black's perspective
+    pub features: Vec<(u16, u16)>,
+
+    pub white_to_move: bool,
+
+    /// Engine score in centipawns for the side to move, if the data has one
+    pub score: Option<f32>,
+
+    /// Game result for the side to move
+    /// 0.0 -> loss;
+    /// 0.5 -> draw;
+    /// 1.0 -> win;
+    pub result: f32,
+}
+
+impl TrainingPosition {
+    fn new(board: &Board, white_score: Option<f32>, white_result: f32) -> Self {
+        let mut features = Vec::with_capacity(32);
+        for piece in Piece::ALL_PIECES {
+            let mut bit_board = *board.get_bit_board(piece);
+            consume_bit_board!(bit_board, square {
+                let (white, black) = feature_indices(piece, square);
+                features.push((white.try_into().unwrap(), black.try_into().unwrap()));
+            });
+        }
+
+        let white_to_move = board.white_to_move;
+        Self {
+            features,
+            white_to_move,
+            score: white_score.map(|score| if white_to_move { score } else { -score }),
+            result: if white_to_move {
+                white_result
+            } else {
+                1.0 - white_result
+            },
+        }
+    }
+
+    /// Win probability to train towards, blending the game result with the engine score by `wdl`
+    pub fn target(&self, wdl: f32) -> f32 {
+        self.score.map_or(self.result, |score| {
+            wdl.mul_add(self.result, (1.0 - wdl) * sigmoid(score / SCALE as f32))
+        })
+    }
+
+    /// Features of the side to move, then of the other side
+    pub fn perspectives(&self) -> (impl Iterator<Item = usize>, impl Iterator<Item = usize>) {
+        let white_to_move = self.white_to_move;
+        let us = self
+            .features
+            .iter()
+            .map(move |(white, black)| usize::from(if white_to_move { *white } else { *black }));
+        let them = self
+            .features
+            .iter()
+            .map(move |(white, black)| usize::from(if white_to_move { *black } else { *white }));
+        (us, them)
+    }
+}
+
+fn parse_result(result: &str) -> Option<f32> {
+    match result.trim() {
+        "0.0" | "0" | "0-1" => Some(0.0),
+        "0.5" | "1/2-1/2" => Some(0.5),
+        "1.0" | "1" | "1-0" => Some(1.0),
+        _ => None,
+    }
+}
+
+/// Parses a line in either format, with scores and results from white's perspective:
+/// `<fen> [<result>]` like `dataset/positions.txt`, or `<fen> | <score> | <result>` from datagen
+pub fn parse_line(line: &str) -> Result<TrainingPosition, String> {
+    let (fen, score, result) = if let Some((fen, rest)) = line.split_once('|') {
+        let (score, result) = rest
+            .split_once('|')
+            .ok_or_else(|| format!("Missing result in {line}"))?;
+        let score: f32 = score
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid score in {line}"))?;
+        (fen, Some(score), result)
+    } else {
+        let (fen, result) = line
+            .rsplit_once('[')
+            .ok_or_else(|| format!("Missing result in {line}"))?;
+        (fen, None, result.trim_end().trim_end_matches(']'))
+    };
+
+    let result = parse_result(result).ok_or_else(|| format!("Unknown game result in {line}"))?;
+    let board = Board::from_fen(fen.trim()).map_err(|error| format!("{error} in {line}"))?;
+    Ok(TrainingPosition::new(&board, score, result))
+}
+
+pub fn load(path: &str) -> Vec<TrainingPosition> {
+    let file = File::open(path).unwrap_or_else(|error| panic!("Failed to open {path}: {error}"));
+    let mut positions = Vec::new();
+
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            eprintln!("Failed to read data");
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_line(&line) {
+            Ok(position) => positions.push(position),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+    positions.shrink_to_fit();
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_line;
+
+    #[test]
+    fn parses_both_formats() {
+        let tuner_format =
+            parse_line("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 [1.0]")
+                .unwrap();
+        assert_eq!(tuner_format.features.len(), 32);
+        assert!(!tuner_format.white_to_move);
+        assert_eq!(tuner_format.score, None);
+        assert_eq!(tuner_format.result, 0.0);
+
+        let datagen_format =
+            parse_line("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1 | 35 | 0.5")
+                .unwrap();
+        assert_eq!(datagen_format.score, Some(-35.0));
+        assert_eq!(datagen_format.result, 0.5);
+
+        assert!(parse_line("8/8/8/8/8/8/8/8 w - - 0 1 [1.0]").is_err());
+        assert!(parse_line("4k3/8/8/8/8/8/8/4K3 w - - 0 1 | x | 1.0").is_err());
+        assert!(parse_line("4k3/8/8/8/8/8/8/4K3 w - - 0 1 [2.0]").is_err());
+    }
+}
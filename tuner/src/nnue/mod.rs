@@ -0,0 +1,248 @@
+mod data;
+mod network;
+mod optimiser;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use data::TrainingPosition;
+use network::{FloatNetwork, PARAMETER_COUNT};
+use optimiser::{AdamW, Checkpoint, LearningRateSchedule, save_checkpoint};
+use rayon::prelude::*;
+
+const USAGE: &str = "Usage: tuner nnue --data <file> [--data <file>...] [options]
+    --epochs <count>            passes over the data (default 40)
+    --batch-size <count>        positions per optimiser step (default 16384)
+    --lr <rate>                 initial learning rate (default 0.001)
+    --schedule <schedule>       constant, step or cosine (default cosine)
+    --gamma <factor>            step schedule multiplier (default 0.3)
+    --step <epochs>             step schedule interval (default 10)
+    --final-lr <rate>           cosine schedule final learning rate (default 0.00001)
+    --wdl <proportion>          weight of the game result against the engine score (default 0.5)
+    --weight-decay <factor>     AdamW weight decay (default 0.01)
+    --threads <count>           rayon threads (default all cores)
+    --output <directory>        where checkpoints and networks are saved (default checkpoints)
+    --resume <checkpoint>       carry on from a checkpoint
+    --seed <seed>               initial weights and shuffling (default 1)";
+
+struct Options {
+    data: Vec<String>,
+    epochs: u32,
+    batch_size: usize,
+    learning_rate: f32,
+    schedule: LearningRateSchedule,
+    wdl: f32,
+    weight_decay: f32,
+    threads: Option<usize>,
+    output: PathBuf,
+    resume: Option<PathBuf>,
+    seed: u64,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Self {
+        let mut options = Self {
+            data: Vec::new(),
+            epochs: 40,
+            batch_size: 16384,
+            learning_rate: 0.001,
+            schedule: LearningRateSchedule::Constant,
+            wdl: 0.5,
+            weight_decay: 0.01,
+            threads: None,
+            output: PathBuf::from("checkpoints"),
+            resume: None,
+            seed: 1,
+        };
+        let mut schedule = String::from("cosine");
+        let (mut gamma, mut step, mut final_rate) = (0.3, 10, 0.00001);
+
+        while let Some(label) = args.next() {
+            let value = args
+                .next()
+                .unwrap_or_else(|| panic!("Missing value for {label}\n{USAGE}"));
+            let invalid = || -> ! { panic!("Invalid value {value} for {label}\n{USAGE}") };
+            match label.as_str() {
+                "--data" => options.data.push(value.clone()),
+                "--epochs" => options.epochs = value.parse().unwrap_or_else(|_| invalid()),
+                "--batch-size" => {
+                    options.batch_size = value.parse().unwrap_or_else(|_| invalid());
+                }
+                "--lr" => options.learning_rate = value.parse().unwrap_or_else(|_| invalid()),
+                "--schedule" => schedule.clone_from(&value),
+                "--gamma" => gamma = value.parse().unwrap_or_else(|_| invalid()),
+                "--step" => step = value.parse().unwrap_or_else(|_| invalid()),
+                "--final-lr" => final_rate = value.parse().unwrap_or_else(|_| invalid()),
+                "--wdl" => options.wdl = value.parse().unwrap_or_else(|_| invalid()),
+                "--weight-decay" => {
+                    options.weight_decay = value.parse().unwrap_or_else(|_| invalid());
+                }
+                "--threads" => options.threads = Some(value.parse().unwrap_or_else(|_| invalid())),
+                "--output" => options.output = PathBuf::from(&value),
+                "--resume" => options.resume = Some(PathBuf::from(&value)),
+                "--seed" => options.seed = value.parse().unwrap_or_else(|_| invalid()),
+                _ => panic!("Unknown option {label}\n{USAGE}"),
+            }
+        }
+
+        options.schedule = match schedule.as_str() {
+            "constant" => LearningRateSchedule::Constant,
+            "step" => LearningRateSchedule::Step { gamma, step },
+            "cosine" => LearningRateSchedule::Cosine { final_rate },
+            _ => panic!("Unknown schedule {schedule}\n{USAGE}"),
+        };
+        assert!(!options.data.is_empty(), "No data files\n{USAGE}");
+        assert!(options.batch_size > 0, "Batch size must be positive");
+        assert!(
+            (0.0..=1.0).contains(&options.wdl),
+            "WDL proportion must be within 0 to 1"
+        );
+
+        options
+    }
+}
+
+/// Sums the gradients and errors of a batch of positions, split between threads
+fn batch_gradients(
+    network: &FloatNetwork,
+    positions: &[TrainingPosition],
+    batch: &[usize],
+    wdl: f32,
+) -> (Vec<f32>, f64) {
+    let chunk_size = batch.len().div_ceil(rayon::current_num_threads());
+    batch
+        .par_chunks(chunk_size)
+        .map(|chunk| {
+            let mut gradients = vec![0.0; PARAMETER_COUNT];
+            let mut error = 0.0;
+            for &index in chunk {
+                let position = &positions[index];
+                error += f64::from(network.backpropagate(
+                    position,
+                    position.target(wdl),
+                    &mut gradients,
+                ));
+            }
+            (gradients, error)
+        })
+        .reduce(
+            || (vec![0.0; PARAMETER_COUNT], 0.0),
+            |(mut a_gradients, a_error), (b_gradients, b_error)| {
+                for (a, b) in a_gradients.iter_mut().zip(b_gradients) {
+                    *a += b;
+                }
+                (a_gradients, a_error + b_error)
+            },
+        )
+}
+
+/// Fisher-Yates shuffle with xorshift
+fn shuffle(order: &mut [usize], seed: u64) {
+    let mut state = seed.max(1);
+    for index in (1..order.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        order.swap(index, (state % (index as u64 + 1)) as usize);
+    }
+}
+
+fn train(options: &Options, positions: &[TrainingPosition]) {
+    let (mut network, mut optimiser, first_epoch) = options.resume.as_ref().map_or_else(
+        || {
+            (
+                FloatNetwork::random(options.seed),
+                AdamW::new(PARAMETER_COUNT, options.weight_decay),
+                0,
+            )
+        },
+        |path| {
+            let checkpoint = Checkpoint::load(path, PARAMETER_COUNT);
+            println!("Resuming from epoch {}", checkpoint.epoch);
+            (
+                FloatNetwork {
+                    parameters: checkpoint.parameters,
+                },
+                checkpoint.optimiser,
+                checkpoint.epoch,
+            )
+        },
+    );
+    fs::create_dir_all(&options.output)
+        .unwrap_or_else(|error| panic!("Failed to create {}: {error}", options.output.display()));
+
+    let mut order: Vec<usize> = (0..positions.len()).collect();
+    for epoch in first_epoch..options.epochs {
+        let epoch_start_time = Instant::now();
+
+        // Shuffling depends only on the epoch, so resuming gives the same order
+        order.sort_unstable();
+        shuffle(&mut order, options.seed.wrapping_add(u64::from(epoch)));
+
+        let learning_rate =
+            options
+                .schedule
+                .learning_rate(options.learning_rate, epoch, options.epochs);
+        let mut total_error = 0.0;
+        for batch in order.chunks(options.batch_size) {
+            let (mut gradients, error) = batch_gradients(&network, positions, batch, options.wdl);
+            for gradient in &mut gradients {
+                *gradient /= batch.len() as f32;
+            }
+            optimiser.update(&mut network.parameters, &gradients, learning_rate);
+            network.clip_weights();
+            total_error += error;
+        }
+
+        let completed = epoch + 1;
+        println!(
+            "Epoch {completed}: MSE = {}, learning rate = {learning_rate}, {:.1} seconds",
+            total_error / positions.len() as f64,
+            epoch_start_time.elapsed().as_secs_f64()
+        );
+
+        let network_path = options.output.join(format!("epoch-{completed}.nnue"));
+        fs::write(&network_path, network.quantise().to_bytes())
+            .unwrap_or_else(|error| panic!("Failed to write {}: {error}", network_path.display()));
+        save_checkpoint(
+            &options.output.join(format!("epoch-{completed}.bin")),
+            completed,
+            &network.parameters,
+            &optimiser,
+        );
+    }
+
+    println!("Finished");
+}
+
+/// Trains a network in the format loaded by the engine's `EvalFile` option
+pub fn run(args: impl Iterator<Item = String>) {
+    let options = Options::parse(args);
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .unwrap();
+    }
+
+    let data_set_start_time = Instant::now();
+    let positions: Vec<TrainingPosition> = options
+        .data
+        .iter()
+        .flat_map(|path| data::load(path))
+        .collect();
+    assert!(!positions.is_empty(), "No positions in the data files");
+    println!(
+        "Parsed {} positions in {:.1} seconds",
+        positions.len(),
+        data_set_start_time.elapsed().as_secs_f64()
+    );
+
+    let train_start_time = Instant::now();
+    train(&options, &positions);
+    println!(
+        "Trained in {:.1} seconds",
+        train_start_time.elapsed().as_secs_f64()
+    );
+}
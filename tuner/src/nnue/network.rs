@@ -0,0 +1,277 @@
+use encrustant::evaluation::nnue::{
+    Accumulator, HIDDEN_SIZE, INPUT_SIZE, MAX_OUTPUT_WEIGHT, Network, QA, QB,
+};
+
+use super::data::TrainingPosition;
+
+pub const FEATURE_WEIGHTS: usize = 0;
+pub const FEATURE_BIASES: usize = FEATURE_WEIGHTS + INPUT_SIZE * HIDDEN_SIZE;
+pub const OUTPUT_WEIGHTS: usize = FEATURE_BIASES + HIDDEN_SIZE;
+pub const OUTPUT_BIAS: usize = OUTPUT_WEIGHTS + 2 * HIDDEN_SIZE;
+pub const PARAMETER_COUNT: usize = OUTPUT_BIAS + 1;
+
+/// Weights are kept within this so that they still fit once quantised
+const MAX_WEIGHT: f32 = 1.98;
+
+/// Feature biases are kept within this so that a bias plus 32 feature weights fits in the engine's `i16`
+/// accumulators once quantised
+const MAX_FEATURE_BIAS: f32 = 64.0;
+
+pub fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + f32::exp(-x))
+}
+
+/// Unquantised network, laid out like the engine's network file
+#[derive(Clone)]
+pub struct FloatNetwork {
+    pub parameters: Vec<f32>,
+}
+
+struct Activations {
+    accumulators: [[f32; HIDDEN_SIZE]; 2],
+    output: f32,
+}
+
+impl FloatNetwork {
+    pub fn random(seed: u64) -> Self {
+        let mut state = seed.max(1);
+        let mut random = || {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            ((state >> 40) as f32 / (1 << 24) as f32).mul_add(2.0, -1.0)
+        };
+
+        let mut parameters = vec![0.0; PARAMETER_COUNT];
+        let feature_range = (1.0 / 32.0_f32).sqrt();
+        let output_range = (1.0 / (2 * HIDDEN_SIZE) as f32).sqrt();
+        for parameter in &mut parameters[FEATURE_WEIGHTS..FEATURE_BIASES] {
+            *parameter = random() * feature_range;
+        }
+        for parameter in &mut parameters[OUTPUT_WEIGHTS..OUTPUT_BIAS] {
+            *parameter = random() * output_range;
+        }
+        Self { parameters }
+    }
+
+    fn accumulate(&self, features: impl Iterator<Item = usize>) -> [f32; HIDDEN_SIZE] {
+        let mut accumulator = [0.0; HIDDEN_SIZE];
+        accumulator.copy_from_slice(&self.parameters[FEATURE_BIASES..OUTPUT_WEIGHTS]);
+        for feature in features {
+            let weights = &self.parameters[FEATURE_WEIGHTS + feature * HIDDEN_SIZE
+                ..FEATURE_WEIGHTS + (feature + 1) * HIDDEN_SIZE];
+            for (value, weight) in accumulator.iter_mut().zip(weights) {
+                *value += weight;
+            }
+        }
+        accumulator
+    }
+
+    fn forward(&self, position: &TrainingPosition) -> Activations {
+        let (us, them) = position.perspectives();
+        let accumulators = [self.accumulate(us), self.accumulate(them)];
+
+        let mut output = self.parameters[OUTPUT_BIAS];
+        for (side, accumulator) in accumulators.iter().enumerate() {
+            let weights = &self.parameters
+                [OUTPUT_WEIGHTS + side * HIDDEN_SIZE..OUTPUT_WEIGHTS + (side + 1) * HIDDEN_SIZE];
+            for (value, weight) in accumulator.iter().zip(weights) {
+                let activation = value.clamp(0.0, 1.0);
+                output += activation * activation * weight;
+            }
+        }
+
+        Activations {
+            accumulators,
+            output,
+        }
+    }
+
+    /// Score in centipawns for the side to move
+    #[cfg(test)]
+    pub fn evaluate(&self, position: &TrainingPosition) -> f32 {
+        self.forward(position).output * encrustant::evaluation::nnue::SCALE as f32
+    }
+
+    /// Adds the gradient of the squared error between the predicted and target win probability,
+    /// returning the error
+    pub fn backpropagate(
+        &self,
+        position: &TrainingPosition,
+        target: f32,
+        gradients: &mut [f32],
+    ) -> f32 {
+        let activations = self.forward(position);
+        let prediction = sigmoid(activations.output);
+        let error = prediction - target;
+        let output_gradient = 2.0 * error * prediction * (1.0 - prediction);
+
+        gradients[OUTPUT_BIAS] += output_gradient;
+
+        let (us, them) = position.perspectives();
+        let features: [Vec<usize>; 2] = [us.collect(), them.collect()];
+        for (side, accumulator) in activations.accumulators.iter().enumerate() {
+            let output_weights = OUTPUT_WEIGHTS + side * HIDDEN_SIZE;
+
+            let mut accumulator_gradients = [0.0; HIDDEN_SIZE];
+            for (index, value) in accumulator.iter().enumerate() {
+                let activation = value.clamp(0.0, 1.0);
+                gradients[output_weights + index] += output_gradient * activation * activation;
+
+                if *value > 0.0 && *value < 1.0 {
+                    accumulator_gradients[index] =
+                        output_gradient * self.parameters[output_weights + index] * 2.0 * value;
+                }
+            }
+
+            for (index, gradient) in accumulator_gradients.iter().enumerate() {
+                gradients[FEATURE_BIASES + index] += gradient;
+            }
+            for feature in &features[side] {
+                let weights = FEATURE_WEIGHTS + feature * HIDDEN_SIZE;
+                for (index, gradient) in accumulator_gradients.iter().enumerate() {
+                    gradients[weights + index] += gradient;
+                }
+            }
+        }
+
+        error * error
+    }
+
+    pub fn clip_weights(&mut self) {
+        for parameter in &mut self.parameters[FEATURE_WEIGHTS..FEATURE_BIASES] {
+            *parameter = parameter.clamp(-MAX_WEIGHT, MAX_WEIGHT);
+        }
+        for parameter in &mut self.parameters[FEATURE_BIASES..OUTPUT_WEIGHTS] {
+            *parameter = parameter.clamp(-MAX_FEATURE_BIAS, MAX_FEATURE_BIAS);
+        }
+        for parameter in &mut self.parameters[OUTPUT_WEIGHTS..OUTPUT_BIAS] {
+            *parameter = parameter.clamp(-MAX_WEIGHT, MAX_WEIGHT);
+        }
+    }
+
+    /// Rounds to the engine's quantised network, clipping weights and biases that are out of range
+    pub fn quantise(&self) -> Network {
+        let quantise = |value: f32, scale: i32, limit: i16| {
+            (value * scale as f32)
+                .round()
+                .clamp(-f32::from(limit), f32::from(limit)) as i16
+        };
+        let accumulator = |start: usize, scale: i32, limit: i16| {
+            let mut accumulator = Accumulator([0; HIDDEN_SIZE]);
+            for (quantised, value) in accumulator
+                .0
+                .iter_mut()
+                .zip(&self.parameters[start..start + HIDDEN_SIZE])
+            {
+                *quantised = quantise(*value, scale, limit);
+            }
+            accumulator
+        };
+
+        Network {
+            feature_weights: (0..INPUT_SIZE)
+                .map(|feature| {
+                    accumulator(
+                        FEATURE_WEIGHTS + feature * HIDDEN_SIZE,
+                        QA,
+                        quantised_limit(MAX_WEIGHT),
+                    )
+                })
+                .collect(),
+            feature_biases: accumulator(FEATURE_BIASES, QA, quantised_limit(MAX_FEATURE_BIAS)),
+            output_weights: [
+                accumulator(OUTPUT_WEIGHTS, QB, MAX_OUTPUT_WEIGHT),
+                accumulator(OUTPUT_WEIGHTS + HIDDEN_SIZE, QB, MAX_OUTPUT_WEIGHT),
+            ],
+            output_bias: quantise(self.parameters[OUTPUT_BIAS], QA * QB, i16::MAX),
+        }
+    }
+}
+
+/// Largest quantised feature weight or bias, from the largest unquantised one
+fn quantised_limit(limit: f32) -> i16 {
+    (limit * QA as f32).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use encrustant::board::Board;
+
+    use encrustant::evaluation::nnue::Network;
+
+    use super::{FloatNetwork, PARAMETER_COUNT};
+    use crate::nnue::data::parse_line;
+
+    const FENS: [&str; 3] = [
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ];
+
+    #[test]
+    fn quantised_network_matches() {
+        let network = FloatNetwork::random(7);
+        let quantised = network.quantise();
+        for fen in FENS {
+            let position = parse_line(&format!("{fen} [0.5]")).unwrap();
+            let float_evaluation = network.evaluate(&position);
+            let engine_evaluation = quantised.evaluate_board(&Board::from_fen(fen).unwrap());
+            // Rounding the weights loses a little precision
+            assert!(
+                (float_evaluation - engine_evaluation as f32).abs() < 15.0,
+                "{fen}: {float_evaluation} is not close to {engine_evaluation}"
+            );
+        }
+    }
+
+    #[test]
+    fn quantised_network_loads() {
+        let mut network = FloatNetwork::random(9);
+        for (index, parameter) in network.parameters.iter_mut().enumerate() {
+            *parameter = if index % 2 == 0 { 1000.0 } else { -1000.0 };
+        }
+        let bytes = network.quantise().to_bytes();
+        assert!(Network::from_bytes(&bytes).is_ok());
+
+        network.clip_weights();
+        assert_eq!(
+            Network::from_bytes(&network.quantise().to_bytes()),
+            Network::from_bytes(&bytes)
+        );
+    }
+
+    #[test]
+    fn gradients_match_finite_differences() {
+        let mut network = FloatNetwork::random(8);
+        let position = parse_line(&format!("{} [1.0]", FENS[1])).unwrap();
+        let target = 0.8;
+
+        let mut gradients = vec![0.0; PARAMETER_COUNT];
+        network.backpropagate(&position, target, &mut gradients);
+
+        // A spread of parameters, including some the position does not use
+        let mut checked = 0;
+        for index in (0..PARAMETER_COUNT).step_by(97) {
+            let original = network.parameters[index];
+            let step = 1e-3;
+            network.parameters[index] = original + step;
+            let above = network.backpropagate(&position, target, &mut vec![0.0; PARAMETER_COUNT]);
+            network.parameters[index] = original - step;
+            let below = network.backpropagate(&position, target, &mut vec![0.0; PARAMETER_COUNT]);
+            network.parameters[index] = original;
+
+            let numerical = (above - below) / (2.0 * step);
+            assert!(
+                (numerical - gradients[index]).abs() < 1e-3,
+                "parameter {index}: {numerical} is not close to {}",
+                gradients[index]
+            );
+            if gradients[index] != 0.0 {
+                checked += 1;
+            }
+        }
+        assert!(checked > 10);
+    }
+}
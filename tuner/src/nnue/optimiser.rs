@@ -0,0 +1,179 @@
+use std::fs;
+use std::path::Path;
+
+const BETA1: f32 = 0.9;
+const BETA2: f32 = 0.999;
+const EPSILON: f32 = 1e-8;
+
+/// How the learning rate changes over the epochs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LearningRateSchedule {
+    Constant,
+
+    /// Multiplies by `gamma` every `step` epochs
+    Step {
+        gamma: f32,
+        step: u32,
+    },
+
+    /// Follows half a cosine wave from the initial to the final learning rate
+    Cosine {
+        final_rate: f32,
+    },
+}
+
+impl LearningRateSchedule {
+    pub fn learning_rate(self, initial_rate: f32, epoch: u32, epochs: u32) -> f32 {
+        match self {
+            Self::Constant => initial_rate,
+            Self::Step { gamma, step } => initial_rate * gamma.powi((epoch / step.max(1)) as i32),
+            Self::Cosine { final_rate } => {
+                let progress = epoch as f32 / epochs.saturating_sub(1).max(1) as f32;
+                let cosine = f32::midpoint(1.0, (progress * core::f32::consts::PI).cos());
+                cosine.mul_add(initial_rate - final_rate, final_rate)
+            }
+        }
+    }
+}
+
+/// Adam with decoupled weight decay
+pub struct AdamW {
+    momentum: Vec<f32>,
+    velocity: Vec<f32>,
+    step: i32,
+    weight_decay: f32,
+}
+
+impl AdamW {
+    pub fn new(parameter_count: usize, weight_decay: f32) -> Self {
+        Self {
+            momentum: vec![0.0; parameter_count],
+            velocity: vec![0.0; parameter_count],
+            step: 0,
+            weight_decay,
+        }
+    }
+
+    pub fn update(&mut self, parameters: &mut [f32], gradients: &[f32], learning_rate: f32) {
+        self.step += 1;
+        let momentum_correction = 1.0 - BETA1.powi(self.step);
+        let velocity_correction = 1.0 - BETA2.powi(self.step);
+
+        for (((parameter, gradient), momentum), velocity) in parameters
+            .iter_mut()
+            .zip(gradients)
+            .zip(&mut self.momentum)
+            .zip(&mut self.velocity)
+        {
+            *momentum = BETA1.mul_add(*momentum, (1.0 - BETA1) * gradient);
+            *velocity = BETA2.mul_add(*velocity, (1.0 - BETA2) * gradient * gradient);
+
+            let momentum = *momentum / momentum_correction;
+            let velocity = *velocity / velocity_correction;
+            *parameter -= learning_rate
+                * self
+                    .weight_decay
+                    .mul_add(*parameter, momentum / (velocity.sqrt() + EPSILON));
+        }
+    }
+}
+
+/// Saves everything needed to carry on training, as little endian values:
+/// the number of completed epochs, the optimiser step and weight decay, then the parameters and optimiser state
+pub fn save_checkpoint(path: &Path, epoch: u32, parameters: &[f32], optimiser: &AdamW) {
+    let mut bytes = Vec::new();
+    bytes.extend(epoch.to_le_bytes());
+    bytes.extend(optimiser.step.to_le_bytes());
+    bytes.extend(optimiser.weight_decay.to_le_bytes());
+    for values in [parameters, &optimiser.momentum, &optimiser.velocity] {
+        for value in values {
+            bytes.extend(value.to_le_bytes());
+        }
+    }
+    fs::write(path, bytes)
+        .unwrap_or_else(|error| panic!("Failed to write {}: {error}", path.display()));
+}
+
+/// Training state read back from [`save_checkpoint`]
+pub struct Checkpoint {
+    pub epoch: u32,
+    pub parameters: Vec<f32>,
+    pub optimiser: AdamW,
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path, parameter_count: usize) -> Self {
+        let bytes = fs::read(path)
+            .unwrap_or_else(|error| panic!("Failed to read {}: {error}", path.display()));
+        assert_eq!(
+            bytes.len(),
+            12 + 3 * 4 * parameter_count,
+            "{} is not a checkpoint of this network size",
+            path.display()
+        );
+
+        let word = |index: usize| bytes[index * 4..index * 4 + 4].try_into().unwrap();
+        let values = |section: usize| {
+            (0..parameter_count)
+                .map(|index| f32::from_le_bytes(word(3 + section * parameter_count + index)))
+                .collect()
+        };
+        Self {
+            epoch: u32::from_le_bytes(word(0)),
+            parameters: values(0),
+            optimiser: AdamW {
+                momentum: values(1),
+                velocity: values(2),
+                step: i32::from_le_bytes(word(1)),
+                weight_decay: f32::from_le_bytes(word(2)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdamW, Checkpoint, LearningRateSchedule, save_checkpoint};
+
+    #[test]
+    fn schedules() {
+        assert_eq!(
+            LearningRateSchedule::Constant.learning_rate(0.01, 5, 10),
+            0.01
+        );
+
+        let step = LearningRateSchedule::Step {
+            gamma: 0.5,
+            step: 4,
+        };
+        assert_eq!(step.learning_rate(0.01, 3, 10), 0.01);
+        assert_eq!(step.learning_rate(0.01, 4, 10), 0.005);
+        assert_eq!(step.learning_rate(0.01, 9, 10), 0.0025);
+
+        let cosine = LearningRateSchedule::Cosine { final_rate: 0.001 };
+        assert_eq!(cosine.learning_rate(0.01, 0, 10), 0.01);
+        assert!((cosine.learning_rate(0.01, 9, 10) - 0.001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn checkpoint_round_trips() {
+        let mut optimiser = AdamW::new(3, 0.01);
+        let mut parameters = vec![0.5, -0.25, 1.0];
+        optimiser.update(&mut parameters, &[0.1, -0.2, 0.3], 0.001);
+
+        // Tests of other processes, such as another checkout, must not share the file
+        let path = std::env::temp_dir().join(format!(
+            "tuner_checkpoint_round_trips_{}.bin",
+            std::process::id()
+        ));
+        save_checkpoint(&path, 4, &parameters, &optimiser);
+        let loaded = Checkpoint::load(&path, 3);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.epoch, 4);
+        assert_eq!(loaded.parameters, parameters);
+        assert_eq!(loaded.optimiser.step, 1);
+        assert_eq!(loaded.optimiser.weight_decay, 0.01);
+        assert!(loaded.optimiser.momentum.iter().all(|value| *value != 0.0));
+    }
+}